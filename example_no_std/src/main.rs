@@ -71,6 +71,7 @@ fn rust_main() -> Result<(), i32> {
             DisconnectReason::TargetExited(_) => print_str("Target exited"),
             DisconnectReason::TargetTerminated(_) => print_str("Target halted"),
             DisconnectReason::Kill => print_str("GDB sent a kill command"),
+            DisconnectReason::StubShutdown => print_str("The stub shut down"),
         },
         Err(GdbStubError::TargetError(_e)) => {
             print_str("Target raised a fatal error");
@@ -145,7 +145,17 @@ fn main() -> DynResult<()> {
     let mut emu = emu::Emu::new(TEST_PROGRAM_ELF)?;
 
     let connection: Box<dyn ConnectionExt<Error = std::io::Error>> = {
-        if std::env::args().nth(1) == Some("--uds".to_string()) {
+        if std::env::args().nth(1) == Some("--stdio".to_string()) {
+            #[cfg(not(unix))]
+            {
+                return Err("stdio connections can only be used on Unix".into());
+            }
+            #[cfg(unix)]
+            {
+                // e.g: `(gdb) target remote | ./armv4t --stdio`
+                Box::new(gdbstub::conn::PipeConnection::stdio()?)
+            }
+        } else if std::env::args().nth(1) == Some("--uds".to_string()) {
             #[cfg(not(unix))]
             {
                 return Err("Unix Domain Sockets can only be used on Unix".into());
@@ -174,6 +184,7 @@ fn main() -> DynResult<()> {
                 println!("Target terminated with signal {}!", sig)
             }
             DisconnectReason::Kill => println!("GDB sent a kill command!"),
+            DisconnectReason::StubShutdown => println!("The stub shut down!"),
         },
         Err(GdbStubError::TargetError(e)) => {
             println!("target encountered a fatal error: {}", e)
@@ -174,6 +174,7 @@ fn main() -> DynResult<()> {
                 println!("Target terminated with signal {}!", sig)
             }
             DisconnectReason::Kill => println!("GDB sent a kill command!"),
+            DisconnectReason::StubShutdown => println!("The stub shut down!"),
         },
         Err(GdbStubError::TargetError(e)) => {
             println!("target encountered a fatal error: {}", e)
@@ -0,0 +1,36 @@
+//! Implementations for the 64-bit ARM architecture (AArch64).
+
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
+
+pub mod reg;
+
+/// Implements `Arch` for AArch64 (core registers + FP/SIMD).
+///
+/// The target description XML uses the `org.gnu.gdb.aarch64.core` and
+/// `org.gnu.gdb.aarch64.fpu` features.
+///
+/// System registers are not included in the default target description. See
+/// [`AArch64RegId::System`](reg::id::AArch64RegId::System) for details on
+/// how to expose them to the GDB client.
+pub enum AArch64 {}
+
+impl Arch for AArch64 {
+    type Usize = u64;
+    type Registers = reg::AArch64CoreRegs;
+    type RegId = reg::id::AArch64RegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>aarch64</architecture>"#,
+            include_str!("core.xml"),
+            include_str!("fpu.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
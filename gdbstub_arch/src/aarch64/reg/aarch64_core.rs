@@ -0,0 +1,124 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+/// AArch64 core registers (+ FP/SIMD registers).
+///
+/// Registers are serialized in GDB's register numbering order, as defined by
+/// the `org.gnu.gdb.aarch64.core` and `org.gnu.gdb.aarch64.fpu` features.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/aarch64-core.xml>
+/// Additionally: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/aarch64-fpu.xml>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AArch64CoreRegs {
+    /// General purpose registers (X0-X30)
+    pub x: [u64; 31],
+    /// Stack Pointer
+    pub sp: u64,
+    /// Program Counter
+    pub pc: u64,
+    /// Process State (GDB's `cpsr`)
+    pub cpsr: u32,
+    /// FP/SIMD registers (V0-V31)
+    pub v: [u128; 32],
+    /// Floating-point Status Register
+    pub fpsr: u32,
+    /// Floating-point Control Register
+    pub fpcr: u32,
+}
+
+impl Registers for AArch64CoreRegs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        for reg in &self.x {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.sp.to_le_bytes());
+        write_bytes!(&self.pc.to_le_bytes());
+        write_bytes!(&self.cpsr.to_le_bytes());
+
+        for reg in &self.v {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.fpsr.to_le_bytes());
+        write_bytes!(&self.fpcr.to_le_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 0x314 {
+            return Err(());
+        }
+
+        let mut regs = bytes[0..0x108]
+            .chunks_exact(8)
+            .map(|x| u64::from_le_bytes(x.try_into().unwrap()));
+
+        for reg in self.x.iter_mut() {
+            *reg = regs.next().ok_or(())?;
+        }
+        self.sp = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(())?;
+
+        self.cpsr = u32::from_le_bytes(bytes[0x108..0x10c].try_into().unwrap());
+
+        let mut regs = bytes[0x10c..0x30c]
+            .chunks_exact(0x10)
+            .map(|x| u128::from_le_bytes(x.try_into().unwrap()));
+
+        for reg in self.v.iter_mut() {
+            *reg = regs.next().ok_or(())?;
+        }
+
+        self.fpsr = u32::from_le_bytes(bytes[0x30c..0x310].try_into().unwrap());
+        self.fpcr = u32::from_le_bytes(bytes[0x310..0x314].try_into().unwrap());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aarch64_core_round_trip() {
+        let mut regs_before = AArch64CoreRegs {
+            sp: 0xffff_0000_1234_5678,
+            pc: 0x4000_0000,
+            cpsr: 0x6000_03c5,
+            fpsr: 0x0800_0010,
+            fpcr: 0x0300_0000,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.x.iter_mut().enumerate() {
+            *reg = 0x0101_0101_0101_0101 * i as u64;
+        }
+        for (i, reg) in regs_before.v.iter_mut().enumerate() {
+            *reg = (i as u128 + 1) << 120 | 0xf00d_0000 | i as u128;
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        let mut regs_after = AArch64CoreRegs::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+}
@@ -0,0 +1,151 @@
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+/// AArch64 register identifier.
+///
+/// Register numbers 0-67 follow GDB's numbering of the
+/// `org.gnu.gdb.aarch64.core` and `org.gnu.gdb.aarch64.fpu` features.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/aarch64-core.xml>
+/// Additionally: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/aarch64-fpu.xml>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AArch64RegId {
+    /// General purpose registers (X0-X30)
+    X(u8),
+    /// Stack Pointer
+    Sp,
+    /// Program Counter
+    Pc,
+    /// Process State (GDB's `cpsr`)
+    Cpsr,
+    /// FP/SIMD registers (V0-V31)
+    V(u8),
+    /// Floating-point Status Register
+    Fpsr,
+    /// Floating-point Control Register
+    Fpcr,
+    /// 64-bit System register, identified by its `MRS` / `MSR` encoding (see
+    /// [`AArch64RegId::sysreg`]).
+    ///
+    /// System registers are not part of the default target description. To
+    /// expose them to the GDB client, a target can append a feature to the
+    /// target description XML (via the `TargetDescriptionXmlOverride`
+    /// extension) which lists each register using its encoding as its
+    /// `regnum`. e.g: for `MIDR_EL1` (`op0=3, op1=0, CRn=0, CRm=0, op2=0`):
+    ///
+    /// ```xml
+    /// <feature name="org.gnu.gdb.aarch64.sysregs">
+    ///   <reg name="midr_el1" bitsize="64" regnum="49152" group="system"/>
+    /// </feature>
+    /// ```
+    ///
+    /// As `op0` is always `2` or `3` for system registers, encodings never
+    /// overlap with the core register numbers.
+    System(u32),
+}
+
+impl AArch64RegId {
+    /// Return the `System` register with the given `MRS` / `MSR` encoding.
+    ///
+    /// The encoding is packed as `op0:op1:CRn:CRm:op2` (bits 15:0).
+    pub const fn sysreg(op0: u8, op1: u8, crn: u8, crm: u8, op2: u8) -> AArch64RegId {
+        AArch64RegId::System(
+            ((op0 as u32 & 0b11) << 14)
+                | ((op1 as u32 & 0b111) << 11)
+                | ((crn as u32 & 0b1111) << 7)
+                | ((crm as u32 & 0b1111) << 3)
+                | (op2 as u32 & 0b111),
+        )
+    }
+}
+
+impl RegId for AArch64RegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        use self::AArch64RegId::*;
+
+        let (r, sz): (AArch64RegId, usize) = match id {
+            0..=30 => (X(id as u8), 8),
+            31 => (Sp, 8),
+            32 => (Pc, 8),
+            33 => (Cpsr, 4),
+            34..=65 => (V(id as u8 - 34), 16),
+            66 => (Fpsr, 4),
+            67 => (Fpcr, 4),
+            0x8000..=0xffff => (System(id as u32), 8),
+            _ => return None,
+        };
+
+        Some((r, Some(NonZeroUsize::new(sz)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    use super::AArch64RegId;
+    use crate::aarch64::reg::AArch64CoreRegs;
+
+    /// Check that each register returned by `from_raw_id` lines up with its
+    /// value in the data written by `gdb_serialize`.
+    #[test]
+    fn test_aarch64_numbering() {
+        let mut regs = AArch64CoreRegs {
+            sp: 0x1f1f_1f1f_1f1f_1f1f,
+            pc: 0x2020_2020_2020_2020,
+            cpsr: 0x2121_2121,
+            fpsr: 0x4242_4242,
+            fpcr: 0x4343_4343,
+            ..Default::default()
+        };
+        for (i, reg) in regs.x.iter_mut().enumerate() {
+            *reg = u64::from_le_bytes([i as u8; 8]);
+        }
+        for (i, reg) in regs.v.iter_mut().enumerate() {
+            *reg = u128::from_le_bytes([0x22 + i as u8; 16]);
+        }
+
+        let mut data = vec![];
+        regs.gdb_serialize(|x| data.push(x.unwrap()));
+
+        let mut offset = 0;
+        let mut i = 0;
+        while let Some((id, size)) = AArch64RegId::from_raw_id(i) {
+            let size = size.unwrap().get();
+            let expected = match id {
+                AArch64RegId::X(n) => regs.x[n as usize].to_le_bytes().to_vec(),
+                AArch64RegId::Sp => regs.sp.to_le_bytes().to_vec(),
+                AArch64RegId::Pc => regs.pc.to_le_bytes().to_vec(),
+                AArch64RegId::Cpsr => regs.cpsr.to_le_bytes().to_vec(),
+                AArch64RegId::V(n) => regs.v[n as usize].to_le_bytes().to_vec(),
+                AArch64RegId::Fpsr => regs.fpsr.to_le_bytes().to_vec(),
+                AArch64RegId::Fpcr => regs.fpcr.to_le_bytes().to_vec(),
+                AArch64RegId::System(_) => unreachable!(),
+            };
+            assert_eq!(&data[offset..offset + size], &expected[..], "{:?}", id);
+            offset += size;
+            i += 1;
+        }
+
+        assert_eq!(i, 68);
+        assert_eq!(offset, data.len());
+    }
+
+    #[test]
+    fn test_aarch64_sysreg() {
+        // MIDR_EL1
+        let midr = AArch64RegId::sysreg(3, 0, 0, 0, 0);
+        assert_eq!(midr, AArch64RegId::System(0xc000));
+        assert_eq!(AArch64RegId::from_raw_id(0xc000).unwrap().0, midr);
+
+        // TPIDR_EL0
+        let tpidr = AArch64RegId::sysreg(3, 3, 13, 0, 2);
+        assert_eq!(tpidr, AArch64RegId::System(0xde82));
+        assert_eq!(AArch64RegId::from_raw_id(0xde82).unwrap().0, tpidr);
+
+        assert!(AArch64RegId::from_raw_id(68).is_none());
+    }
+}
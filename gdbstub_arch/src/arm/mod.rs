@@ -48,3 +48,60 @@ impl Arch for Armv4t {
         SingleStepGdbBehavior::Optional
     }
 }
+
+/// Implements `Arch` for ARM M-profile (Cortex-M) cores without a floating
+/// point unit (e.g: Cortex-M0/M3/M4).
+///
+/// The target description XML uses the `org.gnu.gdb.arm.m-profile` and
+/// `org.gnu.gdb.arm.m-system` features.
+pub enum CortexM {}
+
+impl Arch for CortexM {
+    type Usize = u32;
+    type Registers = reg::CortexMRegs;
+    type RegId = reg::id::CortexMRegId;
+    type BreakpointKind = ArmBreakpointKind;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>arm</architecture>"#,
+            include_str!("m-profile.xml"),
+            include_str!("m-system.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Optional
+    }
+}
+
+/// Implements `Arch` for ARM M-profile (Cortex-M) cores with a single-precision
+/// floating point unit (e.g: Cortex-M4F/M7).
+///
+/// The target description XML uses the `org.gnu.gdb.arm.m-profile`,
+/// `org.gnu.gdb.arm.m-system`, and `org.gnu.gdb.arm.vfp` features.
+pub enum CortexMFpu {}
+
+impl Arch for CortexMFpu {
+    type Usize = u32;
+    type Registers = reg::CortexMFpuRegs;
+    type RegId = reg::id::CortexMFpuRegId;
+    type BreakpointKind = ArmBreakpointKind;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>arm</architecture>"#,
+            include_str!("m-profile.xml"),
+            include_str!("m-system.xml"),
+            include_str!("vfp.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Optional
+    }
+}
@@ -0,0 +1,229 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+/// ARM M-profile (Cortex-M) core + system registers.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-m-profile.xml>
+/// Additionally: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-m-system.xml>
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct CortexMRegs {
+    /// General purpose registers (R0-R12)
+    pub r: [u32; 13],
+    /// Stack Pointer (R13)
+    ///
+    /// This is the currently active stack pointer (i.e: either `msp` or
+    /// `psp`, depending on `control.SPSEL` and the current mode).
+    pub sp: u32,
+    /// Link Register (R14)
+    pub lr: u32,
+    /// Program Counter (R15)
+    pub pc: u32,
+    /// Combined Program Status Register (xPSR)
+    pub xpsr: u32,
+    /// Main Stack Pointer
+    pub msp: u32,
+    /// Process Stack Pointer
+    pub psp: u32,
+    /// Priority Mask Register
+    pub primask: u32,
+    /// Base Priority Mask Register
+    pub basepri: u32,
+    /// Fault Mask Register
+    pub faultmask: u32,
+    /// Control Register
+    pub control: u32,
+}
+
+/// Number of bytes in a serialized [`CortexMRegs`].
+const CORE_LEN: usize = 23 * 4;
+
+impl Registers for CortexMRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        for reg in self.r.iter() {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.sp.to_le_bytes());
+        write_bytes!(&self.lr.to_le_bytes());
+        write_bytes!(&self.pc.to_le_bytes());
+        write_bytes!(&self.xpsr.to_le_bytes());
+
+        write_bytes!(&self.msp.to_le_bytes());
+        write_bytes!(&self.psp.to_le_bytes());
+        write_bytes!(&self.primask.to_le_bytes());
+        write_bytes!(&self.basepri.to_le_bytes());
+        write_bytes!(&self.faultmask.to_le_bytes());
+        write_bytes!(&self.control.to_le_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != CORE_LEN {
+            return Err(());
+        }
+
+        let mut regs = bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()));
+
+        for reg in self.r.iter_mut() {
+            *reg = regs.next().ok_or(())?
+        }
+        self.sp = regs.next().ok_or(())?;
+        self.lr = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(())?;
+        self.xpsr = regs.next().ok_or(())?;
+
+        self.msp = regs.next().ok_or(())?;
+        self.psp = regs.next().ok_or(())?;
+        self.primask = regs.next().ok_or(())?;
+        self.basepri = regs.next().ok_or(())?;
+        self.faultmask = regs.next().ok_or(())?;
+        self.control = regs.next().ok_or(())?;
+
+        Ok(())
+    }
+}
+
+/// ARM M-profile (Cortex-M) core + system registers, with the single-precision
+/// VFPv4 / FPv5 floating point extension (e.g: Cortex-M4F, Cortex-M7).
+///
+/// GDB accesses the floating point registers as `d0-d15`, and synthesizes the
+/// `s0-s31` registers from them. Use [`CortexMFpuRegs::s`] and
+/// [`CortexMFpuRegs::set_s`] to access the single-precision registers
+/// directly.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-vfpv2.xml>
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct CortexMFpuRegs {
+    /// Core + system registers
+    pub core: CortexMRegs,
+    /// Double-precision floating point registers (D0-D15)
+    pub d: [u64; 16],
+    /// Floating-point Status and Control Register
+    pub fpscr: u32,
+}
+
+impl CortexMFpuRegs {
+    /// Return the value of single-precision register `s{n}`.
+    ///
+    /// `s{2i}` and `s{2i+1}` alias the low and high halves of `d{i}`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 31`.
+    pub fn s(&self, n: usize) -> u32 {
+        (self.d[n / 2] >> ((n % 2) * 32)) as u32
+    }
+
+    /// Set the value of single-precision register `s{n}`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 31`.
+    pub fn set_s(&mut self, n: usize, val: u32) {
+        let shift = (n % 2) * 32;
+        let d = &mut self.d[n / 2];
+        *d = (*d & !(0xffff_ffff << shift)) | ((val as u64) << shift);
+    }
+}
+
+impl Registers for CortexMFpuRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.core.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        self.core.gdb_serialize(&mut write_byte);
+
+        for reg in self.d.iter() {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.fpscr.to_le_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != CORE_LEN + 16 * 8 + 4 {
+            return Err(());
+        }
+
+        let (core, fpu) = bytes.split_at(CORE_LEN);
+        self.core.gdb_deserialize(core)?;
+
+        let mut regs = fpu[..0x80]
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+
+        for reg in self.d.iter_mut() {
+            *reg = regs.next().ok_or(())?
+        }
+        self.fpscr = u32::from_le_bytes(fpu[0x80..].try_into().unwrap());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cortex_m_fpu_round_trip() {
+        let mut regs_before = CortexMFpuRegs {
+            core: CortexMRegs {
+                r: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13],
+                sp: 0x2000_7ff0,
+                lr: 0xffff_fff9,
+                pc: 0x0800_0124,
+                xpsr: 0x6100_0000,
+                msp: 0x2000_7ff0,
+                psp: 0x2000_1000,
+                primask: 1,
+                basepri: 0x40,
+                faultmask: 0,
+                control: 0b110,
+            },
+            d: Default::default(),
+            fpscr: 0x0300_0000,
+        };
+        for n in 0..32 {
+            regs_before.set_s(n, 0x3f80_0000 + n as u32);
+        }
+        assert_eq!(regs_before.s(5), 0x3f80_0005);
+        assert_eq!(regs_before.d[2], 0x3f80_0005_3f80_0004);
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        let mut regs_after = CortexMFpuRegs::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+}
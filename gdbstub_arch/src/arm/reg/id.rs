@@ -36,3 +36,124 @@ impl RegId for ArmCoreRegId {
         Some((reg, Some(NonZeroUsize::new(4)?)))
     }
 }
+
+/// ARM M-profile (Cortex-M) core + system register identifier.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-m-profile.xml>
+/// Additionally: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-m-system.xml>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CortexMRegId {
+    /// General purpose registers (R0-R12)
+    Gpr(u8),
+    /// Stack Pointer (R13)
+    Sp,
+    /// Link Register (R14)
+    Lr,
+    /// Program Counter (R15)
+    Pc,
+    /// Combined Program Status Register (xPSR)
+    Xpsr,
+    /// Main Stack Pointer
+    Msp,
+    /// Process Stack Pointer
+    Psp,
+    /// Priority Mask Register
+    Primask,
+    /// Base Priority Mask Register
+    Basepri,
+    /// Fault Mask Register
+    Faultmask,
+    /// Control Register
+    Control,
+}
+
+impl RegId for CortexMRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        let reg = match id {
+            0..=12 => Self::Gpr(id as u8),
+            13 => Self::Sp,
+            14 => Self::Lr,
+            15 => Self::Pc,
+            16 => Self::Xpsr,
+            17 => Self::Msp,
+            18 => Self::Psp,
+            19 => Self::Primask,
+            20 => Self::Basepri,
+            21 => Self::Faultmask,
+            22 => Self::Control,
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(4)?)))
+    }
+}
+
+/// ARM M-profile (Cortex-M) register identifier, including the floating point
+/// extension registers.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-vfpv2.xml>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CortexMFpuRegId {
+    /// Core + system registers
+    Core(CortexMRegId),
+    /// Double-precision floating point registers (D0-D15)
+    ///
+    /// `D(i)` aliases the single-precision registers `s{2i}` and `s{2i+1}`.
+    D(u8),
+    /// Floating-point Status and Control Register
+    Fpscr,
+}
+
+impl RegId for CortexMFpuRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        let (reg, sz) = match id {
+            0..=22 => return CortexMRegId::from_raw_id(id).map(|(r, sz)| (Self::Core(r), sz)),
+            23..=38 => (Self::D(id as u8 - 23), 8),
+            39 => (Self::Fpscr, 4),
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(sz)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_cortex_m() {
+        test::<crate::arm::reg::CortexMRegs, crate::arm::reg::id::CortexMRegId>()
+    }
+
+    #[test]
+    fn test_cortex_m_fpu() {
+        test::<crate::arm::reg::CortexMFpuRegs, crate::arm::reg::id::CortexMFpuRegId>()
+    }
+}
@@ -0,0 +1,129 @@
+//! Implementations for the Atmel / Microchip AVR family of MCUs.
+//!
+//! AVR is a Harvard architecture, with separate flash, SRAM, and EEPROM
+//! address spaces. GDB folds these into a single 32-bit address space by
+//! offsetting each region (see [`AvrAddressSpace`]), so the addresses passed
+//! to `read_addrs` / `write_addrs` must be decoded before use.
+
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
+
+pub mod reg;
+
+/// Base of the SRAM (data) region in GDB's AVR address space.
+pub const AVR_SRAM_OFFSET: u32 = 0x0080_0000;
+/// Base of the EEPROM region in GDB's AVR address space.
+pub const AVR_EEPROM_OFFSET: u32 = 0x0081_0000;
+
+/// End (exclusive) of the EEPROM region in GDB's AVR address space.
+const AVR_EEPROM_END: u32 = 0x0082_0000;
+
+/// A decoded address in one of the AVR's memory spaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvrAddressSpace {
+    /// Byte address into program memory (flash).
+    Flash(u32),
+    /// Address into data memory (registers, I/O space, and SRAM).
+    Sram(u16),
+    /// Address into EEPROM.
+    Eeprom(u16),
+}
+
+impl AvrAddressSpace {
+    /// Decode an address sent by GDB (e.g: in `read_addrs` / `write_addrs`).
+    ///
+    /// Returns `None` for addresses past the end of the EEPROM region (e.g:
+    /// the fuse / lock / signature regions used by some toolchains).
+    pub fn from_gdb_addr(addr: u32) -> Option<AvrAddressSpace> {
+        let space = match addr {
+            0..=0x007f_ffff => AvrAddressSpace::Flash(addr),
+            AVR_SRAM_OFFSET..=0x0080_ffff => AvrAddressSpace::Sram((addr - AVR_SRAM_OFFSET) as u16),
+            AVR_EEPROM_OFFSET..=0x0081_ffff => {
+                AvrAddressSpace::Eeprom((addr - AVR_EEPROM_OFFSET) as u16)
+            }
+            _ => return None,
+        };
+        Some(space)
+    }
+
+    /// Encode the address into GDB's AVR address space.
+    ///
+    /// Flash addresses outside of the flash region are truncated.
+    pub fn to_gdb_addr(self) -> u32 {
+        match self {
+            AvrAddressSpace::Flash(addr) => addr & (AVR_SRAM_OFFSET - 1),
+            AvrAddressSpace::Sram(addr) => AVR_SRAM_OFFSET + addr as u32,
+            AvrAddressSpace::Eeprom(addr) => AVR_EEPROM_OFFSET + addr as u32,
+        }
+    }
+
+    /// Split a memory access of `len` bytes starting at `addr` at the point
+    /// where it crosses into the next address space.
+    ///
+    /// Returns the decoded start address, along with the number of bytes of
+    /// the access which fall into that address space.
+    pub fn from_gdb_range(addr: u32, len: usize) -> Option<(AvrAddressSpace, usize)> {
+        let space = AvrAddressSpace::from_gdb_addr(addr)?;
+        let end = match space {
+            AvrAddressSpace::Flash(_) => AVR_SRAM_OFFSET,
+            AvrAddressSpace::Sram(_) => AVR_EEPROM_OFFSET,
+            AvrAddressSpace::Eeprom(_) => AVR_EEPROM_END,
+        };
+        Some((space, len.min((end - addr) as usize)))
+    }
+}
+
+/// Implements `Arch` for AVR MCUs.
+///
+/// Memory addresses use GDB's AVR address space encoding. Use
+/// [`AvrAddressSpace::from_gdb_addr`] to decode them.
+pub enum Avr {}
+
+impl Arch for Avr {
+    type Usize = u32;
+    type Registers = reg::AvrCoreRegs;
+    type RegId = reg::id::AvrRegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(r#"<target version="1.0"><architecture>avr</architecture></target>"#)
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avr_address_space() {
+        use AvrAddressSpace::*;
+
+        assert_eq!(AvrAddressSpace::from_gdb_addr(0x1234), Some(Flash(0x1234)));
+        assert_eq!(AvrAddressSpace::from_gdb_addr(0x80_0100), Some(Sram(0x100)));
+        assert_eq!(
+            AvrAddressSpace::from_gdb_addr(0x81_0010),
+            Some(Eeprom(0x10))
+        );
+        assert_eq!(AvrAddressSpace::from_gdb_addr(0x82_0000), None);
+
+        for space in [Flash(0x7f_ffff), Sram(0xffff), Eeprom(0)] {
+            assert_eq!(
+                AvrAddressSpace::from_gdb_addr(space.to_gdb_addr()),
+                Some(space)
+            );
+        }
+
+        assert_eq!(
+            AvrAddressSpace::from_gdb_range(0x80_fffe, 4),
+            Some((Sram(0xfffe), 2))
+        );
+        assert_eq!(
+            AvrAddressSpace::from_gdb_range(0x80_0060, 4),
+            Some((Sram(0x60), 4))
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+/// AVR core registers.
+///
+/// GDB does not provide a XML file for the AVR. The register layout is taken
+/// from [avr-tdep.c](https://github.com/bminor/binutils-gdb/blob/master/gdb/avr-tdep.c).
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct AvrCoreRegs {
+    /// General purpose registers (R0-R31)
+    pub r: [u8; 32],
+    /// Status register
+    pub sreg: u8,
+    /// Stack pointer
+    pub sp: u16,
+    /// Program counter, as a byte address into flash
+    ///
+    /// Note that the hardware PC is a word address, and must be doubled before
+    /// being reported to GDB.
+    pub pc: u32,
+}
+
+impl Registers for AvrCoreRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        write_bytes!(&self.r);
+        write_byte(Some(self.sreg));
+        write_bytes!(&self.sp.to_le_bytes());
+        write_bytes!(&self.pc.to_le_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 39 {
+            return Err(());
+        }
+
+        self.r.copy_from_slice(&bytes[0..32]);
+        self.sreg = bytes[32];
+        self.sp = u16::from_le_bytes(bytes[33..35].try_into().unwrap());
+        self.pc = u32::from_le_bytes(bytes[35..39].try_into().unwrap());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avr_core_round_trip() {
+        let mut regs_before = AvrCoreRegs {
+            sreg: 0x82,
+            sp: 0x08ff,
+            pc: 0x0001_2344,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.r.iter_mut().enumerate() {
+            *reg = i as u8;
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        assert_eq!(data.len(), 39);
+
+        let mut regs_after = AvrCoreRegs::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+}
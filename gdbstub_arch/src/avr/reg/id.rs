@@ -0,0 +1,66 @@
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+/// AVR register identifier.
+///
+/// GDB does not provide a XML file for the AVR.
+/// The best file to reference is [avr-tdep.c](https://github.com/bminor/binutils-gdb/blob/master/gdb/avr-tdep.c).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AvrRegId {
+    /// General purpose registers (R0-R31)
+    Gpr(u8),
+    /// Status register
+    Sreg,
+    /// Stack pointer
+    Sp,
+    /// Program counter
+    Pc,
+}
+
+impl RegId for AvrRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        let (reg, size) = match id {
+            0..=31 => (AvrRegId::Gpr(id as u8), 1),
+            32 => (AvrRegId::Sreg, 1),
+            33 => (AvrRegId::Sp, 2),
+            34 => (AvrRegId::Pc, 4),
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(size)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_avr() {
+        test::<crate::avr::reg::AvrCoreRegs, crate::avr::reg::id::AvrRegId>()
+    }
+}
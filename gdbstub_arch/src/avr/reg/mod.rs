@@ -0,0 +1,8 @@
+//! `Register` structs for the AVR architecture.
+
+/// `RegId` definitions for the AVR architecture.
+pub mod id;
+
+mod avr;
+
+pub use avr::AvrCoreRegs;
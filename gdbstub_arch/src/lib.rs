@@ -49,16 +49,25 @@
 //! API without requiring an explicit breaking API change. Once all `RegIdImpl`
 //! have a default implementation, only a single breaking API change will be
 //! required to remove `RegIdImpl` entirely (along with this documentation).
+//!
+//! As of the addition of [`PowerPcRegId`](ppc::reg::id::PowerPcRegId), every
+//! built-in `Arch` implementation has a default `RegIdImpl`.
 
 #![cfg_attr(not(test), no_std)]
 #![deny(missing_docs)]
 
+pub mod aarch64;
 pub mod arm;
+pub mod avr;
+pub mod loongarch;
+pub mod m68k;
 pub mod mips;
 pub mod msp430;
 pub mod ppc;
 pub mod riscv;
+pub mod sh4;
 pub mod x86;
+pub mod xtensa;
 
 // used as part of intra-doc link
 #[allow(unused_imports)]
@@ -0,0 +1,32 @@
+//! Implementations for the LoongArch architecture.
+
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
+
+pub mod reg;
+
+/// Implements `Arch` for 64-bit LoongArch.
+///
+/// The target description XML uses the `org.gnu.gdb.loongarch.base` and
+/// `org.gnu.gdb.loongarch.fpu` features.
+pub enum LoongArch64 {}
+
+impl Arch for LoongArch64 {
+    type Usize = u64;
+    type Registers = reg::LoongArch64CoreRegs;
+    type RegId = reg::id::LoongArch64RegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>loongarch64</architecture>"#,
+            include_str!("xml/base64.xml"),
+            include_str!("xml/fpu.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Ignored
+    }
+}
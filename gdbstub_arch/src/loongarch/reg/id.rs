@@ -0,0 +1,84 @@
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+/// LoongArch64 register identifier.
+///
+/// Sources:
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/loongarch/base64.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/loongarch/fpu.xml>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LoongArch64RegId {
+    /// General purpose registers (r0-r31)
+    Gpr(u8),
+    /// Original value of `a0` on syscall entry
+    OrigA0,
+    /// Program counter
+    Pc,
+    /// Bad virtual address
+    Badv,
+    /// Floating point registers (f0-f31)
+    Fpr(u8),
+    /// Condition flag registers (fcc0-fcc7)
+    Fcc(u8),
+    /// Floating point control and status register
+    Fcsr,
+}
+
+impl RegId for LoongArch64RegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        use self::LoongArch64RegId::*;
+
+        let (reg, size) = match id {
+            0..=31 => (Gpr(id as u8), 8),
+            32 => (OrigA0, 8),
+            33 => (Pc, 8),
+            34 => (Badv, 8),
+            35..=66 => (Fpr((id - 35) as u8), 8),
+            67..=74 => (Fcc((id - 67) as u8), 1),
+            75 => (Fcsr, 4),
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(size)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_loongarch64() {
+        test::<
+            crate::loongarch::reg::LoongArch64CoreRegs,
+            crate::loongarch::reg::id::LoongArch64RegId,
+        >()
+    }
+}
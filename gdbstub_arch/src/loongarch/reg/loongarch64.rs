@@ -0,0 +1,119 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+/// LoongArch64 general purpose and floating point registers.
+///
+/// Sources:
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/loongarch/base64.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/loongarch/fpu.xml>
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct LoongArch64CoreRegs {
+    /// General purpose registers (r0-r31)
+    pub r: [u64; 32],
+    /// Original value of `a0` on syscall entry
+    pub orig_a0: u64,
+    /// Program counter
+    pub pc: u64,
+    /// Bad virtual address
+    pub badv: u64,
+    /// Floating point registers (f0-f31), stored as raw bits
+    pub f: [u64; 32],
+    /// Condition flag registers (fcc0-fcc7)
+    pub fcc: [u8; 8],
+    /// Floating point control and status register
+    pub fcsr: u32,
+}
+
+impl Registers for LoongArch64CoreRegs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        for reg in &self.r {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.orig_a0.to_le_bytes());
+        write_bytes!(&self.pc.to_le_bytes());
+        write_bytes!(&self.badv.to_le_bytes());
+
+        for reg in &self.f {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.fcc);
+        write_bytes!(&self.fcsr.to_le_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 35 * 8 + 32 * 8 + 8 + 4 {
+            return Err(());
+        }
+
+        let (regs, rest) = bytes.split_at(35 * 8 + 32 * 8);
+        let mut regs = regs
+            .chunks_exact(8)
+            .map(|x| u64::from_le_bytes(x.try_into().unwrap()));
+
+        for reg in self.r.iter_mut() {
+            *reg = regs.next().ok_or(())?;
+        }
+        self.orig_a0 = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(())?;
+        self.badv = regs.next().ok_or(())?;
+
+        for reg in self.f.iter_mut() {
+            *reg = regs.next().ok_or(())?;
+        }
+
+        let (fcc, fcsr) = rest.split_at(8);
+        self.fcc.copy_from_slice(fcc);
+        self.fcsr = u32::from_le_bytes(fcsr.try_into().unwrap());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loongarch64_round_trip() {
+        let mut regs_before = LoongArch64CoreRegs {
+            orig_a0: u64::MAX,
+            pc: 0x9000_0000_0020_0000,
+            badv: 0xdead_beef,
+            fcc: [0, 1, 0, 1, 1, 0, 0, 1],
+            fcsr: 0x0300,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.r.iter_mut().enumerate() {
+            *reg = 0x0101_0101_0101_0101 * i as u64;
+        }
+        for (i, reg) in regs_before.f.iter_mut().enumerate() {
+            *reg = (i as f64 * 1.5).to_bits();
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        let mut regs_after = LoongArch64CoreRegs::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+}
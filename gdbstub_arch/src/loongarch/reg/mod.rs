@@ -0,0 +1,8 @@
+//! `Register` structs for LoongArch architectures.
+
+/// `RegId` definitions for LoongArch architectures.
+pub mod id;
+
+mod loongarch64;
+
+pub use loongarch64::LoongArch64CoreRegs;
@@ -0,0 +1,59 @@
+//! Implementations for the Motorola 68000 (m68k) family of CPUs.
+
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
+
+pub mod reg;
+
+/// Implements `Arch` for Motorola 68000 family CPUs without a floating point
+/// unit (e.g: 68000, as found in the Sega Genesis / Amiga 500).
+///
+/// The target description XML uses the `org.gnu.gdb.m68k.core` feature.
+pub enum M68k {}
+
+impl Arch for M68k {
+    type Usize = u32;
+    type Registers = reg::M68kCoreRegs;
+    type RegId = reg::id::M68kRegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>m68k</architecture>"#,
+            include_str!("core.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
+
+/// Implements `Arch` for Motorola 68000 family CPUs with a 68881 / 68882 (or
+/// integrated) floating point unit.
+///
+/// The target description XML uses the `org.gnu.gdb.m68k.core` and
+/// `org.gnu.gdb.coldfire.fp` features.
+pub enum M68kFpu {}
+
+impl Arch for M68kFpu {
+    type Usize = u32;
+    type Registers = reg::M68kFpuRegs;
+    type RegId = reg::id::M68kFpuRegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>m68k</architecture>"#,
+            include_str!("core.xml"),
+            include_str!("fpu.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
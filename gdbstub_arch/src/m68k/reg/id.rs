@@ -0,0 +1,103 @@
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+/// Motorola 68000 family core register identifier.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/m68k-core.xml>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum M68kRegId {
+    /// Data registers (D0-D7)
+    D(u8),
+    /// Address registers (A0-A7)
+    A(u8),
+    /// Status register (`ps`)
+    Sr,
+    /// Program counter
+    Pc,
+}
+
+impl RegId for M68kRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        let reg = match id {
+            0..=7 => Self::D(id as u8),
+            8..=15 => Self::A(id as u8 - 8),
+            16 => Self::Sr,
+            17 => Self::Pc,
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(4)?)))
+    }
+}
+
+/// Motorola 68000 family core + FPU register identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum M68kFpuRegId {
+    /// Core registers
+    Core(M68kRegId),
+    /// Floating point registers (FP0-FP7)
+    Fp(u8),
+    /// Floating point control register
+    Fpcr,
+    /// Floating point status register
+    Fpsr,
+    /// Floating point instruction address register
+    Fpiar,
+}
+
+impl RegId for M68kFpuRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        let (reg, sz) = match id {
+            0..=17 => return M68kRegId::from_raw_id(id).map(|(r, sz)| (Self::Core(r), sz)),
+            18..=25 => (Self::Fp(id as u8 - 18), 12),
+            26 => (Self::Fpcr, 4),
+            27 => (Self::Fpsr, 4),
+            28 => (Self::Fpiar, 4),
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(sz)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_m68k() {
+        test::<crate::m68k::reg::M68kCoreRegs, crate::m68k::reg::id::M68kRegId>()
+    }
+
+    #[test]
+    fn test_m68k_fpu() {
+        test::<crate::m68k::reg::M68kFpuRegs, crate::m68k::reg::id::M68kFpuRegId>()
+    }
+}
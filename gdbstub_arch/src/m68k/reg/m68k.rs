@@ -0,0 +1,175 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+/// Motorola 68000 family core registers.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/m68k-core.xml>
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct M68kCoreRegs {
+    /// Data registers (D0-D7)
+    pub d: [u32; 8],
+    /// Address registers (A0-A7)
+    ///
+    /// GDB refers to `a6` as `fp` and `a7` as `sp`.
+    pub a: [u32; 8],
+    /// Status register (`ps`)
+    pub sr: u32,
+    /// Program counter
+    pub pc: u32,
+}
+
+/// Number of bytes in a serialized [`M68kCoreRegs`].
+const CORE_LEN: usize = 18 * 4;
+
+impl Registers for M68kCoreRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        for reg in self.d.iter().chain(self.a.iter()) {
+            write_bytes!(&reg.to_be_bytes());
+        }
+        write_bytes!(&self.sr.to_be_bytes());
+        write_bytes!(&self.pc.to_be_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != CORE_LEN {
+            return Err(());
+        }
+
+        let mut regs = bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()));
+
+        for reg in self.d.iter_mut().chain(self.a.iter_mut()) {
+            *reg = regs.next().ok_or(())?
+        }
+        self.sr = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(())?;
+
+        Ok(())
+    }
+}
+
+/// Motorola 68000 family core registers, with a 68881 / 68882 (or 68040+
+/// integrated) floating point unit.
+///
+/// GDB has no standard feature describing the 68881 FPU, so the target XML
+/// uses the `org.gnu.gdb.coldfire.fp` feature with 96-bit registers, which GDB
+/// interprets as 68881 extended precision values.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct M68kFpuRegs {
+    /// Core registers
+    pub core: M68kCoreRegs,
+    /// Floating point registers (FP0-FP7), stored as raw 96-bit extended
+    /// precision values in big-endian byte order
+    pub fp: [[u8; 12]; 8],
+    /// Floating point control register
+    pub fpcr: u32,
+    /// Floating point status register
+    pub fpsr: u32,
+    /// Floating point instruction address register
+    pub fpiar: u32,
+}
+
+impl Registers for M68kFpuRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.core.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        self.core.gdb_serialize(&mut write_byte);
+
+        for reg in self.fp.iter() {
+            write_bytes!(reg);
+        }
+        write_bytes!(&self.fpcr.to_be_bytes());
+        write_bytes!(&self.fpsr.to_be_bytes());
+        write_bytes!(&self.fpiar.to_be_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != CORE_LEN + 8 * 12 + 3 * 4 {
+            return Err(());
+        }
+
+        let (core, fpu) = bytes.split_at(CORE_LEN);
+        self.core.gdb_deserialize(core)?;
+
+        let (fp, ctrl) = fpu.split_at(8 * 12);
+        for (reg, b) in self.fp.iter_mut().zip(fp.chunks_exact(12)) {
+            reg.copy_from_slice(b);
+        }
+
+        let mut regs = ctrl
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()));
+
+        self.fpcr = regs.next().ok_or(())?;
+        self.fpsr = regs.next().ok_or(())?;
+        self.fpiar = regs.next().ok_or(())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn m68k_fpu_round_trip() {
+        let mut regs_before = M68kFpuRegs {
+            core: M68kCoreRegs {
+                d: [0, 1, 2, 3, 4, 5, 6, 7],
+                a: [8, 9, 10, 11, 12, 13, 14, 0x00ff_fffc],
+                sr: 0x2700,
+                pc: 0x0000_0200,
+            },
+            fpcr: 0x10,
+            fpsr: 0x20,
+            fpiar: 0x0000_0400,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.fp.iter_mut().enumerate() {
+            *reg = [i as u8; 12];
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        // big-endian pc
+        assert_eq!(data[68..72], [0x00, 0x00, 0x02, 0x00]);
+
+        let mut regs_after = M68kFpuRegs::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+}
@@ -0,0 +1,9 @@
+//! `Register` structs for Motorola 68000 family CPUs.
+
+/// `RegId` definitions for Motorola 68000 family CPUs.
+pub mod id;
+
+mod m68k;
+
+pub use m68k::M68kCoreRegs;
+pub use m68k::M68kFpuRegs;
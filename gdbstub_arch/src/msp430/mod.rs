@@ -4,6 +4,28 @@ use gdbstub::arch::{Arch, SingleStepGdbBehavior};
 
 pub mod reg;
 
+/// MSP430-specific breakpoint kinds.
+///
+/// GDB uses the 16-bit `0x4343` instruction for software breakpoints on both
+/// the MSP430 and MSP430X, and therefore always requests breakpoints of kind
+/// 2. [`Msp430X`] uses this type, so that any other (invalid) kind is rejected
+/// before reaching the target.
+#[derive(Debug)]
+pub enum Msp430BreakpointKind {
+    /// 16-bit breakpoint instruction.
+    Insn16,
+}
+
+impl gdbstub::arch::BreakpointKind for Msp430BreakpointKind {
+    fn from_usize(kind: usize) -> Option<Self> {
+        let kind = match kind {
+            2 => Msp430BreakpointKind::Insn16,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
 /// Implements `Arch` for standard 16-bit TI-MSP430 MCUs.
 pub struct Msp430 {}
 
@@ -24,13 +46,20 @@ impl Arch for Msp430 {
 }
 
 /// Implements `Arch` for 20-bit TI-MSP430 MCUs (CPUX).
+///
+/// Registers are exchanged with GDB as 32-bit values (see
+/// [`Msp430Regs`](reg::Msp430Regs)).
+///
+/// _Note:_ GDB's msp430 support selects the register layout based on the
+/// architecture name alone (it never consults the target description's
+/// features), so the target XML only reports the architecture.
 pub struct Msp430X {}
 
 impl Arch for Msp430X {
     type Usize = u32;
     type Registers = reg::Msp430Regs<u32>;
     type RegId = reg::id::Msp430RegId<u32>;
-    type BreakpointKind = usize;
+    type BreakpointKind = Msp430BreakpointKind;
 
     fn target_description_xml() -> Option<&'static str> {
         Some(r#"<target version="1.0"><architecture>msp430x</architecture></target>"#)
@@ -7,6 +7,9 @@ use gdbstub::internal::LeBytes;
 ///
 /// The register width is set based on the `<U>` type. For 16-bit MSP430 CPUs
 /// this should be `u16` and for 20-bit MSP430 CPUs (CPUX) this should be `u32`.
+///
+/// When using `u32`, each register is sent to GDB as a 32-bit little-endian
+/// value, with the upper 12 bits always cleared.
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct Msp430Regs<U> {
     /// Program Counter (R0)
@@ -19,6 +22,15 @@ pub struct Msp430Regs<U> {
     pub r: [U; 12],
 }
 
+/// Mask of the bits which are actually backed by hardware: all 16 bits on the
+/// MSP430, and the low 20 bits on the MSP430X.
+fn reg_mask<U: PrimInt>() -> U {
+    match core::mem::size_of::<U>() {
+        2 => U::max_value(),
+        _ => U::from(0x000f_ffffu32).unwrap(),
+    }
+}
+
 impl<U> Registers for Msp430Regs<U>
 where
     U: PrimInt + LeBytes + Default + core::fmt::Debug,
@@ -42,12 +54,14 @@ where
             };
         }
 
-        write_le_bytes!(&self.pc);
-        write_le_bytes!(&self.sp);
-        write_le_bytes!(&self.sr);
+        let mask = reg_mask::<U>();
+
+        write_le_bytes!(&(self.pc & mask));
+        write_le_bytes!(&(self.sp & mask));
+        write_le_bytes!(&(self.sr & mask));
         (0..core::mem::size_of::<U>()).for_each(|_| write_byte(None)); // Constant Generator (CG/R3)
         for reg in self.r.iter() {
-            write_le_bytes!(&reg);
+            write_le_bytes!(&(*reg & mask));
         }
     }
 
@@ -59,9 +73,10 @@ where
             return Err(());
         }
 
+        let mask = reg_mask::<U>();
         let mut regs = bytes
             .chunks_exact(ptrsize)
-            .map(|c| U::from_le_bytes(c).unwrap());
+            .map(|c| U::from_le_bytes(c).unwrap() & mask);
 
         self.pc = regs.next().ok_or(())?;
         self.sp = regs.next().ok_or(())?;
@@ -83,3 +98,33 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msp430x_20bit_layout() {
+        let regs = Msp430Regs::<u32> {
+            pc: 0x1_4400,
+            sp: 0xf_fffe,
+            sr: 0x0008,
+            r: [0xdead_beef; 12],
+        };
+
+        let mut data = vec![];
+        regs.gdb_serialize(|x| data.push(x.unwrap_or(0)));
+
+        assert_eq!(data.len(), 16 * 4);
+        assert_eq!(data[0..4], [0x00, 0x44, 0x01, 0x00]);
+        // upper 12 bits are cleared
+        assert_eq!(data[16..20], [0xef, 0xbe, 0x0d, 0x00]);
+
+        let mut regs_after = Msp430Regs::<u32>::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_after.pc, regs.pc);
+        assert_eq!(regs_after.sp, regs.sp);
+        assert_eq!(regs_after.r, [0x000d_beef; 12]);
+    }
+}
@@ -8,7 +8,7 @@ pub mod reg;
 ///
 /// Check out the [module level docs](gdbstub::arch#whats-with-regidimpl) for
 /// more info about the `RegIdImpl` type parameter.
-pub enum PowerPcAltivec32<RegIdImpl: RegId> {
+pub enum PowerPcAltivec32<RegIdImpl: RegId = reg::id::PowerPcRegId> {
     #[doc(hidden)]
     _Marker(core::marker::PhantomData<RegIdImpl>),
 }
@@ -30,3 +30,59 @@ impl<RegIdImpl: RegId> Arch for PowerPcAltivec32<RegIdImpl> {
         SingleStepGdbBehavior::Required
     }
 }
+
+macro_rules! ppc64_target_xml {
+    () => {
+        concat!(
+            r#"<target version="1.0"><architecture>powerpc:common64</architecture>"#,
+            include_str!("xml/power64-core.xml"),
+            include_str!("xml/power-fpu.xml"),
+            include_str!("xml/power-altivec.xml"),
+            include_str!("xml/power-vsx.xml"),
+            "</target>"
+        )
+    };
+}
+
+/// Implements `Arch` for big-endian 64-bit PowerPC + AltiVec SIMD + VSX.
+pub enum PowerPc64 {}
+
+impl Arch for PowerPc64 {
+    type Usize = u64;
+    type Registers = reg::PowerPc64Regs;
+    type RegId = reg::id::PowerPcRegId<u64>;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(ppc64_target_xml!())
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
+
+/// Implements `Arch` for little-endian 64-bit PowerPC (ppc64le) + AltiVec SIMD
+/// + VSX.
+///
+/// The target description XML does not specify an endianness, so GDB must
+/// either infer it from the loaded ELF file, or be told via `set endian
+/// little`.
+pub enum PowerPc64le {}
+
+impl Arch for PowerPc64le {
+    type Usize = u64;
+    type Registers = reg::PowerPc64Regs<true>;
+    type RegId = reg::id::PowerPcRegId<u64>;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(ppc64_target_xml!())
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
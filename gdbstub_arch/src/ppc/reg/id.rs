@@ -1,2 +1,129 @@
-// TODO: Add proper `RegId` implementation. See [issue #29](https://github.com/daniel5151/gdbstub/issues/29)
-// pub enum PowerPc32RegId {}
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+/// PowerPC register identifier.
+///
+/// The GPR / `pc` / `msr` / `lr` / `ctr` width is set to `u32` or `u64` based
+/// on the `<U>` type. The `VsHi` registers are only available on 64-bit
+/// PowerPC.
+///
+/// Sources:
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-core.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power64-core.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-fpu.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-altivec.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-vsx.xml>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PowerPcRegId<U = u32> {
+    /// General purpose registers (r0-r31)
+    Gpr(u8),
+    /// Floating point registers (f0-f31)
+    Fpr(u8),
+    /// Program counter
+    Pc,
+    /// Machine state
+    Msr,
+    /// Condition register
+    Cr,
+    /// Link register
+    Lr,
+    /// Count register
+    Ctr,
+    /// Integer exception register
+    Xer,
+    /// Floating-point status and control register
+    Fpscr,
+    /// Vector registers (vr0-vr31)
+    Vr(u8),
+    /// Vector status and control register
+    Vscr,
+    /// Vector context save register
+    Vrsave,
+    /// Upper 64 bits of the VSX registers vs0-vs31 (vs0h-vs31h)
+    ///
+    /// The lower 64 bits of vs0-vs31 alias the floating point registers.
+    VsHi(u8),
+
+    #[doc(hidden)]
+    _Marker(core::marker::PhantomData<U>),
+}
+
+fn from_raw_id<U>(id: usize, vsx: bool) -> Option<(PowerPcRegId<U>, Option<NonZeroUsize>)> {
+    use self::PowerPcRegId::*;
+
+    let ptrsize = core::mem::size_of::<U>();
+
+    let (r, sz) = match id {
+        0..=31 => (Gpr(id as u8), ptrsize),
+        32..=63 => (Fpr(id as u8 - 32), 8),
+        64 => (Pc, ptrsize),
+        65 => (Msr, ptrsize),
+        66 => (Cr, 4),
+        67 => (Lr, ptrsize),
+        68 => (Ctr, ptrsize),
+        69 => (Xer, 4),
+        70 => (Fpscr, 4),
+        71..=102 => (Vr(id as u8 - 71), 16),
+        103 => (Vscr, 4),
+        104 => (Vrsave, 4),
+        105..=136 if vsx => (VsHi(id as u8 - 105), 8),
+        _ => return None,
+    };
+
+    Some((r, Some(NonZeroUsize::new(sz)?)))
+}
+
+impl RegId for PowerPcRegId<u32> {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        from_raw_id::<u32>(id, false)
+    }
+}
+
+impl RegId for PowerPcRegId<u64> {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        from_raw_id::<u64>(id, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_powerpc32() {
+        test::<crate::ppc::reg::PowerPcCommonRegs, crate::ppc::reg::id::PowerPcRegId>()
+    }
+
+    #[test]
+    fn test_powerpc64() {
+        test::<crate::ppc::reg::PowerPc64Regs, crate::ppc::reg::id::PowerPcRegId<u64>>()
+    }
+}
@@ -4,6 +4,8 @@
 pub mod id;
 
 mod common;
+mod ppc64;
 
 pub use common::PowerPcCommonRegs;
+pub use ppc64::PowerPc64Regs;
 type PpcVector = u128;
@@ -0,0 +1,200 @@
+use gdbstub::arch::Registers;
+
+use super::PpcVector;
+
+use core::convert::TryInto;
+
+/// 64-bit PowerPC core registers, FPU registers, AltiVec SIMD registers, and
+/// VSX registers.
+///
+/// Registers are serialized in big-endian byte order by default. Set
+/// `LITTLE_ENDIAN` to `true` for little-endian (ppc64le) targets.
+///
+/// Sources:
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power64-core.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-fpu.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-altivec.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/rs6000/power-vsx.xml>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PowerPc64Regs<const LITTLE_ENDIAN: bool = false> {
+    /// General purpose registers
+    pub r: [u64; 32],
+    /// Floating Point registers
+    pub f: [f64; 32],
+    /// Program counter
+    pub pc: u64,
+    /// Machine state
+    pub msr: u64,
+    /// Condition register
+    pub cr: u32,
+    /// Link register
+    pub lr: u64,
+    /// Count register
+    pub ctr: u64,
+    /// Integer exception register
+    pub xer: u32,
+    /// Floating-point status and control register
+    pub fpscr: u32,
+    /// Vector registers
+    pub vr: [PpcVector; 32],
+    /// Vector status and control register
+    pub vscr: u32,
+    /// Vector context save register
+    pub vrsave: u32,
+    /// Upper 64 bits of the VSX registers vs0-vs31
+    ///
+    /// The lower 64 bits of vs0-vs31 alias the floating point registers.
+    pub vsh: [u64; 32],
+}
+
+impl<const LITTLE_ENDIAN: bool> Registers for PowerPc64Regs<LITTLE_ENDIAN> {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($val:expr) => {
+                let bytes = if LITTLE_ENDIAN {
+                    $val.to_le_bytes()
+                } else {
+                    $val.to_be_bytes()
+                };
+                for b in &bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        macro_rules! write_regs {
+            ($($reg:ident),*) => {
+                $(
+                    write_bytes!(self.$reg);
+                )*
+            }
+        }
+
+        for reg in &self.r {
+            write_bytes!(reg);
+        }
+
+        for reg in &self.f {
+            write_bytes!(reg);
+        }
+
+        write_regs!(pc, msr, cr, lr, ctr, xer, fpscr);
+
+        for &reg in &self.vr {
+            let reg: u128 = reg;
+            write_bytes!(reg);
+        }
+
+        write_regs!(vscr, vrsave);
+
+        for reg in &self.vsh {
+            write_bytes!(reg);
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 0x534 {
+            return Err(());
+        }
+
+        macro_rules! parse {
+            ($ty:ty, $bytes:expr) => {{
+                let bytes = $bytes.try_into().unwrap();
+                if LITTLE_ENDIAN {
+                    <$ty>::from_le_bytes(bytes)
+                } else {
+                    <$ty>::from_be_bytes(bytes)
+                }
+            }};
+        }
+
+        for (reg, b) in self.r.iter_mut().zip(bytes[0..0x100].chunks_exact(8)) {
+            *reg = parse!(u64, b);
+        }
+
+        for (reg, b) in self.f.iter_mut().zip(bytes[0x100..0x200].chunks_exact(8)) {
+            *reg = parse!(f64, b);
+        }
+
+        self.pc = parse!(u64, &bytes[0x200..0x208]);
+        self.msr = parse!(u64, &bytes[0x208..0x210]);
+        self.cr = parse!(u32, &bytes[0x210..0x214]);
+        self.lr = parse!(u64, &bytes[0x214..0x21c]);
+        self.ctr = parse!(u64, &bytes[0x21c..0x224]);
+        self.xer = parse!(u32, &bytes[0x224..0x228]);
+        self.fpscr = parse!(u32, &bytes[0x228..0x22c]);
+
+        for (reg, b) in self
+            .vr
+            .iter_mut()
+            .zip(bytes[0x22c..0x42c].chunks_exact(0x10))
+        {
+            *reg = parse!(u128, b);
+        }
+
+        self.vscr = parse!(u32, &bytes[0x42c..0x430]);
+        self.vrsave = parse!(u32, &bytes[0x430..0x434]);
+
+        for (reg, b) in self.vsh.iter_mut().zip(bytes[0x434..0x534].chunks_exact(8)) {
+            *reg = parse!(u64, b);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<const LITTLE_ENDIAN: bool>() -> Vec<u8> {
+        let mut regs_before = PowerPc64Regs::<LITTLE_ENDIAN> {
+            pc: 0xc000_0000_0000_0100,
+            msr: 0x8000_0000_0000_1033,
+            cr: 0x2400_0482,
+            lr: 0xc000_0000_0001_2345,
+            ctr: 6,
+            xer: 0x2000_0000,
+            fpscr: 8,
+            f: [9.5; 32],
+            vr: [0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10; 32],
+            vscr: 0x0001_0000,
+            vrsave: 0xffff_ffff,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.r.iter_mut().enumerate() {
+            *reg = 0x0101_0101_0101_0101 * i as u64;
+        }
+        for (i, reg) in regs_before.vsh.iter_mut().enumerate() {
+            *reg = 0xaa00 + i as u64;
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        let mut regs_after = PowerPc64Regs::<LITTLE_ENDIAN>::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+        data
+    }
+
+    #[test]
+    fn ppc64_round_trip() {
+        let be = round_trip::<false>();
+        let le = round_trip::<true>();
+
+        // pc
+        assert_eq!(be[0x200..0x208], [0xc0, 0, 0, 0, 0, 0, 0x01, 0]);
+        assert_eq!(le[0x200..0x208], [0, 0x01, 0, 0, 0, 0, 0, 0xc0]);
+    }
+}
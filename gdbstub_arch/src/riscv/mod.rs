@@ -1,6 +1,10 @@
 //! Implementations for the [RISC-V](https://riscv.org/) architecture.
 //!
-//! *Note*: currently only supports integer versions of the ISA.
+//! - [`Riscv32`] / [`Riscv64`] only support the integer registers.
+//! - [`Riscv32F`] / [`Riscv32D`] / [`Riscv64F`] / [`Riscv64D`] additionally
+//!   support the floating point registers, a described set of CSRs (see
+//!   [`RiscvCsrRegs`](reg::RiscvCsrRegs)), and (optionally) the vector
+//!   registers.
 
 use gdbstub::arch::{Arch, SingleStepGdbBehavior};
 
@@ -43,3 +47,84 @@ impl Arch for Riscv64 {
         SingleStepGdbBehavior::Ignored
     }
 }
+
+macro_rules! impl_riscv_fpu_arch {
+    (
+        $(#[$meta:meta])*
+        $arch:ident, $usize:ty, $flen:ty, $xlen:literal, $flen_bits:literal
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Vector registers are supported by setting `VLENB` (i.e: `VLEN / 8`)
+        /// to one of `16`, `32`, `64`, or `128`. The default `VLENB` of `0`
+        /// disables the vector registers.
+        pub enum $arch<const VLENB: usize = 0> {}
+
+        impl_riscv_fpu_arch!(@impl $arch, $usize, $flen, 0, concat!(
+            include_str!(concat!("xml/cpu", $xlen, ".xml")),
+            include_str!(concat!("xml/fpu", $flen_bits, ".xml")),
+            include_str!(concat!("xml/csr", $xlen, ".xml")),
+        ), $xlen);
+
+        impl_riscv_fpu_arch!(@vector $arch, $usize, $flen, $xlen, $flen_bits, 16, 32, 64, 128);
+    };
+
+    (@vector $arch:ident, $usize:ty, $flen:ty, $xlen:literal, $flen_bits:literal, $($vlenb:literal),*) => {
+        $(
+            impl_riscv_fpu_arch!(@impl $arch, $usize, $flen, $vlenb, concat!(
+                include_str!(concat!("xml/cpu", $xlen, ".xml")),
+                include_str!(concat!("xml/fpu", $flen_bits, ".xml")),
+                include_str!(concat!("xml/csr", $xlen, "v.xml")),
+                include_str!(concat!("xml/vector", $vlenb, ".xml")),
+            ), $xlen);
+        )*
+    };
+
+    (@impl $arch:ident, $usize:ty, $flen:ty, $vlenb:literal, $features:expr, $xlen:literal) => {
+        impl Arch for $arch<$vlenb> {
+            type Usize = $usize;
+            type Registers = reg::RiscvRegs<$usize, $flen, $vlenb>;
+            type RegId = reg::id::RiscvRegId<$usize, $flen, $vlenb>;
+            type BreakpointKind = usize;
+
+            fn target_description_xml() -> Option<&'static str> {
+                Some(concat!(
+                    r#"<target version="1.0"><architecture>riscv:rv"#,
+                    $xlen,
+                    "</architecture>",
+                    $features,
+                    "</target>"
+                ))
+            }
+
+            #[inline(always)]
+            fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+                SingleStepGdbBehavior::Ignored
+            }
+        }
+    };
+}
+
+impl_riscv_fpu_arch! {
+    /// Implements `Arch` for 32-bit RISC-V with single-precision floating point
+    /// (F extension).
+    Riscv32F, u32, u32, "32", "32"
+}
+
+impl_riscv_fpu_arch! {
+    /// Implements `Arch` for 32-bit RISC-V with double-precision floating point
+    /// (D extension).
+    Riscv32D, u32, u64, "32", "64"
+}
+
+impl_riscv_fpu_arch! {
+    /// Implements `Arch` for 64-bit RISC-V with single-precision floating point
+    /// (F extension).
+    Riscv64F, u64, u32, "64", "32"
+}
+
+impl_riscv_fpu_arch! {
+    /// Implements `Arch` for 64-bit RISC-V with double-precision floating point
+    /// (D extension).
+    Riscv64D, u64, u64, "64", "64"
+}
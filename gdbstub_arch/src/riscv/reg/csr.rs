@@ -0,0 +1,131 @@
+/// RISC-V Control and Status Registers.
+///
+/// Includes the set of Machine and Supervisor-level CSRs described in the
+/// target description XML of the RISC-V arches with floating point support
+/// (e.g: [`Riscv64D`](crate::riscv::Riscv64D)).
+///
+/// _Note:_ On RV32, only the low 32 bits of the `cycle`, `time`, and `instret`
+/// counters are described.
+///
+/// Useful links:
+/// * [RISC-V Privileged Architecture](https://github.com/riscv/riscv-isa-manual/releases)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RiscvCsrRegs<U> {
+    /// Supervisor status register
+    pub sstatus: U,
+    /// Supervisor interrupt-enable register
+    pub sie: U,
+    /// Supervisor trap handler base address
+    pub stvec: U,
+    /// Supervisor counter enable
+    pub scounteren: U,
+    /// Scratch register for supervisor trap handlers
+    pub sscratch: U,
+    /// Supervisor exception program counter
+    pub sepc: U,
+    /// Supervisor trap cause
+    pub scause: U,
+    /// Supervisor bad address or instruction
+    pub stval: U,
+    /// Supervisor interrupt pending
+    pub sip: U,
+    /// Supervisor address translation and protection
+    pub satp: U,
+    /// Machine status register
+    pub mstatus: U,
+    /// ISA and extensions
+    pub misa: U,
+    /// Machine exception delegation register
+    pub medeleg: U,
+    /// Machine interrupt delegation register
+    pub mideleg: U,
+    /// Machine interrupt-enable register
+    pub mie: U,
+    /// Machine trap-handler base address
+    pub mtvec: U,
+    /// Machine counter enable
+    pub mcounteren: U,
+    /// Scratch register for machine trap handlers
+    pub mscratch: U,
+    /// Machine exception program counter
+    pub mepc: U,
+    /// Machine trap cause
+    pub mcause: U,
+    /// Machine bad address or instruction
+    pub mtval: U,
+    /// Machine interrupt pending
+    pub mip: U,
+    /// Cycle counter
+    pub cycle: U,
+    /// Timer
+    pub time: U,
+    /// Instructions-retired counter
+    pub instret: U,
+    /// Vendor ID
+    pub mvendorid: U,
+    /// Architecture ID
+    pub marchid: U,
+    /// Implementation ID
+    pub mimpid: U,
+    /// Hardware thread ID
+    pub mhartid: U,
+}
+
+macro_rules! impl_csr_access {
+    ($($csr:literal => $field:ident,)*) => {
+        impl<U: Copy> RiscvCsrRegs<U> {
+            /// Return the value of the CSR with the given number, or `None` if
+            /// the CSR is not part of the described set.
+            pub fn get(&self, csr: u16) -> Option<U> {
+                match csr {
+                    $($csr => Some(self.$field),)*
+                    _ => None,
+                }
+            }
+
+            /// Return a mutable reference to the CSR with the given number, or
+            /// `None` if the CSR is not part of the described set.
+            pub fn get_mut(&mut self, csr: u16) -> Option<&mut U> {
+                match csr {
+                    $($csr => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+        }
+
+        /// CSR numbers of the described set, in ascending order.
+        pub(crate) const DESCRIBED_CSRS: &[u16] = &[$($csr),*];
+    };
+}
+
+impl_csr_access! {
+    0x100 => sstatus,
+    0x104 => sie,
+    0x105 => stvec,
+    0x106 => scounteren,
+    0x140 => sscratch,
+    0x141 => sepc,
+    0x142 => scause,
+    0x143 => stval,
+    0x144 => sip,
+    0x180 => satp,
+    0x300 => mstatus,
+    0x301 => misa,
+    0x302 => medeleg,
+    0x303 => mideleg,
+    0x304 => mie,
+    0x305 => mtvec,
+    0x306 => mcounteren,
+    0x340 => mscratch,
+    0x341 => mepc,
+    0x342 => mcause,
+    0x343 => mtval,
+    0x344 => mip,
+    0xc00 => cycle,
+    0xc01 => time,
+    0xc02 => instret,
+    0xf11 => mvendorid,
+    0xf12 => marchid,
+    0xf13 => mimpid,
+    0xf14 => mhartid,
+}
@@ -0,0 +1,35 @@
+/// RISC-V floating point registers.
+///
+/// The register width is set to `u32` (F extension) or `u64` (D extension)
+/// based on the `<F>` type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RiscvFpuRegs<F> {
+    /// Floating point registers (f0-f31)
+    pub f: [F; 32],
+    /// Floating-point control and status register
+    ///
+    /// The `fflags` and `frm` CSRs are accessed as fields of this register.
+    pub fcsr: u32,
+}
+
+impl<F> RiscvFpuRegs<F> {
+    /// Accrued exceptions (`fcsr[4:0]`)
+    pub fn fflags(&self) -> u32 {
+        self.fcsr & 0x1f
+    }
+
+    /// Set the accrued exceptions (`fcsr[4:0]`)
+    pub fn set_fflags(&mut self, val: u32) {
+        self.fcsr = (self.fcsr & !0x1f) | (val & 0x1f);
+    }
+
+    /// Dynamic rounding mode (`fcsr[7:5]`)
+    pub fn frm(&self) -> u32 {
+        (self.fcsr >> 5) & 0x7
+    }
+
+    /// Set the dynamic rounding mode (`fcsr[7:5]`)
+    pub fn set_frm(&mut self, val: u32) {
+        self.fcsr = (self.fcsr & !0xe0) | ((val & 0x7) << 5);
+    }
+}
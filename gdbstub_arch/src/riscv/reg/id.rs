@@ -3,9 +3,15 @@ use core::num::NonZeroUsize;
 use gdbstub::arch::RegId;
 
 /// RISC-V Register identifier.
+///
+/// The integer register / CSR width is set to `u32` or `u64` based on the
+/// `<U>` type, the floating point register width is set based on the `<F>`
+/// type, and the vector register length (in bytes) is set by `VLENB`.
+///
+/// Source: [riscv-tdep.h](https://github.com/bminor/binutils-gdb/blob/master/gdb/riscv-tdep.h)
 #[derive(Debug, Clone, Copy)]
 #[non_exhaustive]
-pub enum RiscvRegId<U> {
+pub enum RiscvRegId<U, F = U, const VLENB: usize = 0> {
     /// General Purpose Register (x0-x31).
     Gpr(u8),
     /// Floating Point Register (f0-f31).
@@ -16,23 +22,27 @@ pub enum RiscvRegId<U> {
     Csr(u16),
     /// Privilege level.
     Priv,
+    /// Vector Register (v0-v31). Only valid if `VLENB` is non-zero.
+    Vector(u8),
 
     #[doc(hidden)]
-    _Marker(core::marker::PhantomData<U>),
+    _Marker(core::marker::PhantomData<(U, F)>),
 }
 
 macro_rules! impl_riscv_reg_id {
     ($usize:ty) => {
-        impl RegId for RiscvRegId<$usize> {
+        impl<F: core::fmt::Debug, const VLENB: usize> RegId for RiscvRegId<$usize, F, VLENB> {
             fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
                 const USIZE: usize = core::mem::size_of::<$usize>();
+                let flen = core::mem::size_of::<F>();
 
                 let (id, size) = match id {
                     0..=31 => (Self::Gpr(id as u8), USIZE),
                     32 => (Self::Pc, USIZE),
-                    33..=64 => (Self::Fpr((id - 33) as u8), USIZE),
+                    33..=64 => (Self::Fpr((id - 33) as u8), flen),
                     65..=4160 => (Self::Csr((id - 65) as u16), USIZE),
                     4161 => (Self::Priv, 1),
+                    4162..=4193 if VLENB != 0 => (Self::Vector((id - 4162) as u8), VLENB),
                     _ => return None,
                 };
 
@@ -44,3 +54,122 @@ macro_rules! impl_riscv_reg_id {
 
 impl_riscv_reg_id!(u32);
 impl_riscv_reg_id!(u64);
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::Arch;
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+    use gdbstub::internal::LeBytes;
+    use num_traits::PrimInt;
+
+    use super::RiscvRegId;
+    use crate::riscv::reg::RiscvRegs;
+
+    fn le_bytes<T: LeBytes>(val: T) -> Vec<u8> {
+        let mut buf = [0; 16];
+        let len = val.to_le_bytes(&mut buf).unwrap();
+        buf[..len].to_vec()
+    }
+
+    /// Check that each register in the data written by `gdb_serialize` lines
+    /// up with `from_raw_id`, and that the target description XML describes
+    /// exactly those registers.
+    fn test<A, U, F, const VLENB: usize>()
+    where
+        A: Arch<Registers = RiscvRegs<U, F, VLENB>, RegId = RiscvRegId<U, F, VLENB>>,
+        U: PrimInt + LeBytes + Default + core::fmt::Debug,
+        F: PrimInt + LeBytes + Default + core::fmt::Debug,
+        RiscvRegId<U, F, VLENB>: RegId,
+    {
+        let mut regs = RiscvRegs::<U, F, VLENB>::default();
+        for (i, reg) in regs.core.x.iter_mut().enumerate() {
+            *reg = U::from(i + 1).unwrap();
+        }
+        regs.core.pc = U::from(0x8000_0000u32).unwrap();
+        for (i, reg) in regs.fpu.f.iter_mut().enumerate() {
+            *reg = F::from(0x100 + i).unwrap();
+        }
+        regs.fpu.fcsr = 0xa5;
+        for csr in 0..0x1000 {
+            regs.set_csr(csr, U::from(0x1000 + csr as usize).unwrap());
+        }
+        for (i, reg) in regs.vector.v.iter_mut().enumerate() {
+            *reg = [i as u8; VLENB];
+        }
+
+        let mut data = vec![];
+        regs.gdb_serialize(|x| data.push(x.unwrap()));
+
+        let mut regnums = vec![];
+        let mut offset = 0;
+        for i in 0..=4193 {
+            let (id, size) = match RiscvRegId::<U, F, VLENB>::from_raw_id(i) {
+                Some((id, size)) => (id, size.unwrap().get()),
+                None => continue,
+            };
+            let expected = match id {
+                RiscvRegId::Gpr(n) => le_bytes(regs.core.x[n as usize]),
+                RiscvRegId::Pc => le_bytes(regs.core.pc),
+                RiscvRegId::Fpr(n) => le_bytes(regs.fpu.f[n as usize]),
+                RiscvRegId::Csr(csr) => match regs.get_csr(csr) {
+                    Some(val) => le_bytes(val),
+                    None => continue,
+                },
+                RiscvRegId::Vector(n) => regs.vector.v[n as usize].to_vec(),
+                _ => continue,
+            };
+            assert_eq!(size, expected.len(), "{:?}", id);
+            assert_eq!(&data[offset..offset + size], &expected[..], "{:?}", id);
+            regnums.push(i);
+            offset += size;
+        }
+        assert_eq!(offset, data.len());
+
+        // the XML only uses explicit `regnum`s at discontinuities
+        let xml = A::target_description_xml().unwrap();
+        let mut xml_regnums = vec![];
+        for reg in xml.split("<reg ").skip(1) {
+            let regnum = match reg.split("regnum=\"").nth(1) {
+                Some(s) => s.split('"').next().unwrap().parse().unwrap(),
+                None => xml_regnums.last().unwrap() + 1,
+            };
+            xml_regnums.push(regnum);
+        }
+        assert_eq!(regnums, xml_regnums);
+
+        let mut regs_after = RiscvRegs::<U, F, VLENB>::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+        assert_eq!(regs, regs_after);
+    }
+
+    #[test]
+    fn test_riscv32f() {
+        test::<crate::riscv::Riscv32F, u32, u32, 0>()
+    }
+
+    #[test]
+    fn test_riscv32d() {
+        test::<crate::riscv::Riscv32D, u32, u64, 0>()
+    }
+
+    #[test]
+    fn test_riscv64d() {
+        test::<crate::riscv::Riscv64D, u64, u64, 0>()
+    }
+
+    #[test]
+    fn test_riscv64f_vector() {
+        test::<crate::riscv::Riscv64F<16>, u64, u32, 16>()
+    }
+
+    #[test]
+    fn test_riscv64d_vector() {
+        test::<crate::riscv::Riscv64D<64>, u64, u64, 64>()
+    }
+
+    #[test]
+    fn test_riscv32d_vector() {
+        test::<crate::riscv::Riscv32D<32>, u32, u64, 32>()
+    }
+}
@@ -3,6 +3,12 @@
 /// `RegId` definitions for RISC-V architectures.
 pub mod id;
 
+mod csr;
+mod fpu;
 mod riscv;
+mod vector;
 
-pub use riscv::RiscvCoreRegs;
+pub use csr::RiscvCsrRegs;
+pub use fpu::RiscvFpuRegs;
+pub use riscv::{RiscvCoreRegs, RiscvRegs};
+pub use vector::RiscvVectorRegs;
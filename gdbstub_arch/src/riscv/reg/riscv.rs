@@ -3,6 +3,8 @@ use num_traits::PrimInt;
 use gdbstub::arch::Registers;
 use gdbstub::internal::LeBytes;
 
+use super::{RiscvCsrRegs, RiscvFpuRegs, RiscvVectorRegs};
+
 /// RISC-V Integer registers.
 ///
 /// The register width is set to `u32` or `u64` based on the `<U>` type.
@@ -75,3 +77,192 @@ where
         Ok(())
     }
 }
+
+/// RISC-V registers, including the floating point registers, a described set
+/// of CSRs, and (optionally) the vector registers.
+///
+/// - The integer register width is set to `u32` or `u64` based on the `<U>`
+///   type.
+/// - The floating point register width is set to `u32` (F extension) or `u64`
+///   (D extension) based on the `<F>` type.
+/// - The vector register length is set by `VLENB` (i.e: `VLEN / 8`). Set
+///   `VLENB` to `0` if the V extension is not supported.
+///
+/// Registers are serialized in GDB's register numbering order (see
+/// [`RiscvRegId`](super::id::RiscvRegId)).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RiscvRegs<U, F, const VLENB: usize = 0> {
+    /// Integer registers
+    pub core: RiscvCoreRegs<U>,
+    /// Floating point registers
+    pub fpu: RiscvFpuRegs<F>,
+    /// Control and Status Registers
+    pub csr: RiscvCsrRegs<U>,
+    /// Vector registers (unused if `VLENB` is `0`)
+    pub vector: RiscvVectorRegs<U, VLENB>,
+}
+
+/// Floating point CSRs, in ascending order.
+const FPU_CSRS: &[u16] = &[0x001, 0x002, 0x003];
+/// Floating point + vector CSRs, in ascending order.
+const FPU_VECTOR_CSRS: &[u16] = &[
+    0x001, 0x002, 0x003, 0x008, 0x009, 0x00a, 0x00f, 0xc20, 0xc21, 0xc22,
+];
+
+impl<U, F, const VLENB: usize> RiscvRegs<U, F, VLENB>
+where
+    U: PrimInt,
+{
+    /// Return the value of the CSR with the given number, or `None` if the CSR
+    /// is not described by the target description XML.
+    pub fn get_csr(&self, csr: u16) -> Option<U> {
+        let val = match csr {
+            0x001 => U::from(self.fpu.fflags())?,
+            0x002 => U::from(self.fpu.frm())?,
+            0x003 => U::from(self.fpu.fcsr)?,
+            _ if VLENB == 0 => return self.csr.get(csr),
+            0x008 => self.vector.vstart,
+            0x009 => self.vector.vxsat,
+            0x00a => self.vector.vxrm,
+            0x00f => self.vector.vcsr,
+            0xc20 => self.vector.vl,
+            0xc21 => self.vector.vtype,
+            0xc22 => U::from(VLENB)?,
+            _ => return self.csr.get(csr),
+        };
+        Some(val)
+    }
+
+    /// Set the value of the CSR with the given number, returning `None` if the
+    /// CSR is not described by the target description XML.
+    ///
+    /// Writes to the read-only `vlenb` CSR are ignored.
+    pub fn set_csr(&mut self, csr: u16, val: U) -> Option<()> {
+        match csr {
+            0x001 => self.fpu.set_fflags(val.to_u32()?),
+            0x002 => self.fpu.set_frm(val.to_u32()?),
+            0x003 => self.fpu.fcsr = val.to_u32()?,
+            _ if VLENB == 0 => *self.csr.get_mut(csr)? = val,
+            0x008 => self.vector.vstart = val,
+            0x009 => self.vector.vxsat = val,
+            0x00a => self.vector.vxrm = val,
+            0x00f => self.vector.vcsr = val,
+            0xc20 => self.vector.vl = val,
+            0xc21 => self.vector.vtype = val,
+            0xc22 => {}
+            _ => *self.csr.get_mut(csr)? = val,
+        }
+        Some(())
+    }
+
+    /// Invoke `f` with the number of each CSR described by the target
+    /// description XML, in ascending order.
+    fn for_each_csr(mut f: impl FnMut(u16)) {
+        let mut extra = if VLENB == 0 {
+            FPU_CSRS
+        } else {
+            FPU_VECTOR_CSRS
+        }
+        .iter()
+        .copied()
+        .peekable();
+
+        for &csr in super::csr::DESCRIBED_CSRS {
+            while let Some(extra_csr) = extra.next_if(|&extra_csr| extra_csr < csr) {
+                f(extra_csr)
+            }
+            f(csr)
+        }
+        extra.for_each(f)
+    }
+}
+
+impl<U, F, const VLENB: usize> Registers for RiscvRegs<U, F, VLENB>
+where
+    U: PrimInt + LeBytes + Default + core::fmt::Debug,
+    F: PrimInt + LeBytes + Default + core::fmt::Debug,
+{
+    type ProgramCounter = U;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.core.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_le_bytes {
+            ($value:expr) => {
+                let mut buf = [0; 16];
+                // infallible (unless digit is a >128 bit number)
+                let len = $value.to_le_bytes(&mut buf).unwrap();
+                let buf = &buf[..len];
+                for b in buf {
+                    write_byte(Some(*b));
+                }
+            };
+        }
+
+        // x0-x31, pc
+        self.core.gdb_serialize(&mut write_byte);
+
+        // f0-f31
+        for reg in self.fpu.f.iter() {
+            write_le_bytes!(reg);
+        }
+
+        // CSRs are numbered 65 + their CSR number
+        Self::for_each_csr(|csr| {
+            // infallible, as `for_each_csr` only yields described CSRs
+            let val = self.get_csr(csr).unwrap_or_else(U::zero);
+            write_le_bytes!(&val);
+        });
+
+        // v0-v31
+        for reg in self.vector.v.iter() {
+            for b in reg {
+                write_byte(Some(*b));
+            }
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        let usize_len = core::mem::size_of::<U>();
+        let flen = core::mem::size_of::<F>();
+
+        let mut csr_count = 0;
+        Self::for_each_csr(|_| csr_count += 1);
+
+        if bytes.len() != 33 * usize_len + 32 * flen + csr_count * usize_len + 32 * VLENB {
+            return Err(());
+        }
+
+        let (core, bytes) = bytes.split_at(33 * usize_len);
+        self.core.gdb_deserialize(core)?;
+
+        let (fpu, bytes) = bytes.split_at(32 * flen);
+        for (reg, b) in self.fpu.f.iter_mut().zip(fpu.chunks_exact(flen)) {
+            *reg = F::from_le_bytes(b).ok_or(())?;
+        }
+
+        let (csrs, vector) = bytes.split_at(csr_count * usize_len);
+        let mut csrs = csrs.chunks_exact(usize_len);
+        let mut res = Ok(());
+        Self::for_each_csr(|csr| {
+            let val = csrs.next().and_then(U::from_le_bytes);
+            if val.and_then(|val| self.set_csr(csr, val)).is_none() {
+                res = Err(())
+            }
+        });
+        res?;
+
+        for (reg, b) in self
+            .vector
+            .v
+            .iter_mut()
+            .zip(vector.chunks_exact(VLENB.max(1)))
+        {
+            reg.copy_from_slice(b);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+/// RISC-V vector (V extension) registers.
+///
+/// The vector register length is set by `VLENB` (i.e: `VLEN / 8`), and the
+/// vector CSR width is set to `u32` or `u64` based on the `<U>` type.
+///
+/// The read-only `vlenb` CSR is always reported as `VLENB`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiscvVectorRegs<U, const VLENB: usize> {
+    /// Vector registers (v0-v31), stored in little-endian byte order
+    pub v: [[u8; VLENB]; 32],
+    /// Vector start position
+    pub vstart: U,
+    /// Fixed-point saturate flag
+    pub vxsat: U,
+    /// Fixed-point rounding mode
+    pub vxrm: U,
+    /// Vector control and status register
+    pub vcsr: U,
+    /// Vector length
+    pub vl: U,
+    /// Vector data type register
+    pub vtype: U,
+}
+
+impl<U: Default, const VLENB: usize> Default for RiscvVectorRegs<U, VLENB> {
+    fn default() -> Self {
+        RiscvVectorRegs {
+            v: [[0; VLENB]; 32],
+            vstart: U::default(),
+            vxsat: U::default(),
+            vxrm: U::default(),
+            vcsr: U::default(),
+            vl: U::default(),
+            vtype: U::default(),
+        }
+    }
+}
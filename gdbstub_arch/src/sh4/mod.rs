@@ -0,0 +1,50 @@
+//! Implementations for the Hitachi / Renesas SuperH (SH-4) family of CPUs.
+
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
+
+pub mod reg;
+
+/// Implements `Arch` for big-endian SH-4 CPUs.
+///
+/// GDB does not define any target description features for SuperH, so the
+/// target XML only specifies the architecture.
+pub enum Sh4 {}
+
+impl Arch for Sh4 {
+    type Usize = u32;
+    type Registers = reg::Sh4Regs;
+    type RegId = reg::id::Sh4RegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(r#"<target version="1.0"><architecture>sh4</architecture></target>"#)
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
+
+/// Implements `Arch` for little-endian SH-4 CPUs (e.g: the Sega Dreamcast).
+///
+/// The target description XML does not specify an endianness, so GDB must
+/// either infer it from the loaded ELF file, or be told via `set endian
+/// little`.
+pub enum Sh4le {}
+
+impl Arch for Sh4le {
+    type Usize = u32;
+    type Registers = reg::Sh4Regs<true>;
+    type RegId = reg::id::Sh4RegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(r#"<target version="1.0"><architecture>sh4</architecture></target>"#)
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
@@ -0,0 +1,104 @@
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+/// SH-4 register identifier.
+///
+/// GDB does not provide a XML file for the SH-4.
+/// The best file to reference is [sh-tdep.c](https://github.com/bminor/binutils-gdb/blob/master/gdb/sh-tdep.c).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Sh4RegId {
+    /// General purpose registers (R0-R15)
+    Gpr(u8),
+    /// Program counter
+    Pc,
+    /// Procedure register (return address)
+    Pr,
+    /// Global base register
+    Gbr,
+    /// Vector base register
+    Vbr,
+    /// Multiply-accumulate register (high)
+    Mach,
+    /// Multiply-accumulate register (low)
+    Macl,
+    /// Status register
+    Sr,
+    /// Floating point communication register
+    Fpul,
+    /// Floating point status / control register
+    Fpscr,
+    /// Floating point registers (FR0-FR15)
+    Fr(u8),
+    /// Saved status register
+    Ssr,
+    /// Saved program counter
+    Spc,
+    /// Bank 0 general purpose registers (R0_BANK0-R7_BANK0)
+    Bank0(u8),
+    /// Bank 1 general purpose registers (R0_BANK1-R7_BANK1)
+    Bank1(u8),
+}
+
+impl RegId for Sh4RegId {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        use self::Sh4RegId::*;
+
+        let reg = match id {
+            0..=15 => Gpr(id as u8),
+            16 => Pc,
+            17 => Pr,
+            18 => Gbr,
+            19 => Vbr,
+            20 => Mach,
+            21 => Macl,
+            22 => Sr,
+            23 => Fpul,
+            24 => Fpscr,
+            25..=40 => Fr(id as u8 - 25),
+            41 => Ssr,
+            42 => Spc,
+            43..=50 => Bank0(id as u8 - 43),
+            51..=58 => Bank1(id as u8 - 51),
+            _ => return None,
+        };
+        Some((reg, Some(NonZeroUsize::new(4)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_sh4() {
+        test::<crate::sh4::reg::Sh4Regs, crate::sh4::reg::id::Sh4RegId>()
+    }
+}
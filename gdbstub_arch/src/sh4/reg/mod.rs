@@ -0,0 +1,8 @@
+//! `Register` structs for SuperH (SH-4) CPUs.
+
+/// `RegId` definitions for SuperH (SH-4) CPUs.
+pub mod id;
+
+mod sh4;
+
+pub use sh4::Sh4Regs;
@@ -0,0 +1,176 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+/// SH-4 core, system, floating point, and banked registers.
+///
+/// Registers are serialized in big-endian byte order by default. Set
+/// `LITTLE_ENDIAN` to `true` for little-endian targets (e.g: the Dreamcast).
+///
+/// GDB does not provide a XML file for the SH-4.
+/// The register layout is taken from [sh-tdep.c](https://github.com/bminor/binutils-gdb/blob/master/gdb/sh-tdep.c).
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Sh4Regs<const LITTLE_ENDIAN: bool = false> {
+    /// General purpose registers (R0-R15)
+    pub r: [u32; 16],
+    /// Program counter
+    pub pc: u32,
+    /// Procedure register (return address)
+    pub pr: u32,
+    /// Global base register
+    pub gbr: u32,
+    /// Vector base register
+    pub vbr: u32,
+    /// Multiply-accumulate register (high)
+    pub mach: u32,
+    /// Multiply-accumulate register (low)
+    pub macl: u32,
+    /// Status register
+    pub sr: u32,
+    /// Floating point communication register
+    pub fpul: u32,
+    /// Floating point status / control register
+    pub fpscr: u32,
+    /// Floating point registers (FR0-FR15), stored as raw bits
+    pub fr: [u32; 16],
+    /// Saved status register
+    pub ssr: u32,
+    /// Saved program counter
+    pub spc: u32,
+    /// Bank 0 general purpose registers (R0_BANK0-R7_BANK0)
+    pub bank0: [u32; 8],
+    /// Bank 1 general purpose registers (R0_BANK1-R7_BANK1)
+    pub bank1: [u32; 8],
+}
+
+impl<const LITTLE_ENDIAN: bool> Registers for Sh4Regs<LITTLE_ENDIAN> {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_regs {
+            ($($reg:expr),*) => {
+                $(
+                    let bytes = if LITTLE_ENDIAN {
+                        $reg.to_le_bytes()
+                    } else {
+                        $reg.to_be_bytes()
+                    };
+                    for b in &bytes {
+                        write_byte(Some(*b))
+                    }
+                )*
+            }
+        }
+
+        for reg in &self.r {
+            write_regs!(reg);
+        }
+        write_regs!(self.pc, self.pr, self.gbr, self.vbr, self.mach, self.macl, self.sr);
+        write_regs!(self.fpul, self.fpscr);
+        for reg in &self.fr {
+            write_regs!(reg);
+        }
+        write_regs!(self.ssr, self.spc);
+        for reg in self.bank0.iter().chain(self.bank1.iter()) {
+            write_regs!(reg);
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 59 * 4 {
+            return Err(());
+        }
+
+        let mut regs = bytes.chunks_exact(4).map(|c| {
+            let c = c.try_into().unwrap();
+            if LITTLE_ENDIAN {
+                u32::from_le_bytes(c)
+            } else {
+                u32::from_be_bytes(c)
+            }
+        });
+
+        for reg in self.r.iter_mut() {
+            *reg = regs.next().ok_or(())?
+        }
+        self.pc = regs.next().ok_or(())?;
+        self.pr = regs.next().ok_or(())?;
+        self.gbr = regs.next().ok_or(())?;
+        self.vbr = regs.next().ok_or(())?;
+        self.mach = regs.next().ok_or(())?;
+        self.macl = regs.next().ok_or(())?;
+        self.sr = regs.next().ok_or(())?;
+        self.fpul = regs.next().ok_or(())?;
+        self.fpscr = regs.next().ok_or(())?;
+        for reg in self.fr.iter_mut() {
+            *reg = regs.next().ok_or(())?
+        }
+        self.ssr = regs.next().ok_or(())?;
+        self.spc = regs.next().ok_or(())?;
+        for reg in self.bank0.iter_mut().chain(self.bank1.iter_mut()) {
+            *reg = regs.next().ok_or(())?
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<const LITTLE_ENDIAN: bool>() -> Vec<u8> {
+        let mut regs_before = Sh4Regs::<LITTLE_ENDIAN> {
+            pc: 0x8c01_0000,
+            pr: 0x8c00_1234,
+            gbr: 1,
+            vbr: 0x8c00_0000,
+            mach: 2,
+            macl: 3,
+            sr: 0x7000_00f0,
+            fpul: 4,
+            fpscr: 0x0004_0001,
+            ssr: 5,
+            spc: 6,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.r.iter_mut().enumerate() {
+            *reg = i as u32;
+        }
+        for (i, reg) in regs_before.fr.iter_mut().enumerate() {
+            *reg = (i as f32).to_bits();
+        }
+        for (i, reg) in regs_before.bank0.iter_mut().enumerate() {
+            *reg = 0x100 + i as u32;
+        }
+        for (i, reg) in regs_before.bank1.iter_mut().enumerate() {
+            *reg = 0x200 + i as u32;
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        let mut regs_after = Sh4Regs::<LITTLE_ENDIAN>::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+        data
+    }
+
+    #[test]
+    fn sh4_round_trip() {
+        let be = round_trip::<false>();
+        let le = round_trip::<true>();
+
+        // pc
+        assert_eq!(be[64..68], [0x8c, 0x01, 0x00, 0x00]);
+        assert_eq!(le[64..68], [0x00, 0x00, 0x01, 0x8c]);
+    }
+}
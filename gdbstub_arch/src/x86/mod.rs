@@ -26,6 +26,74 @@ impl Arch for X86_64_SSE {
     }
 }
 
+/// Implements `Arch` for 64-bit x86 + SSE and AVX Extensions, along with the
+/// `orig_rax`, `fs_base` / `gs_base`, and control registers.
+///
+/// The target description XML uses the `org.gnu.gdb.i386.core`, `sse`,
+/// `linux`, `segments`, and `avx` features, along with a `org.gdbstub.i386.sys`
+/// feature describing `cr0`, `cr2`-`cr4`, `cr8`, and `efer`.
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum X86_64_AVX {}
+
+impl Arch for X86_64_AVX {
+    type Usize = u64;
+    type Registers = reg::X86_64AvxRegs;
+    type RegId = reg::id::X86_64AvxRegId;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>i386:x86-64</architecture>"#,
+            include_str!("xml/64bit-core.xml"),
+            include_str!("xml/64bit-sse.xml"),
+            include_str!("xml/64bit-linux.xml"),
+            include_str!("xml/64bit-segments.xml"),
+            include_str!("xml/64bit-sys.xml"),
+            include_str!("xml/64bit-avx.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
+
+/// Implements `Arch` for 64-bit x86 + SSE, AVX, and AVX-512 Extensions, along
+/// with the `orig_rax`, `fs_base` / `gs_base`, and control registers.
+///
+/// The target description XML extends [`X86_64_AVX`]'s with the
+/// `org.gnu.gdb.i386.avx512` feature.
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum X86_64_AVX512 {}
+
+impl Arch for X86_64_AVX512 {
+    type Usize = u64;
+    type Registers = reg::X86_64Avx512Regs;
+    type RegId = reg::id::X86_64AvxRegId<true>;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>i386:x86-64</architecture>"#,
+            include_str!("xml/64bit-core.xml"),
+            include_str!("xml/64bit-sse.xml"),
+            include_str!("xml/64bit-linux.xml"),
+            include_str!("xml/64bit-segments.xml"),
+            include_str!("xml/64bit-sys.xml"),
+            include_str!("xml/64bit-avx.xml"),
+            include_str!("xml/64bit-avx512.xml"),
+            "</target>"
+        ))
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
+
 /// Implements `Arch` for 32-bit x86 + SSE Extensions.
 #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 pub enum X86_SSE {}
@@ -0,0 +1,269 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::Registers;
+
+use super::X86_64CoreRegs;
+
+/// x86-64 control registers (+ EFER).
+///
+/// These registers are described using the `org.gdbstub.i386.sys` feature, as
+/// GDB does not define a standard feature for them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct X86_64ControlRegs {
+    /// Control register 0 (system control flags)
+    pub cr0: u64,
+    /// Control register 2 (page fault linear address)
+    pub cr2: u64,
+    /// Control register 3 (page table base)
+    pub cr3: u64,
+    /// Control register 4 (architecture extension flags)
+    pub cr4: u64,
+    /// Control register 8 (task priority)
+    pub cr8: u64,
+    /// Extended Feature Enable Register
+    pub efer: u64,
+}
+
+/// 64-bit x86 core registers + SSE, AVX, and system registers.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/i386/64bit-linux.xml>
+/// Additionally: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/i386/64bit-segments.xml>
+/// Additionally: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/i386/64bit-avx.xml>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct X86_64AvxRegs {
+    /// Core + SSE registers
+    pub core: X86_64CoreRegs,
+    /// Original RAX on syscall entry (`org.gnu.gdb.i386.linux`)
+    pub orig_rax: u64,
+    /// FS segment base
+    pub fs_base: u64,
+    /// GS segment base
+    pub gs_base: u64,
+    /// Control registers
+    pub control: X86_64ControlRegs,
+    /// Upper 128 bits of YMM0 through YMM15
+    pub ymm_hi: [u128; 16],
+}
+
+/// Number of bytes in a serialized [`X86_64CoreRegs`], up to and including
+/// `mxcsr`.
+const CORE_SSE_LEN: usize = 0x218;
+/// Number of bytes in a serialized [`X86_64AvxRegs`].
+const AVX_LEN: usize = CORE_SSE_LEN + 9 * 8 + 16 * 0x10;
+
+impl Registers for X86_64AvxRegs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.core.rip
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        self.core.serialize_core_sse(&mut write_byte);
+
+        write_bytes!(&self.orig_rax.to_le_bytes());
+        write_bytes!(&self.fs_base.to_le_bytes());
+        write_bytes!(&self.gs_base.to_le_bytes());
+
+        write_bytes!(&self.control.cr0.to_le_bytes());
+        write_bytes!(&self.control.cr2.to_le_bytes());
+        write_bytes!(&self.control.cr3.to_le_bytes());
+        write_bytes!(&self.control.cr4.to_le_bytes());
+        write_bytes!(&self.control.cr8.to_le_bytes());
+        write_bytes!(&self.control.efer.to_le_bytes());
+
+        // ymm0h to ymm15h
+        for reg in &self.ymm_hi {
+            write_bytes!(&reg.to_le_bytes());
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != AVX_LEN {
+            return Err(());
+        }
+
+        self.core.gdb_deserialize(&bytes[..CORE_SSE_LEN])?;
+
+        let mut regs = bytes[CORE_SSE_LEN..CORE_SSE_LEN + 9 * 8]
+            .chunks_exact(8)
+            .map(|x| u64::from_le_bytes(x.try_into().unwrap()));
+
+        self.orig_rax = regs.next().ok_or(())?;
+        self.fs_base = regs.next().ok_or(())?;
+        self.gs_base = regs.next().ok_or(())?;
+
+        self.control.cr0 = regs.next().ok_or(())?;
+        self.control.cr2 = regs.next().ok_or(())?;
+        self.control.cr3 = regs.next().ok_or(())?;
+        self.control.cr4 = regs.next().ok_or(())?;
+        self.control.cr8 = regs.next().ok_or(())?;
+        self.control.efer = regs.next().ok_or(())?;
+
+        let mut regs = bytes[CORE_SSE_LEN + 9 * 8..]
+            .chunks_exact(0x10)
+            .map(|x| u128::from_le_bytes(x.try_into().unwrap()));
+
+        for reg in self.ymm_hi.iter_mut() {
+            *reg = regs.next().ok_or(())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 64-bit x86 core registers + SSE, AVX, AVX-512, and system registers.
+///
+/// Source: <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/i386/64bit-avx512.xml>
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct X86_64Avx512Regs {
+    /// Core + SSE + AVX registers
+    pub avx: X86_64AvxRegs,
+    /// XMM16 through XMM31
+    pub xmm: [u128; 16],
+    /// Upper 128 bits of YMM16 through YMM31
+    pub ymm_hi: [u128; 16],
+    /// Opmask registers: K0 through K7
+    pub k: [u64; 8],
+    /// Upper 256 bits of ZMM0 through ZMM31, stored as `[low, high]`
+    pub zmm_hi: [[u128; 2]; 32],
+}
+
+/// Number of bytes in a serialized [`X86_64Avx512Regs`].
+const AVX512_LEN: usize = AVX_LEN + 32 * 0x10 + 8 * 8 + 32 * 0x20;
+
+impl Registers for X86_64Avx512Regs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.avx.core.rip
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        self.avx.gdb_serialize(&mut write_byte);
+
+        // xmm16 to xmm31
+        for reg in &self.xmm {
+            write_bytes!(&reg.to_le_bytes());
+        }
+
+        // ymm16h to ymm31h
+        for reg in &self.ymm_hi {
+            write_bytes!(&reg.to_le_bytes());
+        }
+
+        // k0 to k7
+        for reg in &self.k {
+            write_bytes!(&reg.to_le_bytes());
+        }
+
+        // zmm0h to zmm31h
+        for [lo, hi] in &self.zmm_hi {
+            write_bytes!(&lo.to_le_bytes());
+            write_bytes!(&hi.to_le_bytes());
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != AVX512_LEN {
+            return Err(());
+        }
+
+        self.avx.gdb_deserialize(&bytes[..AVX_LEN])?;
+
+        let (vec, rest) = bytes[AVX_LEN..].split_at(32 * 0x10);
+        let mut regs = vec
+            .chunks_exact(0x10)
+            .map(|x| u128::from_le_bytes(x.try_into().unwrap()));
+
+        for reg in self.xmm.iter_mut().chain(self.ymm_hi.iter_mut()) {
+            *reg = regs.next().ok_or(())?;
+        }
+
+        let (k, zmm_hi) = rest.split_at(8 * 8);
+        let mut regs = k
+            .chunks_exact(8)
+            .map(|x| u64::from_le_bytes(x.try_into().unwrap()));
+
+        for reg in self.k.iter_mut() {
+            *reg = regs.next().ok_or(())?;
+        }
+
+        let mut regs = zmm_hi
+            .chunks_exact(0x10)
+            .map(|x| u128::from_le_bytes(x.try_into().unwrap()));
+
+        for [lo, hi] in self.zmm_hi.iter_mut() {
+            *lo = regs.next().ok_or(())?;
+            *hi = regs.next().ok_or(())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn x86_64_avx512_round_trip() {
+        let mut regs_before = X86_64Avx512Regs::default();
+        regs_before.avx.core.regs = [0x1234; 16];
+        regs_before.avx.core.rip = 0xffff_ffff_8100_0000;
+        regs_before.avx.core.mxcsr = 0x1f80;
+        regs_before.avx.orig_rax = u64::MAX;
+        regs_before.avx.fs_base = 0x7f00_0000_1000;
+        regs_before.avx.gs_base = 0xffff_8880_0000_0000;
+        regs_before.avx.control = X86_64ControlRegs {
+            cr0: 0x8005_0033,
+            cr2: 0xdead_b000,
+            cr3: 0x1_0000_0000,
+            cr4: 0x3406f0,
+            cr8: 0xf,
+            efer: 0xd01,
+        };
+        for (i, reg) in regs_before.avx.ymm_hi.iter_mut().enumerate() {
+            *reg = 0x100 + i as u128;
+        }
+        for (i, reg) in regs_before.xmm.iter_mut().enumerate() {
+            *reg = 0x200 + i as u128;
+        }
+        for (i, reg) in regs_before.ymm_hi.iter_mut().enumerate() {
+            *reg = 0x300 + i as u128;
+        }
+        for (i, reg) in regs_before.k.iter_mut().enumerate() {
+            *reg = 1 << i;
+        }
+        for (i, reg) in regs_before.zmm_hi.iter_mut().enumerate() {
+            *reg = [0x400 + i as u128, u128::MAX - i as u128];
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        let mut regs_after = X86_64Avx512Regs::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+}
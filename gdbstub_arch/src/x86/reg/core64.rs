@@ -28,14 +28,9 @@ pub struct X86_64CoreRegs {
     pub mxcsr: u32,
 }
 
-impl Registers for X86_64CoreRegs {
-    type ProgramCounter = u64;
-
-    fn pc(&self) -> Self::ProgramCounter {
-        self.rip
-    }
-
-    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+impl X86_64CoreRegs {
+    /// Serialize the core + SSE registers, stopping after `mxcsr`.
+    pub(super) fn serialize_core_sse(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_bytes {
             ($bytes:expr) => {
                 for b in $bytes {
@@ -71,10 +66,24 @@ impl Registers for X86_64CoreRegs {
 
         // mxcsr
         write_bytes!(&self.mxcsr.to_le_bytes());
+    }
+}
+
+impl Registers for X86_64CoreRegs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.rip
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        self.serialize_core_sse(&mut write_byte);
 
-        // padding?
-        // XXX: Couldn't figure out what these do and GDB doesn't actually display any
-        // registers that use these values.
+        // orig_rax, fs_base, gs_base
+        //
+        // GDB's default (Linux) amd64 target description includes these
+        // registers, so report them as unavailable. See `X86_64AvxRegs` for a
+        // register file which includes them.
         (0..0x18).for_each(|_| write_byte(None))
     }
 
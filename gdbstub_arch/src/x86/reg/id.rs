@@ -189,6 +189,84 @@ impl RegId for X86_64CoreRegId {
     }
 }
 
+/// 64-bit x86 core + SSE + AVX register identifier, along with the
+/// `orig_rax`, `fs_base` / `gs_base`, and control registers.
+///
+/// The AVX-512 registers are only available when `AVX512` is `true`.
+///
+/// Sources:
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/i386/64bit-avx.xml>
+/// * <https://github.com/bminor/binutils-gdb/blob/master/gdb/features/i386/64bit-avx512.xml>
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum X86_64AvxRegId<const AVX512: bool = false> {
+    /// General purpose registers:
+    /// RAX, RBX, RCX, RDX, RSI, RDI, RBP, RSP, r8-r15
+    Gpr(u8),
+    /// Instruction pointer
+    Rip,
+    /// Status register
+    Eflags,
+    /// Segment registers
+    Segment(X86SegmentRegId),
+    /// FPU registers: ST0 through ST7
+    St(u8),
+    /// FPU internal registers
+    Fpu(X87FpuInternalRegId),
+    /// SIMD Registers: XMM0 through XMM15 (or XMM31, with AVX-512)
+    Xmm(u8),
+    /// SSE Status/Control Register
+    Mxcsr,
+    /// Original RAX on syscall entry
+    OrigRax,
+    /// FS segment base
+    FsBase,
+    /// GS segment base
+    GsBase,
+    /// Control registers: CR0, CR2, CR3, CR4, and CR8
+    Cr(u8),
+    /// Extended Feature Enable Register
+    Efer,
+    /// Upper 128 bits of YMM0 through YMM15 (or YMM31, with AVX-512)
+    YmmHi(u8),
+    /// AVX-512 opmask registers: K0 through K7
+    K(u8),
+    /// Upper 256 bits of AVX-512 registers ZMM0 through ZMM31
+    ZmmHi(u8),
+}
+
+impl<const AVX512: bool> RegId for X86_64AvxRegId<AVX512> {
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        use self::X86_64AvxRegId::*;
+
+        let (r, sz): (Self, usize) = match id {
+            0..=15 => (Gpr(id as u8), 8),
+            16 => (Rip, 8),
+            17 => (Eflags, 4),
+            18..=23 => (Segment(X86SegmentRegId::from_u8(id as u8 - 18)?), 4),
+            24..=31 => (St(id as u8 - 24), 10),
+            32..=39 => (Fpu(X87FpuInternalRegId::from_u8(id as u8 - 32)?), 4),
+            40..=55 => (Xmm(id as u8 - 40), 16),
+            56 => (Mxcsr, 4),
+            57 => (OrigRax, 8),
+            58 => (FsBase, 8),
+            59 => (GsBase, 8),
+            60 => (Cr(0), 8),
+            61..=63 => (Cr(id as u8 - 59), 8),
+            64 => (Cr(8), 8),
+            65 => (Efer, 8),
+            66..=81 => (YmmHi(id as u8 - 66), 16),
+            82..=97 if AVX512 => (Xmm(id as u8 - 82 + 16), 16),
+            98..=113 if AVX512 => (YmmHi(id as u8 - 98 + 16), 16),
+            114..=121 if AVX512 => (K(id as u8 - 114), 8),
+            122..=153 if AVX512 => (ZmmHi(id as u8 - 122), 32),
+            _ => return None,
+        };
+
+        Some((r, Some(NonZeroUsize::new(sz)?)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use gdbstub::arch::RegId;
@@ -224,8 +302,39 @@ mod tests {
         test::<crate::x86::reg::X86CoreRegs, crate::x86::reg::id::X86CoreRegId>()
     }
 
+    /// Check that the number of registers in the target description XML
+    /// matches the number of registers returned by `from_raw_id`.
+    fn test_xml<A: gdbstub::arch::Arch>() {
+        let xml = A::target_description_xml().unwrap();
+        let num_regs = xml.matches("<reg ").count();
+
+        assert!(A::RegId::from_raw_id(num_regs - 1).is_some());
+        let mut data_len = 0;
+        <A::Registers>::default().gdb_serialize(|b| {
+            if b.is_some() {
+                data_len += 1
+            }
+        });
+        let regs_len: usize = (0..num_regs)
+            .map(|i| A::RegId::from_raw_id(i).unwrap().1.unwrap().get())
+            .sum();
+        assert_eq!(data_len, regs_len);
+    }
+
+    #[test]
+    fn test_x86_64_xml() {
+        test_xml::<crate::x86::X86_64_AVX>();
+        test_xml::<crate::x86::X86_64_AVX512>();
+    }
+
     #[test]
     fn test_x86_64() {
         test::<crate::x86::reg::X86_64CoreRegs, crate::x86::reg::id::X86_64CoreRegId>()
     }
+
+    #[test]
+    fn test_x86_64_avx() {
+        test::<crate::x86::reg::X86_64AvxRegs, crate::x86::reg::id::X86_64AvxRegId>();
+        test::<crate::x86::reg::X86_64Avx512Regs, crate::x86::reg::id::X86_64AvxRegId<true>>();
+    }
 }
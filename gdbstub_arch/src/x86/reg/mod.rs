@@ -7,9 +7,11 @@ use gdbstub::arch::Registers;
 /// `RegId` definitions for x86 architectures.
 pub mod id;
 
+mod avx;
 mod core32;
 mod core64;
 
+pub use avx::{X86_64Avx512Regs, X86_64AvxRegs, X86_64ControlRegs};
 pub use core32::X86CoreRegs;
 pub use core64::X86_64CoreRegs;
 
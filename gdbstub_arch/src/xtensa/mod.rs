@@ -0,0 +1,125 @@
+//! Implementations for the Xtensa architecture (e.g: Espressif ESP32 MCUs).
+//!
+//! Xtensa is a configurable architecture: the set of registers present (and
+//! therefore the register layout GDB expects) depends on which core options
+//! were selected for a particular chip. This is captured by the
+//! [`XtensaConfig`] trait, with built-in configurations provided for common
+//! Espressif chips.
+
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
+
+pub mod reg;
+
+/// Xtensa core configuration, describing which optional registers are present
+/// on a particular chip.
+///
+/// These mirror the `XCHAL_HAVE_*` / `XCHAL_NUM_AREGS` definitions from the
+/// chip's `core-isa.h`, and determine the layout of the register file sent to
+/// GDB (see [`XtensaRegs`](reg::XtensaRegs)).
+pub trait XtensaConfig: core::fmt::Debug + Default + Copy + PartialEq {
+    /// Number of physical address registers (`ar0`-`arN`). Must be 16, 32, or
+    /// 64.
+    const NUM_AREGS: usize;
+    /// Zero-overhead loop registers (`lbeg`, `lend`, `lcount`).
+    const HAVE_LOOPS: bool;
+    /// Windowed register option (`windowbase`, `windowstart`).
+    const HAVE_WINDOWED: bool;
+    /// Thread pointer option (`threadptr`).
+    const HAVE_THREADPTR: bool;
+    /// Boolean registers option (`br`).
+    const HAVE_BOOLEANS: bool;
+    /// Conditional store option (`scompare1`).
+    const HAVE_S32C1I: bool;
+    /// MAC16 option (`acclo`, `acchi`, `m0`-`m3`).
+    const HAVE_MAC16: bool;
+    /// Double precision FP acceleration registers (`expstate`, `f64r_lo`,
+    /// `f64r_hi`, `f64s`).
+    const HAVE_DFP_ACCEL: bool;
+    /// Single precision floating point coprocessor (`f0`-`f15`, `fcr`, `fsr`).
+    const HAVE_FP: bool;
+
+    /// Target description XML matching this configuration, if any.
+    fn target_description_xml() -> Option<&'static str>;
+}
+
+/// Index into the physical address registers of the first register in the
+/// window selected by `windowbase`.
+///
+/// `windowbase` is masked to the number of windows present in `C`, so
+/// arbitrary (e.g: GDB-supplied) values can't overflow.
+pub(crate) fn window_base_index<C: XtensaConfig>(windowbase: u32) -> usize {
+    (windowbase as usize & (C::NUM_AREGS / 4 - 1)) * 4
+}
+
+/// Core configuration for the ESP32 (LX6).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Esp32;
+
+impl XtensaConfig for Esp32 {
+    const NUM_AREGS: usize = 64;
+    const HAVE_LOOPS: bool = true;
+    const HAVE_WINDOWED: bool = true;
+    const HAVE_THREADPTR: bool = true;
+    const HAVE_BOOLEANS: bool = true;
+    const HAVE_S32C1I: bool = true;
+    const HAVE_MAC16: bool = true;
+    const HAVE_DFP_ACCEL: bool = true;
+    const HAVE_FP: bool = true;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>xtensa</architecture>"#,
+            include_str!("xml/esp32.xml"),
+            "</target>"
+        ))
+    }
+}
+
+/// Core configuration for the ESP32-S2 (LX7, without FPU / MAC16).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Esp32S2;
+
+impl XtensaConfig for Esp32S2 {
+    const NUM_AREGS: usize = 64;
+    const HAVE_LOOPS: bool = true;
+    const HAVE_WINDOWED: bool = true;
+    const HAVE_THREADPTR: bool = true;
+    const HAVE_BOOLEANS: bool = false;
+    const HAVE_S32C1I: bool = true;
+    const HAVE_MAC16: bool = false;
+    const HAVE_DFP_ACCEL: bool = false;
+    const HAVE_FP: bool = false;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(concat!(
+            r#"<target version="1.0"><architecture>xtensa</architecture>"#,
+            include_str!("xml/esp32s2.xml"),
+            "</target>"
+        ))
+    }
+}
+
+/// Implements `Arch` for Xtensa cores, using the register layout expected by
+/// Espressif's GDB (`xtensa-esp32-elf-gdb` and friends).
+///
+/// The `C` parameter selects the core configuration, e.g: `Xtensa<Esp32>`.
+pub enum Xtensa<C: XtensaConfig = Esp32> {
+    #[doc(hidden)]
+    _Marker(core::marker::PhantomData<C>),
+}
+
+impl<C: XtensaConfig> Arch for Xtensa<C> {
+    type Usize = u32;
+    type Registers = reg::XtensaRegs<C>;
+    type RegId = reg::id::XtensaRegId<C>;
+    type BreakpointKind = usize;
+
+    fn target_description_xml() -> Option<&'static str> {
+        C::target_description_xml()
+    }
+
+    #[inline(always)]
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
+}
@@ -0,0 +1,200 @@
+use core::num::NonZeroUsize;
+
+use gdbstub::arch::RegId;
+
+use crate::xtensa::XtensaConfig;
+
+/// Xtensa register identifier.
+///
+/// GDB refers to the windowed address registers by their physical index
+/// (`ar0`-`arN`). The registers visible in the current window (`a0`-`a15`)
+/// depend on the value of `windowbase`, and can be mapped to their physical
+/// register using [`XtensaRegId::from_window`].
+///
+/// The numbering of the optional registers depends on the `C` configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XtensaRegId<C> {
+    /// Program counter
+    Pc,
+    /// Physical address registers (ar0-ar63)
+    Ar(u8),
+    /// Loop begin
+    Lbeg,
+    /// Loop end
+    Lend,
+    /// Loop count
+    Lcount,
+    /// Shift amount register
+    Sar,
+    /// Base of the current register window
+    Windowbase,
+    /// Bitmask of live register windows
+    Windowstart,
+    /// Processor configuration ID 0
+    Configid0,
+    /// Processor configuration ID 1
+    Configid1,
+    /// Processor state
+    Ps,
+    /// Thread pointer
+    Threadptr,
+    /// Boolean registers
+    Br,
+    /// Expected data value for `S32C1I`
+    Scompare1,
+    /// MAC16 accumulator (low)
+    Acclo,
+    /// MAC16 accumulator (high)
+    Acchi,
+    /// MAC16 data registers (m0-m3)
+    M(u8),
+    /// DFP accelerator exception state
+    Expstate,
+    /// DFP accelerator result (low)
+    F64rLo,
+    /// DFP accelerator result (high)
+    F64rHi,
+    /// DFP accelerator status
+    F64s,
+    /// Floating point registers (f0-f15)
+    Fpr(u8),
+    /// Floating point control register
+    Fcr,
+    /// Floating point status register
+    Fsr,
+
+    #[doc(hidden)]
+    _Marker(core::marker::PhantomData<C>),
+}
+
+impl<C: XtensaConfig> XtensaRegId<C> {
+    /// Map the visible address register `a<n>` (0-15) to its physical `Ar`
+    /// register, given the current value of `windowbase`.
+    ///
+    /// Only the low bits of `windowbase` which can address a window within
+    /// the `C::NUM_AREGS` physical registers are used.
+    pub fn from_window(windowbase: u32, n: u8) -> Self {
+        let base = if C::HAVE_WINDOWED {
+            crate::xtensa::window_base_index::<C>(windowbase)
+        } else {
+            0
+        };
+        XtensaRegId::Ar(((base + n as usize) % C::NUM_AREGS) as u8)
+    }
+}
+
+impl<C: XtensaConfig> RegId for XtensaRegId<C> {
+    // the final `group!` leaves `id` unused
+    #[allow(unused_assignments)]
+    fn from_raw_id(id: usize) -> Option<(Self, Option<NonZeroUsize>)> {
+        use self::XtensaRegId::*;
+
+        let mut id = id;
+
+        // Walk the register groups in order, skipping any which aren't present
+        // in this configuration.
+        macro_rules! group {
+            ($present:expr, [$($reg:expr),*]) => {
+                if $present {
+                    let regs = [$($reg),*];
+                    if let Some(reg) = regs.get(id) {
+                        return Some((*reg, Some(NonZeroUsize::new(4)?)));
+                    }
+                    id -= regs.len();
+                }
+            };
+            ($present:expr, $n:expr, $reg:expr) => {
+                if $present {
+                    if id < $n {
+                        return Some(($reg(id as u8), Some(NonZeroUsize::new(4)?)));
+                    }
+                    id -= $n;
+                }
+            };
+        }
+
+        group!(true, [Pc]);
+        group!(true, C::NUM_AREGS, Ar);
+        group!(C::HAVE_LOOPS, [Lbeg, Lend, Lcount]);
+        group!(true, [Sar]);
+        group!(C::HAVE_WINDOWED, [Windowbase, Windowstart]);
+        group!(true, [Configid0, Configid1, Ps]);
+        group!(C::HAVE_THREADPTR, [Threadptr]);
+        group!(C::HAVE_BOOLEANS, [Br]);
+        group!(C::HAVE_S32C1I, [Scompare1]);
+        group!(C::HAVE_MAC16, [Acclo, Acchi]);
+        group!(C::HAVE_MAC16, 4, M);
+        group!(C::HAVE_DFP_ACCEL, [Expstate, F64rLo, F64rHi, F64s]);
+        group!(C::HAVE_FP, 16, Fpr);
+        group!(C::HAVE_FP, [Fcr, Fsr]);
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::RegId;
+    use gdbstub::arch::Registers;
+
+    use super::XtensaRegId;
+    use crate::xtensa::{Esp32, Esp32S2};
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<Rs: Registers, RId: RegId>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |b: Option<u8>| {
+            if b.is_some() {
+                serialized_data_len += 1;
+            }
+        };
+        Rs::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = RId::from_raw_id(i) {
+            sum_reg_sizes += size.unwrap().get();
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_esp32() {
+        test::<crate::xtensa::reg::XtensaRegs<Esp32>, XtensaRegId<Esp32>>()
+    }
+
+    #[test]
+    fn test_esp32s2() {
+        test::<crate::xtensa::reg::XtensaRegs<Esp32S2>, XtensaRegId<Esp32S2>>()
+    }
+
+    #[test]
+    fn test_xtensa_numbering() {
+        let reg = |id| XtensaRegId::<Esp32>::from_raw_id(id).unwrap().0;
+        assert_eq!(reg(0), XtensaRegId::Pc);
+        assert_eq!(reg(64), XtensaRegId::Ar(63));
+        assert_eq!(reg(69), XtensaRegId::Windowbase);
+        assert_eq!(reg(73), XtensaRegId::Ps);
+        assert_eq!(reg(87), XtensaRegId::Fpr(0));
+        assert_eq!(reg(104), XtensaRegId::Fsr);
+        assert!(XtensaRegId::<Esp32>::from_raw_id(105).is_none());
+
+        let reg = |id| XtensaRegId::<Esp32S2>::from_raw_id(id).unwrap().0;
+        assert_eq!(reg(75), XtensaRegId::Scompare1);
+        assert!(XtensaRegId::<Esp32S2>::from_raw_id(76).is_none());
+
+        assert_eq!(XtensaRegId::<Esp32>::from_window(15, 4), XtensaRegId::Ar(0));
+        assert_eq!(
+            XtensaRegId::<Esp32>::from_window(u32::MAX, 3),
+            XtensaRegId::Ar(63)
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! `Register` structs for Xtensa architectures.
+
+/// `RegId` definitions for Xtensa architectures.
+pub mod id;
+
+mod xtensa;
+
+pub use xtensa::XtensaRegs;
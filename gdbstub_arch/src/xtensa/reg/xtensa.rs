@@ -0,0 +1,325 @@
+use core::convert::TryInto;
+use core::marker::PhantomData;
+
+use gdbstub::arch::Registers;
+
+use crate::xtensa::XtensaConfig;
+
+/// Xtensa registers.
+///
+/// Registers which are not present in the `C` configuration are not sent to
+/// GDB, and their fields are ignored.
+///
+/// The register order follows the `esp_gdbstub_gdb_regfile_t` struct in
+/// ESP-IDF's [`esp_gdbstub`](https://github.com/espressif/esp-idf/tree/master/components/esp_gdbstub)
+/// component, which matches the register numbering used by Espressif's GDB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XtensaRegs<C> {
+    /// Program counter
+    pub pc: u32,
+    /// Physical address registers (ar0-ar63)
+    ///
+    /// Only the first `C::NUM_AREGS` registers are used. See
+    /// [`XtensaRegs::a`] to access the registers visible in the current
+    /// window (a0-a15).
+    pub ar: [u32; 64],
+    /// Loop begin
+    pub lbeg: u32,
+    /// Loop end
+    pub lend: u32,
+    /// Loop count
+    pub lcount: u32,
+    /// Shift amount register
+    pub sar: u32,
+    /// Base of the current register window, in units of 4 registers
+    pub windowbase: u32,
+    /// Bitmask of live register windows
+    pub windowstart: u32,
+    /// Processor configuration ID 0
+    pub configid0: u32,
+    /// Processor configuration ID 1
+    pub configid1: u32,
+    /// Processor state
+    pub ps: u32,
+    /// Thread pointer
+    pub threadptr: u32,
+    /// Boolean registers
+    pub br: u32,
+    /// Expected data value for `S32C1I`
+    pub scompare1: u32,
+    /// MAC16 accumulator (low)
+    pub acclo: u32,
+    /// MAC16 accumulator (high)
+    pub acchi: u32,
+    /// MAC16 data registers (m0-m3)
+    pub m: [u32; 4],
+    /// DFP accelerator exception state
+    pub expstate: u32,
+    /// DFP accelerator result (low)
+    pub f64r_lo: u32,
+    /// DFP accelerator result (high)
+    pub f64r_hi: u32,
+    /// DFP accelerator status
+    pub f64s: u32,
+    /// Floating point registers (f0-f15), stored as raw bits
+    pub f: [u32; 16],
+    /// Floating point control register
+    pub fcr: u32,
+    /// Floating point status register
+    pub fsr: u32,
+
+    #[doc(hidden)]
+    pub _config: PhantomData<C>,
+}
+
+impl<C> Default for XtensaRegs<C> {
+    fn default() -> Self {
+        XtensaRegs {
+            pc: 0,
+            ar: [0; 64],
+            lbeg: 0,
+            lend: 0,
+            lcount: 0,
+            sar: 0,
+            windowbase: 0,
+            windowstart: 0,
+            configid0: 0,
+            configid1: 0,
+            ps: 0,
+            threadptr: 0,
+            br: 0,
+            scompare1: 0,
+            acclo: 0,
+            acchi: 0,
+            m: [0; 4],
+            expstate: 0,
+            f64r_lo: 0,
+            f64r_hi: 0,
+            f64s: 0,
+            f: [0; 16],
+            fcr: 0,
+            fsr: 0,
+            _config: PhantomData,
+        }
+    }
+}
+
+impl<C: XtensaConfig> XtensaRegs<C> {
+    /// Index into `ar` of the visible address register `a<n>` (0-15), based on
+    /// the current `windowbase`.
+    ///
+    /// Out-of-range `windowbase` values are masked, as on hardware.
+    pub fn a_index(&self, n: u8) -> usize {
+        let base = if C::HAVE_WINDOWED {
+            crate::xtensa::window_base_index::<C>(self.windowbase)
+        } else {
+            0
+        };
+        (base + n as usize) % C::NUM_AREGS
+    }
+
+    /// Read the visible address register `a<n>` (0-15).
+    pub fn a(&self, n: u8) -> u32 {
+        self.ar[self.a_index(n)]
+    }
+
+    /// Write the visible address register `a<n>` (0-15).
+    pub fn set_a(&mut self, n: u8, val: u32) {
+        let idx = self.a_index(n);
+        self.ar[idx] = val;
+    }
+
+    /// Number of bytes in the serialized register file.
+    fn len() -> usize {
+        let mut n = 1 + C::NUM_AREGS + 1 + 3;
+        if C::HAVE_LOOPS {
+            n += 3;
+        }
+        if C::HAVE_WINDOWED {
+            n += 2;
+        }
+        n += C::HAVE_THREADPTR as usize + C::HAVE_BOOLEANS as usize + C::HAVE_S32C1I as usize;
+        if C::HAVE_MAC16 {
+            n += 6;
+        }
+        if C::HAVE_DFP_ACCEL {
+            n += 4;
+        }
+        if C::HAVE_FP {
+            n += 18;
+        }
+        n * 4
+    }
+}
+
+impl<C: XtensaConfig> Registers for XtensaRegs<C> {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_regs {
+            ($($reg:expr),*) => {
+                $(
+                    for b in &$reg.to_le_bytes() {
+                        write_byte(Some(*b))
+                    }
+                )*
+            }
+        }
+
+        write_regs!(self.pc);
+        for reg in &self.ar[..C::NUM_AREGS] {
+            write_regs!(reg);
+        }
+        if C::HAVE_LOOPS {
+            write_regs!(self.lbeg, self.lend, self.lcount);
+        }
+        write_regs!(self.sar);
+        if C::HAVE_WINDOWED {
+            write_regs!(self.windowbase, self.windowstart);
+        }
+        write_regs!(self.configid0, self.configid1, self.ps);
+        if C::HAVE_THREADPTR {
+            write_regs!(self.threadptr);
+        }
+        if C::HAVE_BOOLEANS {
+            write_regs!(self.br);
+        }
+        if C::HAVE_S32C1I {
+            write_regs!(self.scompare1);
+        }
+        if C::HAVE_MAC16 {
+            write_regs!(self.acclo, self.acchi);
+            for reg in &self.m {
+                write_regs!(reg);
+            }
+        }
+        if C::HAVE_DFP_ACCEL {
+            write_regs!(self.expstate, self.f64r_lo, self.f64r_hi, self.f64s);
+        }
+        if C::HAVE_FP {
+            for reg in &self.f {
+                write_regs!(reg);
+            }
+            write_regs!(self.fcr, self.fsr);
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != Self::len() {
+            return Err(());
+        }
+
+        let mut regs = bytes
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes(x.try_into().unwrap()));
+
+        macro_rules! read_regs {
+            ($($reg:expr),*) => {
+                $(
+                    $reg = regs.next().ok_or(())?;
+                )*
+            }
+        }
+
+        read_regs!(self.pc);
+        for reg in self.ar[..C::NUM_AREGS].iter_mut() {
+            read_regs!(*reg);
+        }
+        if C::HAVE_LOOPS {
+            read_regs!(self.lbeg, self.lend, self.lcount);
+        }
+        read_regs!(self.sar);
+        if C::HAVE_WINDOWED {
+            read_regs!(self.windowbase, self.windowstart);
+        }
+        read_regs!(self.configid0, self.configid1, self.ps);
+        if C::HAVE_THREADPTR {
+            read_regs!(self.threadptr);
+        }
+        if C::HAVE_BOOLEANS {
+            read_regs!(self.br);
+        }
+        if C::HAVE_S32C1I {
+            read_regs!(self.scompare1);
+        }
+        if C::HAVE_MAC16 {
+            read_regs!(self.acclo, self.acchi);
+            for reg in self.m.iter_mut() {
+                read_regs!(*reg);
+            }
+        }
+        if C::HAVE_DFP_ACCEL {
+            read_regs!(self.expstate, self.f64r_lo, self.f64r_hi, self.f64s);
+        }
+        if C::HAVE_FP {
+            for reg in self.f.iter_mut() {
+                read_regs!(*reg);
+            }
+            read_regs!(self.fcr, self.fsr);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xtensa::{Esp32, Esp32S2};
+
+    fn round_trip<C: XtensaConfig>() {
+        let mut regs_before = XtensaRegs::<C> {
+            pc: 0x400d_1234,
+            sar: 3,
+            windowbase: 2,
+            windowstart: 0b101,
+            ps: 0x0006_0020,
+            ..Default::default()
+        };
+        for (i, reg) in regs_before.ar.iter_mut().enumerate() {
+            *reg = 0x3ffb_0000 + i as u32;
+        }
+
+        let mut data = vec![];
+
+        regs_before.gdb_serialize(|x| {
+            data.push(x.unwrap_or(b'x'));
+        });
+
+        assert_eq!(data.len(), XtensaRegs::<C>::len());
+
+        let mut regs_after = XtensaRegs::<C>::default();
+        regs_after.gdb_deserialize(&data).unwrap();
+
+        assert_eq!(regs_before, regs_after);
+    }
+
+    #[test]
+    fn xtensa_round_trip() {
+        round_trip::<Esp32>();
+        round_trip::<Esp32S2>();
+    }
+
+    #[test]
+    fn xtensa_windowed_regs() {
+        let mut regs = XtensaRegs::<Esp32> {
+            windowbase: 15,
+            ..Default::default()
+        };
+
+        // a0-a3 are ar60-ar63, and a4 wraps around to ar0
+        regs.set_a(3, 0x1234);
+        regs.set_a(4, 0x5678);
+        assert_eq!(regs.ar[63], 0x1234);
+        assert_eq!(regs.ar[0], 0x5678);
+        assert_eq!(regs.a(4), 0x5678);
+
+        // bogus windowbase values (e.g: from GDB) are masked, not overflowed
+        regs.windowbase = u32::MAX;
+        assert_eq!(regs.a_index(3), 63);
+    }
+}
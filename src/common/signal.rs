@@ -3,6 +3,7 @@
 /// Transcribed from <https://github.com/bminor/binutils-gdb/blob/master/include/gdb/signals.def>
 #[repr(u8)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::upper_case_acronyms)]
 #[allow(non_camel_case_types)]
 #[rustfmt::skip]
@@ -0,0 +1,122 @@
+//! Adapts an [`AsyncConnection`] into a buffered [`Connection`] for the
+//! async event loop.
+
+use core::fmt::{self, Debug, Display};
+
+use managed::ManagedSlice;
+
+use crate::conn::{AsyncConnection, Connection};
+
+/// An error which may occur when writing to an [`AsyncConnectionAdapter`].
+#[derive(Debug)]
+pub enum AsyncConnectionAdapterError<E> {
+    /// The underlying [`AsyncConnection`] returned an error.
+    Connection(E),
+    /// The fixed-size buffer provided via
+    /// [`AsyncConnectionAdapter::with_buffer`] was too small to hold the
+    /// stub's pending output.
+    BufferFull,
+}
+
+impl<E: Debug> Display for AsyncConnectionAdapterError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::AsyncConnectionAdapterError::*;
+        match self {
+            Connection(e) => write!(f, "{:?}", e),
+            BufferFull => write!(f, "Async connection adapter's buffer is full"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: Debug> std::error::Error for AsyncConnectionAdapterError<E> {}
+
+/// Adapts an [`AsyncConnection`] into a (synchronous) [`Connection`] that can
+/// be used with [`GdbStub`](crate::stub::GdbStub).
+///
+/// Data written by the stub is buffered, and is written out to the underlying
+/// `AsyncConnection` by calling [`drain`](Self::drain). When using
+/// [`GdbStub::run_async`](crate::stub::GdbStub::run_async), this is done
+/// automatically.
+pub struct AsyncConnectionAdapter<'b, C> {
+    conn: C,
+    tx: ManagedSlice<'b, u8>,
+    tx_len: usize,
+}
+
+impl<'b, C: AsyncConnection> AsyncConnectionAdapter<'b, C> {
+    /// Create a new `AsyncConnectionAdapter` with a growable, heap-allocated
+    /// buffer.
+    ///
+    /// _Note:_ `new` is only available when the `alloc` feature is enabled.
+    /// Use [`with_buffer`](Self::with_buffer) in `#![no_std]` contexts.
+    #[cfg(feature = "alloc")]
+    pub fn new(conn: C) -> AsyncConnectionAdapter<'static, C> {
+        AsyncConnectionAdapter {
+            conn,
+            tx: ManagedSlice::Owned(alloc::vec::Vec::new()),
+            tx_len: 0,
+        }
+    }
+
+    /// Create a new `AsyncConnectionAdapter` using a fixed-size buffer.
+    ///
+    /// The buffer must be large enough to hold the stub's largest response
+    /// (i.e: at least twice the size of the `GdbStub`'s packet buffer, to
+    /// account for escaped bytes). If the buffer overflows, an
+    /// [`AsyncConnectionAdapterError::BufferFull`] error is returned.
+    pub fn with_buffer(conn: C, buf: &'b mut [u8]) -> AsyncConnectionAdapter<'b, C> {
+        AsyncConnectionAdapter {
+            conn,
+            tx: ManagedSlice::Borrowed(buf),
+            tx_len: 0,
+        }
+    }
+
+    /// Return a mutable reference to the underlying `AsyncConnection`.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.conn
+    }
+
+    /// Consume the adapter, returning the underlying `AsyncConnection`.
+    ///
+    /// Any buffered data which has not yet been drained is discarded.
+    pub fn into_inner(self) -> C {
+        self.conn
+    }
+
+    /// Write any buffered data out to the underlying `AsyncConnection`, and
+    /// flush it.
+    pub async fn drain(&mut self) -> Result<(), C::Error> {
+        if self.tx_len == 0 {
+            return Ok(());
+        }
+
+        self.conn.write_all(&self.tx[..self.tx_len]).await?;
+        self.tx_len = 0;
+        self.conn.flush().await
+    }
+}
+
+impl<'b, C: AsyncConnection> Connection for AsyncConnectionAdapter<'b, C> {
+    type Error = AsyncConnectionAdapterError<C::Error>;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        if self.tx_len < self.tx.len() {
+            self.tx[self.tx_len] = byte;
+        } else {
+            match &mut self.tx {
+                #[cfg(feature = "alloc")]
+                ManagedSlice::Owned(buf) => buf.push(byte),
+                _ => return Err(AsyncConnectionAdapterError::BufferFull),
+            }
+        }
+        self.tx_len += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // buffered data is written out via `drain`
+        Ok(())
+    }
+}
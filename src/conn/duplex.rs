@@ -0,0 +1,616 @@
+//! In-memory [`Connection`]s, useful for testing, and for in-process GDB
+//! clients.
+
+use core::fmt::{self, Debug, Display};
+
+use managed::ManagedSlice;
+
+use crate::conn::{Connection, ConnectionExt};
+
+/// Faults to inject into an in-memory connection's byte stream, in order to
+/// exercise error-handling paths.
+///
+/// Faults are applied deterministically, based on the number of bytes /
+/// packets which have passed through the stream. By default, no faults are
+/// injected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Faults {
+    drop_every: usize,
+    corrupt_checksum_every: usize,
+}
+
+impl Faults {
+    /// Create a new `Faults` which doesn't inject any faults.
+    pub fn new() -> Faults {
+        Faults::default()
+    }
+
+    /// Drop every `n`th byte (i.e: the `n`th, `2n`th, etc...). A value of `0`
+    /// disables dropping bytes.
+    pub fn drop_every(mut self, n: usize) -> Faults {
+        self.drop_every = n;
+        self
+    }
+
+    /// Corrupt the checksum of every `n`th packet (i.e: the `n`th, `2n`th,
+    /// etc...). A value of `0` disables corrupting checksums.
+    ///
+    /// Corrupted checksums remain valid hex digits, and will therefore be
+    /// reported as a checksum mismatch (rather than as a malformed packet).
+    pub fn corrupt_checksum_every(mut self, n: usize) -> Faults {
+        self.corrupt_checksum_every = n;
+        self
+    }
+}
+
+/// Applies [`Faults`] to a byte stream.
+#[derive(Debug, Default)]
+struct FaultState {
+    faults: Faults,
+    bytes: usize,
+    packets: usize,
+    checksum_digits: u8,
+    corrupt: bool,
+}
+
+impl FaultState {
+    fn new(faults: Faults) -> FaultState {
+        FaultState {
+            faults,
+            ..FaultState::default()
+        }
+    }
+
+    /// Returns `None` if the byte should be dropped.
+    fn apply(&mut self, byte: u8) -> Option<u8> {
+        self.bytes += 1;
+        if self.faults.drop_every != 0 && self.bytes % self.faults.drop_every == 0 {
+            return None;
+        }
+
+        if self.checksum_digits != 0 {
+            self.checksum_digits -= 1;
+            // corrupt the checksum's first digit
+            if self.corrupt && self.checksum_digits == 1 {
+                return Some(if byte == b'0' { b'1' } else { b'0' });
+            }
+        } else if byte == b'#' {
+            self.checksum_digits = 2;
+            self.packets += 1;
+            self.corrupt = self.faults.corrupt_checksum_every != 0
+                && self.packets % self.faults.corrupt_checksum_every == 0;
+        }
+
+        Some(byte)
+    }
+}
+
+/// Frame `payload` as a `$payload#checksum` packet, passing each byte to `f`.
+fn frame_packet<E>(payload: &[u8], mut f: impl FnMut(u8) -> Result<(), E>) -> Result<(), E> {
+    f(b'$')?;
+    let mut checksum: u8 = 0;
+    for &b in payload {
+        checksum = checksum.wrapping_add(b);
+        f(b)?;
+    }
+    f(b'#')?;
+
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    f(HEX_DIGITS[(checksum >> 4) as usize])?;
+    f(HEX_DIGITS[(checksum & 0xf) as usize])
+}
+
+/// A FIFO byte buffer, backed by either a fixed-size or growable slice.
+struct Fifo<'b> {
+    buf: ManagedSlice<'b, u8>,
+    start: usize,
+    end: usize,
+}
+
+impl<'b> Fifo<'b> {
+    fn new(buf: ManagedSlice<'b, u8>) -> Fifo<'b> {
+        Fifo {
+            buf,
+            start: 0,
+            end: 0,
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> Result<(), MemConnectionError> {
+        if self.end == self.buf.len() && self.start != 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+
+        if self.end < self.buf.len() {
+            self.buf[self.end] = byte;
+        } else {
+            match &mut self.buf {
+                #[cfg(feature = "alloc")]
+                ManagedSlice::Owned(buf) => buf.push(byte),
+                _ => return Err(MemConnectionError::BufferFull),
+            }
+        }
+        self.end += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.start += 1;
+        Some(byte)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        if self.start == self.end {
+            None
+        } else {
+            Some(self.buf[self.start])
+        }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    fn clear(&mut self) {
+        self.start = 0;
+        self.end = 0;
+    }
+}
+
+/// An error which may occur when using a [`MemConnection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemConnectionError {
+    /// A fixed-size buffer provided via [`MemConnection::with_buffers`] is
+    /// full.
+    BufferFull,
+    /// The stub attempted to read data, but all injected data has already
+    /// been consumed.
+    Exhausted,
+}
+
+impl Display for MemConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::MemConnectionError::*;
+        match self {
+            BufferFull => write!(f, "In-memory connection's buffer is full"),
+            Exhausted => write!(f, "In-memory connection has no more data to read"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MemConnectionError {}
+
+/// A single-threaded, in-memory [`Connection`], which can be used in
+/// `#![no_std]` contexts.
+///
+/// Data read by the stub is scripted up-front (or between calls into a
+/// [`GdbStubStateMachine`](crate::stub::state_machine::GdbStubStateMachine))
+/// via [`inject`](Self::inject) / [`inject_packet`](Self::inject_packet),
+/// and all data written by the stub is captured, and can be inspected via
+/// [`output`](Self::output).
+///
+/// As there is no peer to wait on, reading from an empty `MemConnection`
+/// returns a [`MemConnectionError::Exhausted`] error, instead of blocking.
+pub struct MemConnection<'b> {
+    rx: Fifo<'b>,
+    tx: Fifo<'b>,
+    faults: FaultState,
+}
+
+impl<'b> MemConnection<'b> {
+    /// Create a new `MemConnection` with growable, heap-allocated buffers.
+    ///
+    /// _Note:_ `new` is only available when the `alloc` feature is enabled.
+    /// Use [`with_buffers`](Self::with_buffers) in `#![no_std]` contexts.
+    #[cfg(feature = "alloc")]
+    pub fn new() -> MemConnection<'static> {
+        MemConnection {
+            rx: Fifo::new(ManagedSlice::Owned(alloc::vec::Vec::new())),
+            tx: Fifo::new(ManagedSlice::Owned(alloc::vec::Vec::new())),
+            faults: FaultState::default(),
+        }
+    }
+
+    /// Create a new `MemConnection` using fixed-size buffers for injected
+    /// (`rx`) and captured (`tx`) data.
+    pub fn with_buffers(rx: &'b mut [u8], tx: &'b mut [u8]) -> MemConnection<'b> {
+        MemConnection {
+            rx: Fifo::new(ManagedSlice::Borrowed(rx)),
+            tx: Fifo::new(ManagedSlice::Borrowed(tx)),
+            faults: FaultState::default(),
+        }
+    }
+
+    /// Set the faults to inject into subsequently injected data.
+    pub fn set_faults(&mut self, faults: Faults) {
+        self.faults = FaultState::new(faults);
+    }
+
+    /// Queue up raw bytes to be read by the stub.
+    pub fn inject(&mut self, data: &[u8]) -> Result<(), MemConnectionError> {
+        for &b in data {
+            self.inject_byte(b)?;
+        }
+        Ok(())
+    }
+
+    /// Queue up a `$payload#checksum` packet to be read by the stub.
+    pub fn inject_packet(&mut self, payload: &[u8]) -> Result<(), MemConnectionError> {
+        frame_packet(payload, |b| self.inject_byte(b))
+    }
+
+    fn inject_byte(&mut self, byte: u8) -> Result<(), MemConnectionError> {
+        match self.faults.apply(byte) {
+            Some(byte) => self.rx.push(byte),
+            None => Ok(()),
+        }
+    }
+
+    /// Return any injected data which has yet to be read by the stub.
+    pub fn pending(&self) -> &[u8] {
+        self.rx.as_slice()
+    }
+
+    /// Return all data written by the stub (since the last call to
+    /// [`clear_output`](Self::clear_output)).
+    pub fn output(&self) -> &[u8] {
+        self.tx.as_slice()
+    }
+
+    /// Discard all captured output.
+    pub fn clear_output(&mut self) {
+        self.tx.clear()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for MemConnection<'static> {
+    fn default() -> MemConnection<'static> {
+        MemConnection::new()
+    }
+}
+
+impl Debug for MemConnection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemConnection")
+            .field("pending", &self.pending())
+            .field("output", &self.output())
+            .field("faults", &self.faults.faults)
+            .finish()
+    }
+}
+
+impl Connection for MemConnection<'_> {
+    type Error = MemConnectionError;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.tx.push(byte)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ConnectionExt for MemConnection<'_> {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.rx.pop().ok_or(MemConnectionError::Exhausted)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(self.rx.peek())
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::std_duplex::DuplexConnection;
+
+#[cfg(feature = "std")]
+mod std_duplex {
+    use super::{frame_packet, FaultState, Faults};
+
+    use std::collections::VecDeque;
+    use std::io;
+    use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+    use std::time::{Duration, Instant};
+
+    use crate::conn::{Connection, ConnectionExt};
+
+    /// One direction of a duplex connection.
+    #[derive(Default)]
+    struct Channel {
+        queue: VecDeque<u8>,
+        log: Vec<u8>,
+    }
+
+    #[derive(Default)]
+    struct State {
+        channels: [Channel; 2],
+        closed: bool,
+    }
+
+    #[derive(Default)]
+    struct Shared {
+        state: Mutex<State>,
+        cond: Condvar,
+    }
+
+    impl Shared {
+        fn lock(&self) -> MutexGuard<'_, State> {
+            // a poisoned lock only implies that a thread holding the other end
+            // panicked, which doesn't leave the buffers in an invalid state
+            match self.state.lock() {
+                Ok(state) => state,
+                Err(poisoned) => poisoned.into_inner(),
+            }
+        }
+    }
+
+    /// One end of a thread-safe, in-memory, duplex [`Connection`] pair.
+    ///
+    /// Typically, one end is passed to the `GdbStub`, while the other end is
+    /// used to script a GDB client (e.g: from a test, or from a GDB client
+    /// running in the same process).
+    ///
+    /// Each end records all data it receives, which can be inspected via
+    /// [`received`](Self::received). Reads block until data is available,
+    /// the optional read timeout expires, or the peer is dropped.
+    pub struct DuplexConnection {
+        shared: Arc<Shared>,
+        side: usize,
+        faults: FaultState,
+        read_timeout: Option<Duration>,
+    }
+
+    impl DuplexConnection {
+        /// Create a new pair of connected `DuplexConnection`s.
+        pub fn pair() -> (DuplexConnection, DuplexConnection) {
+            let shared = Arc::new(Shared::default());
+            let end = |side| DuplexConnection {
+                shared: shared.clone(),
+                side,
+                faults: FaultState::default(),
+                read_timeout: None,
+            };
+            (end(0), end(1))
+        }
+
+        /// Set the faults to inject into data subsequently written by this
+        /// end.
+        pub fn set_faults(&mut self, faults: Faults) {
+            self.faults = FaultState::new(faults);
+        }
+
+        /// Set the maximum amount of time a read will block for, after which
+        /// an [`io::ErrorKind::TimedOut`] error is returned.
+        ///
+        /// Defaults to `None` (i.e: block indefinitely).
+        pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+            self.read_timeout = timeout;
+        }
+
+        /// Send a `$payload#checksum` packet to the peer.
+        pub fn inject_packet(&mut self, payload: &[u8]) -> io::Result<()> {
+            let shared = self.shared.clone();
+            let mut state = shared.lock();
+            frame_packet(payload, |b| self.send(&mut state, b))?;
+            drop(state);
+            shared.cond.notify_all();
+            Ok(())
+        }
+
+        /// Read the peer's next `$payload#checksum` packet, returning its
+        /// payload. Any data preceding the packet (e.g: `+` acks) is skipped.
+        ///
+        /// Returns an [`io::ErrorKind::InvalidData`] error if the packet's
+        /// checksum doesn't match its payload.
+        pub fn read_packet(&mut self) -> io::Result<Vec<u8>> {
+            while self.read()? != b'$' {}
+
+            let mut payload = Vec::new();
+            let mut checksum: u8 = 0;
+            loop {
+                match self.read()? {
+                    b'#' => break,
+                    b => {
+                        checksum = checksum.wrapping_add(b);
+                        payload.push(b);
+                    }
+                }
+            }
+
+            let digits = [self.read()?, self.read()?];
+            let expected = core::str::from_utf8(&digits)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok());
+            if expected != Some(checksum) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "packet checksum mismatch",
+                ));
+            }
+
+            Ok(payload)
+        }
+
+        /// Return all data this end has received from the peer (regardless of
+        /// whether it has been read yet).
+        pub fn received(&self) -> Vec<u8> {
+            self.lock().channels[self.side ^ 1].log.clone()
+        }
+
+        fn lock(&self) -> MutexGuard<'_, State> {
+            self.shared.lock()
+        }
+
+        fn send(&mut self, state: &mut State, byte: u8) -> io::Result<()> {
+            if state.closed {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+
+            if let Some(byte) = self.faults.apply(byte) {
+                let channel = &mut state.channels[self.side];
+                channel.queue.push_back(byte);
+                channel.log.push(byte);
+            }
+            Ok(())
+        }
+
+        fn recv(&mut self, consume: bool, block: bool) -> io::Result<Option<u8>> {
+            let deadline = self.read_timeout.map(|t| Instant::now() + t);
+
+            let mut state = self.lock();
+            loop {
+                let queue = &mut state.channels[self.side ^ 1].queue;
+                let byte = if consume {
+                    queue.pop_front()
+                } else {
+                    queue.front().copied()
+                };
+
+                match byte {
+                    Some(byte) => return Ok(Some(byte)),
+                    None if !block => return Ok(None),
+                    None if state.closed => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    None => {}
+                }
+
+                state = match deadline {
+                    None => self
+                        .shared
+                        .cond
+                        .wait(state)
+                        .unwrap_or_else(|e| e.into_inner()),
+                    Some(deadline) => {
+                        let timeout = deadline.saturating_duration_since(Instant::now());
+                        if timeout == Duration::ZERO {
+                            return Err(io::ErrorKind::TimedOut.into());
+                        }
+                        self.shared
+                            .cond
+                            .wait_timeout(state, timeout)
+                            .unwrap_or_else(|e| e.into_inner())
+                            .0
+                    }
+                };
+            }
+        }
+    }
+
+    impl Drop for DuplexConnection {
+        fn drop(&mut self) {
+            self.lock().closed = true;
+            self.shared.cond.notify_all();
+        }
+    }
+
+    impl std::fmt::Debug for DuplexConnection {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("DuplexConnection")
+                .field("side", &self.side)
+                .field("faults", &self.faults.faults)
+                .field("read_timeout", &self.read_timeout)
+                .finish()
+        }
+    }
+
+    impl Connection for DuplexConnection {
+        type Error = io::Error;
+
+        fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+            self.write_all(&[byte])
+        }
+
+        fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+            let shared = self.shared.clone();
+            let mut state = shared.lock();
+            for &b in buf {
+                self.send(&mut state, b)?;
+            }
+            drop(state);
+            shared.cond.notify_all();
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl ConnectionExt for DuplexConnection {
+        fn read(&mut self) -> Result<u8, Self::Error> {
+            match self.recv(true, true)? {
+                Some(byte) => Ok(byte),
+                None => unreachable!("blocking reads always return a byte"),
+            }
+        }
+
+        fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+            self.recv(false, false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fault_injection() {
+        let mut rx = [0; 64];
+        let mut tx = [0; 4];
+        let mut conn = MemConnection::with_buffers(&mut rx, &mut tx);
+
+        conn.inject_packet(b"g").unwrap();
+        conn.set_faults(Faults::new().corrupt_checksum_every(2));
+        conn.inject_packet(b"g").unwrap();
+        conn.inject_packet(b"g").unwrap();
+        conn.set_faults(Faults::new().drop_every(3));
+        conn.inject(b"+-+-+-").unwrap();
+        assert_eq!(conn.pending(), b"$g#67$g#67$g#07+--+");
+
+        assert_eq!(conn.peek(), Ok(Some(b'$')));
+        assert_eq!(conn.read(), Ok(b'$'));
+
+        conn.write_all(b"+$OK").unwrap();
+        assert_eq!(conn.write(b'#'), Err(MemConnectionError::BufferFull));
+        assert_eq!(conn.output(), b"+$OK");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn duplex_pair() {
+        use std::time::Duration;
+
+        let (mut stub, mut client) = DuplexConnection::pair();
+        client.set_read_timeout(Some(Duration::from_millis(10)));
+
+        assert_eq!(stub.peek().unwrap(), None);
+        client.inject_packet(b"?").unwrap();
+        assert_eq!(stub.peek().unwrap(), Some(b'$'));
+
+        let handle = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            while buf.last() != Some(&b'f') {
+                buf.push(stub.read().unwrap());
+            }
+            assert_eq!(buf, b"$?#3f");
+            stub.write_all(b"+$S05#b8").unwrap();
+        });
+
+        client.set_read_timeout(Some(Duration::from_secs(5)));
+        assert_eq!(client.read_packet().unwrap(), b"S05");
+        assert_eq!(client.received(), b"+$S05#b8");
+        handle.join().unwrap();
+
+        // the stub's end has been dropped
+        assert_eq!(
+            client.read().unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! A [`Connection`] over an `embedded-hal-nb` serial peripheral.
+
+use embedded_hal_nb::serial::{Read, Write};
+
+use crate::conn::{Connection, ConnectionExt};
+
+/// Adapts an [`embedded-hal-nb`](embedded_hal_nb) serial peripheral (e.g: a
+/// HAL's UART driver) into a [`Connection`].
+///
+/// `Connection` is implemented for any `S: serial::Write<u8>`, and
+/// `ConnectionExt` is implemented for any `S: serial::Read<u8> +
+/// serial::Write<u8>`. Blocking operations spin on the underlying
+/// non-blocking `nb` operations until they complete.
+///
+/// [`ConnectionExt::peek`] is implemented using a one-byte lookahead buffer:
+/// if a byte is available, it is read from the peripheral and buffered until
+/// the next call to [`ConnectionExt::read`].
+#[derive(Debug)]
+pub struct NbSerialConnection<S> {
+    inner: S,
+    peeked: Option<u8>,
+}
+
+impl<S> NbSerialConnection<S> {
+    /// Create a new `NbSerialConnection`.
+    pub fn new(inner: S) -> NbSerialConnection<S> {
+        NbSerialConnection {
+            inner,
+            peeked: None,
+        }
+    }
+
+    /// Return a mutable reference to the underlying peripheral.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume the adapter, returning the underlying peripheral.
+    ///
+    /// _Note:_ any byte buffered by [`ConnectionExt::peek`] is lost.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Write<u8>> Connection for NbSerialConnection<S> {
+    type Error = S::Error;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        nb::block!(self.inner.write(byte))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        nb::block!(self.inner.flush())
+    }
+}
+
+impl<S: Read<u8> + Write<u8>> ConnectionExt for NbSerialConnection<S> {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        match self.peeked.take() {
+            Some(byte) => Ok(byte),
+            None => nb::block!(self.inner.read()),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.peeked.is_none() {
+            match self.inner.read() {
+                Ok(byte) => self.peeked = Some(byte),
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+        Ok(self.peeked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_nb::serial::{ErrorKind, ErrorType};
+
+    use super::*;
+
+    /// A UART which replays a scripted sequence of `read` results, and records
+    /// every byte written to it.
+    struct MockUart<'a> {
+        rx: &'a [nb::Result<u8, ErrorKind>],
+        tx: [u8; 8],
+        tx_len: usize,
+    }
+
+    impl ErrorType for MockUart<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read<u8> for MockUart<'_> {
+        fn read(&mut self) -> nb::Result<u8, Self::Error> {
+            match self.rx.split_first() {
+                Some((&res, rest)) => {
+                    self.rx = rest;
+                    res
+                }
+                None => Err(nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    impl Write<u8> for MockUart<'_> {
+        fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+            if self.tx_len == self.tx.len() {
+                return Err(nb::Error::Other(ErrorKind::Overrun));
+            }
+            self.tx[self.tx_len] = word;
+            self.tx_len += 1;
+            Ok(())
+        }
+
+        fn flush(&mut self) -> nb::Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn peek_lookahead() {
+        let mut conn = NbSerialConnection::new(MockUart {
+            rx: &[
+                Err(nb::Error::WouldBlock),
+                Ok(b'$'),
+                Err(nb::Error::WouldBlock),
+                Ok(b'?'),
+                Err(nb::Error::Other(ErrorKind::Parity)),
+                Err(nb::Error::WouldBlock),
+                Err(nb::Error::Other(ErrorKind::Noise)),
+            ],
+            tx: [0; 8],
+            tx_len: 0,
+        });
+
+        assert_eq!(conn.peek(), Ok(None));
+        assert_eq!(conn.peek(), Ok(Some(b'$')));
+        assert_eq!(conn.peek(), Ok(Some(b'$')));
+        assert_eq!(conn.read(), Ok(b'$'));
+        // `read` spins past `WouldBlock` until a byte arrives
+        assert_eq!(conn.read(), Ok(b'?'));
+        assert_eq!(conn.peek(), Err(ErrorKind::Parity));
+        assert_eq!(conn.read(), Err(ErrorKind::Noise));
+        assert_eq!(conn.peek(), Ok(None));
+
+        assert_eq!(conn.write_all(b"+$OK#9a+"), Ok(()));
+        assert_eq!(conn.write(b'+'), Err(ErrorKind::Overrun));
+        assert_eq!(&conn.get_mut().tx, b"+$OK#9a+");
+    }
+}
@@ -0,0 +1,196 @@
+//! A [`Connection`] over an `embedded-io` stream.
+
+use core::fmt::{self, Debug, Display};
+
+use embedded_io::{Read, ReadReady, Write};
+
+use crate::conn::{Connection, ConnectionExt};
+
+/// An error which may occur when using an [`EmbeddedIoConnection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddedIoConnectionError<E> {
+    /// The underlying `embedded-io` stream returned an error.
+    Io(E),
+    /// The underlying stream reached EOF.
+    UnexpectedEof,
+    /// The underlying stream stopped accepting data.
+    WriteZero,
+}
+
+impl<E: Debug> Display for EmbeddedIoConnectionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::EmbeddedIoConnectionError::*;
+        match self {
+            Io(e) => write!(f, "{:?}", e),
+            UnexpectedEof => write!(f, "Unexpected EOF"),
+            WriteZero => write!(f, "Failed to write whole buffer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: Debug> std::error::Error for EmbeddedIoConnectionError<E> {}
+
+/// Adapts an [`embedded-io`](embedded_io) stream (e.g: a HAL's UART driver)
+/// into a [`Connection`].
+///
+/// `Connection` is implemented for any `T: Write`, and `ConnectionExt` is
+/// implemented for any `T: Read + ReadReady + Write`.
+///
+/// [`ConnectionExt::peek`] is implemented using a one-byte lookahead buffer:
+/// if [`ReadReady::read_ready`] reports that data is available, a single byte
+/// is read from the stream and buffered until the next call to
+/// [`ConnectionExt::read`].
+#[derive(Debug)]
+pub struct EmbeddedIoConnection<T> {
+    inner: T,
+    peeked: Option<u8>,
+}
+
+impl<T> EmbeddedIoConnection<T> {
+    /// Create a new `EmbeddedIoConnection`.
+    pub fn new(inner: T) -> EmbeddedIoConnection<T> {
+        EmbeddedIoConnection {
+            inner,
+            peeked: None,
+        }
+    }
+
+    /// Return a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consume the adapter, returning the underlying stream.
+    ///
+    /// _Note:_ any byte buffered by [`ConnectionExt::peek`] is lost.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Read> EmbeddedIoConnection<T> {
+    fn read_byte(&mut self) -> Result<u8, EmbeddedIoConnectionError<T::Error>> {
+        let mut buf = [0];
+        match self.inner.read(&mut buf) {
+            Ok(0) => Err(EmbeddedIoConnectionError::UnexpectedEof),
+            Ok(_) => Ok(buf[0]),
+            Err(e) => Err(EmbeddedIoConnectionError::Io(e)),
+        }
+    }
+}
+
+impl<T: Write> Connection for EmbeddedIoConnection<T> {
+    type Error = EmbeddedIoConnectionError<T::Error>;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.write_all(&[byte])
+    }
+
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Self::Error> {
+        // `embedded_io::Write::write_all` panics if the stream stops accepting
+        // data, so re-implement it here
+        while !buf.is_empty() {
+            match self.inner.write(buf) {
+                Ok(0) => return Err(EmbeddedIoConnectionError::WriteZero),
+                Ok(n) => buf = buf.get(n..).unwrap_or(&[]),
+                Err(e) => return Err(EmbeddedIoConnectionError::Io(e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().map_err(EmbeddedIoConnectionError::Io)
+    }
+}
+
+impl<T: Read + ReadReady + Write> ConnectionExt for EmbeddedIoConnection<T> {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        match self.peeked.take() {
+            Some(byte) => Ok(byte),
+            None => self.read_byte(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.peeked.is_none()
+            && self
+                .inner
+                .read_ready()
+                .map_err(EmbeddedIoConnectionError::Io)?
+        {
+            self.peeked = Some(self.read_byte()?);
+        }
+        Ok(self.peeked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A UART which receives `rx` one byte at a time, and accepts at most 2
+    /// bytes per write.
+    struct MockUart<'a> {
+        rx: &'a [u8],
+        tx: [u8; 8],
+        tx_len: usize,
+    }
+
+    impl embedded_io::ErrorType for MockUart<'_> {
+        type Error = embedded_io::ErrorKind;
+    }
+
+    impl Read for MockUart<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            match self.rx.split_first() {
+                Some((&b, rest)) => {
+                    buf[0] = b;
+                    self.rx = rest;
+                    Ok(1)
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    impl ReadReady for MockUart<'_> {
+        fn read_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.rx.is_empty())
+        }
+    }
+
+    impl Write for MockUart<'_> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let n = buf.len().min(2).min(self.tx.len() - self.tx_len);
+            self.tx[self.tx_len..][..n].copy_from_slice(&buf[..n]);
+            self.tx_len += n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn peek_lookahead() {
+        let mut conn = EmbeddedIoConnection::new(MockUart {
+            rx: b"$?",
+            tx: [0; 8],
+            tx_len: 0,
+        });
+
+        assert_eq!(conn.peek(), Ok(Some(b'$')));
+        assert_eq!(conn.peek(), Ok(Some(b'$')));
+        assert_eq!(conn.read(), Ok(b'$'));
+        assert_eq!(conn.read(), Ok(b'?'));
+        assert_eq!(conn.peek(), Ok(None));
+        assert_eq!(conn.read(), Err(EmbeddedIoConnectionError::UnexpectedEof));
+
+        assert_eq!(conn.write_all(b"+$OK#9a+"), Ok(()));
+        assert_eq!(conn.write(b'+'), Err(EmbeddedIoConnectionError::WriteZero));
+        assert_eq!(&conn.get_mut().tx, b"+$OK#9a+");
+    }
+}
@@ -1,6 +1,41 @@
 //! Traits to perform in-order, serial, byte-wise I/O.
 
+mod async_adapter;
+mod duplex;
+#[cfg(feature = "embedded-hal-nb")]
+mod embedded_hal_nb;
+#[cfg(feature = "embedded-io")]
+mod embedded_io;
 mod impls;
+#[cfg(all(feature = "std", unix))]
+mod pipe;
+#[cfg(target_has_atomic = "32")]
+mod ring;
+#[cfg(all(feature = "serial", unix))]
+mod serial;
+#[cfg(feature = "std")]
+mod websocket;
+
+pub use async_adapter::{AsyncConnectionAdapter, AsyncConnectionAdapterError};
+#[cfg(feature = "std")]
+pub use duplex::DuplexConnection;
+pub use duplex::{Faults, MemConnection, MemConnectionError};
+#[cfg(feature = "embedded-hal-nb")]
+pub use embedded_hal_nb::NbSerialConnection;
+#[cfg(feature = "embedded-io")]
+pub use embedded_io::{EmbeddedIoConnection, EmbeddedIoConnectionError};
+#[cfg(all(feature = "std", unix))]
+pub use pipe::PipeConnection;
+#[cfg(all(feature = "std", target_has_atomic = "32"))]
+pub use ring::{relay_stream, relay_tcp};
+#[cfg(target_has_atomic = "32")]
+pub use ring::{
+    ring_region_len, RingConnection, RingError, RING_HEADER_LEN, RING_MAGIC, RING_VERSION,
+};
+#[cfg(all(feature = "serial", unix))]
+pub use serial::{FlowControl, Parity, SerialConfig, SerialConnection};
+#[cfg(feature = "std")]
+pub use websocket::WebSocketConnection;
 
 /// A trait to perform in-order, serial, byte-wise I/O.
 ///
@@ -74,3 +109,38 @@ pub trait ConnectionExt: Connection {
     /// queue. Subsequent calls to `peek` MUST return the same byte.
     fn peek(&mut self) -> Result<Option<u8>, Self::Error>;
 }
+
+/// A trait to perform in-order, serial, byte-wise I/O asynchronously.
+///
+/// This trait is used as part of `gdbstub`'s
+/// [`GdbStub::run_async`](crate::stub::GdbStub::run_async) API, and only
+/// relies on `core::future`, making it usable with any async executor (e.g:
+/// `tokio`, `async-std`, `embassy`, etc...).
+///
+/// `AsyncConnection`s are used alongside an [`AsyncConnectionAdapter`], which
+/// buffers the stub's outgoing data until it can be asynchronously written out.
+// The returned futures are not required to be `Send`, as `gdbstub` does not
+// spawn any tasks of its own.
+#[allow(async_fn_in_trait)]
+pub trait AsyncConnection {
+    /// Transport-specific error type.
+    type Error;
+
+    /// Read a single byte.
+    async fn read(&mut self) -> Result<u8, Self::Error>;
+
+    /// Write the entire buffer.
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+
+    /// Flush this Connection, ensuring that all intermediately buffered
+    /// contents reach their destination.
+    async fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Called at the start of a debugging session _before_ any GDB packets have
+    /// been sent/received.
+    ///
+    /// This method's default implementation is a no-op.
+    async fn on_session_start(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
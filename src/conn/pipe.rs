@@ -0,0 +1,190 @@
+//! A [`Connection`] over a pair of pipes / file descriptors.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsFd, AsRawFd, OwnedFd, RawFd};
+use std::time::Duration;
+
+use crate::conn::Connection;
+use crate::conn::ConnectionExt;
+use crate::util::poll::poll_readable;
+
+/// A [`Connection`] over a pair of file descriptors (typically pipes), one
+/// used for reading, and the other for writing.
+///
+/// The most common use-case is running the stub as a subprocess of the GDB
+/// client, communicating over the process's stdin / stdout (see
+/// [`PipeConnection::stdio`]):
+///
+/// ```text
+/// (gdb) target remote | ./emulator --gdb-stdio
+/// ```
+///
+/// [`ConnectionExt::peek`] never blocks: if data is available, a single byte
+/// is read from the reader and buffered until the next call to
+/// [`ConnectionExt::read`]. When driving the connection via `poll(2)` (e.g:
+/// using [`GdbStub::run_poll`](crate::stub::GdbStub::run_poll)), avoid calling
+/// `peek`, as a buffered byte will not be reported as readable by the fd.
+#[derive(Debug)]
+pub struct PipeConnection {
+    reader: File,
+    writer: File,
+    peeked: Option<u8>,
+}
+
+impl PipeConnection {
+    /// Create a new `PipeConnection` from a pair of file descriptors.
+    pub fn new(reader: impl Into<OwnedFd>, writer: impl Into<OwnedFd>) -> PipeConnection {
+        PipeConnection {
+            reader: File::from(reader.into()),
+            writer: File::from(writer.into()),
+            peeked: None,
+        }
+    }
+
+    /// Create a new `PipeConnection` over the process's stdin / stdout.
+    ///
+    /// As any stray writes to stdout would corrupt the GDB protocol stream,
+    /// this method _redirects the process's stdout to stderr_ (via `dup2(2)`),
+    /// after having duplicated the original stdout for use by the connection.
+    /// As such, any subsequent `println!`s or stdout-based logging will be
+    /// safely routed to stderr instead.
+    ///
+    /// _Note:_ stdin is not redirected, and must not be read from elsewhere
+    /// in the process while the connection is in use.
+    pub fn stdio() -> io::Result<PipeConnection> {
+        let reader = io::stdin().as_fd().try_clone_to_owned()?;
+
+        // lock stdout, ensuring no other threads write to it mid-redirection
+        let mut stdout = io::stdout().lock();
+        stdout.flush()?;
+        let writer = stdout.as_fd().try_clone_to_owned()?;
+        redirect_fd(io::stderr().as_raw_fd(), stdout.as_raw_fd())?;
+        drop(stdout);
+
+        Ok(PipeConnection::new(reader, writer))
+    }
+
+    /// Return a reference to the underlying reader.
+    pub fn reader(&self) -> &File {
+        &self.reader
+    }
+
+    /// Return a reference to the underlying writer.
+    pub fn writer(&self) -> &File {
+        &self.writer
+    }
+
+    /// Consume the connection, returning the underlying reader and writer.
+    ///
+    /// _Note:_ any byte buffered by [`ConnectionExt::peek`] is lost.
+    pub fn into_inner(self) -> (File, File) {
+        (self.reader, self.writer)
+    }
+
+    fn read_byte(&mut self) -> io::Result<u8> {
+        let mut buf = [0u8];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+}
+
+/// Returns the reader's file descriptor.
+impl AsRawFd for PipeConnection {
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
+
+impl Connection for PipeConnection {
+    type Error = io::Error;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        Write::write_all(&mut self.writer, &[byte])
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        Write::write_all(&mut self.writer, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Write::flush(&mut self.writer)
+    }
+}
+
+impl ConnectionExt for PipeConnection {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        match self.peeked.take() {
+            Some(byte) => Ok(byte),
+            None => self.read_byte(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.peeked.is_none()
+            && poll_readable([self.reader.as_raw_fd()], Some(Duration::ZERO))?[0]
+        {
+            self.peeked = Some(self.read_byte()?);
+        }
+        Ok(self.peeked)
+    }
+}
+
+// Define some libc types inline (to avoid bringing in entire libc dep)
+
+// every platform supported by the libc crate uses c_int = i32
+#[allow(non_camel_case_types)]
+type c_int = i32;
+
+/// Point `dst` at the same open file description as `src`.
+fn redirect_fd(src: RawFd, dst: RawFd) -> io::Result<()> {
+    extern "C" {
+        fn dup2(src: c_int, dst: c_int) -> c_int;
+    }
+
+    loop {
+        // SAFETY: `dup2` takes no pointer arguments, and both fds are owned by
+        // the process's standard streams.
+        if unsafe { dup2(src, dst) } != -1 {
+            return Ok(());
+        }
+
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn peek_then_read() {
+        // socketpairs stand in for pipes, as std doesn't expose `pipe(2)`
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let mut conn = PipeConnection::new(OwnedFd::from(ours.try_clone().unwrap()), ours);
+
+        assert_eq!(conn.peek().unwrap(), None);
+
+        Write::write_all(&mut &theirs, b"$g#67").unwrap();
+        assert_eq!(conn.peek().unwrap(), Some(b'$'));
+        assert_eq!(conn.peek().unwrap(), Some(b'$'));
+        for &expected in b"$g#67" {
+            assert_eq!(conn.read().unwrap(), expected);
+        }
+        assert_eq!(conn.peek().unwrap(), None);
+
+        Connection::write_all(&mut conn, b"+$OK#9a").unwrap();
+        let mut buf = [0u8; 7];
+        (&theirs).read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"+$OK#9a");
+
+        // EOF is reported as "readable", and surfaced by the subsequent read
+        drop(theirs);
+        assert!(conn.peek().is_err());
+    }
+}
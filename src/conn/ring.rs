@@ -0,0 +1,576 @@
+//! A lock-free, single-producer single-consumer shared-memory ring-buffer
+//! [`Connection`].
+
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::conn::{Connection, ConnectionExt};
+
+/// Magic number identifying an initialized ring (`b"GDBR"` as a
+/// little-endian `u32`).
+pub const RING_MAGIC: u32 = 0x5242_4447;
+/// Version of the ring's memory layout.
+pub const RING_VERSION: u32 = 1;
+/// Size of the ring's header, in bytes.
+pub const RING_HEADER_LEN: usize = 0x20;
+
+const OFFSET_MAGIC: usize = 0x00;
+const OFFSET_VERSION: usize = 0x04;
+const OFFSET_CAPACITY: usize = 0x08;
+const OFFSET_RESERVED: usize = 0x0c;
+const OFFSET_STUB_TO_HOST: usize = 0x10;
+const OFFSET_HOST_TO_STUB: usize = 0x18;
+
+/// An error which may occur when initializing / attaching to a
+/// [`RingConnection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RingError {
+    /// The memory region is null, or isn't 4-byte aligned.
+    Misaligned,
+    /// The memory region is too small to hold the ring.
+    TooSmall,
+    /// The region doesn't contain an initialized ring.
+    BadMagic,
+    /// The ring's layout version is unsupported.
+    UnsupportedVersion(u32),
+    /// The ring's capacity isn't a power of two, or doesn't fit in the memory
+    /// region.
+    BadCapacity(u32),
+}
+
+impl core::fmt::Display for RingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use self::RingError::*;
+        match self {
+            Misaligned => write!(f, "Ring memory region isn't 4-byte aligned"),
+            TooSmall => write!(f, "Ring memory region is too small"),
+            BadMagic => write!(f, "Ring memory region isn't initialized"),
+            UnsupportedVersion(v) => write!(f, "Unsupported ring layout version: {}", v),
+            BadCapacity(n) => write!(f, "Invalid ring capacity: {}", n),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RingError {}
+
+/// Return the size of the memory region required to hold a ring with the
+/// given (power of two) capacity.
+pub const fn ring_region_len(capacity: u32) -> usize {
+    RING_HEADER_LEN + 2 * capacity as usize
+}
+
+fn check_alignment(ptr: *mut u8) -> Result<NonNull<u8>, RingError> {
+    match NonNull::new(ptr) {
+        Some(base) if ptr as usize % 4 == 0 => Ok(base),
+        _ => Err(RingError::Misaligned),
+    }
+}
+
+/// One direction of the ring.
+#[derive(Debug, Clone, Copy)]
+struct Ring {
+    /// offset of the `head` index (`tail` immediately follows it)
+    indices: usize,
+    /// offset of the data
+    data: usize,
+}
+
+/// One end of a shared-memory ring-buffer connection.
+///
+/// Useful in kernels / hypervisor guests, where a serial UART might be the
+/// only other available transport. The stub's end of the connection lives in
+/// the guest, while the other end attaches to the same memory region from the
+/// host (typically from a process which relays traffic to a GDB client, such
+/// as `gdbstub::conn::relay_tcp`).
+///
+/// _Note:_ Only available on targets which support 32-bit atomics.
+///
+/// # Memory Layout
+///
+/// The ring lives in a caller-provided memory region, which must be 4-byte
+/// aligned. All fields are `u32`s in the _native_ endianness of the machine
+/// (as both ends of the ring share the same physical memory).
+///
+/// | Offset     | Field                                  | Written by |
+/// |------------|----------------------------------------|------------|
+/// | `0x00`     | magic (`0x5242_4447`, i.e: `b"GDBR"`)  | stub       |
+/// | `0x04`     | version (`1`)                          | stub       |
+/// | `0x08`     | capacity (`N`)                         | stub       |
+/// | `0x0c`     | reserved (`0`)                         | stub       |
+/// | `0x10`     | stub-to-host `head`                    | stub       |
+/// | `0x14`     | stub-to-host `tail`                    | host       |
+/// | `0x18`     | host-to-stub `head`                    | host       |
+/// | `0x1c`     | host-to-stub `tail`                    | stub       |
+/// | `0x20`     | stub-to-host data (`N` bytes)          | stub       |
+/// | `0x20 + N` | host-to-stub data (`N` bytes)          | host       |
+///
+/// The capacity `N` of each ring is a power of two. The stub initializes the
+/// header via [`RingConnection::init`], whereupon the host may attach to it
+/// (e.g: via [`RingConnection::attach`]) by validating the magic, version, and
+/// capacity fields.
+///
+/// `head` and `tail` are free-running indices, which wrap around at
+/// `u32::MAX`. The ring holds `head - tail` bytes (using wrapping arithmetic),
+/// and the byte at index `i` lives at data offset `i % N`. The ring is empty
+/// when `head == tail`, and full when `head - tail == N`.
+///
+/// - Producers write data into the ring, and then publish it by storing the
+///   new `head` with _release_ semantics.
+/// - Consumers load `head` with _acquire_ semantics, read data out of the ring,
+///   and then free the space by storing the new `tail` with _release_
+///   semantics.
+///
+/// Neither end of the ring tracks whether the other end is still attached.
+/// As such, [`ConnectionExt::read`] spins until data is available, and
+/// [`Connection::write`] spins until space is available in the ring.
+#[derive(Debug)]
+pub struct RingConnection<'a> {
+    base: NonNull<u8>,
+    capacity: u32,
+    tx: Ring,
+    rx: Ring,
+    _region: PhantomData<&'a mut [u8]>,
+}
+
+// SAFETY: `RingConnection` has exclusive ownership of its end of the ring,
+// and all shared state is accessed through atomics / the SPSC protocol.
+unsafe impl Send for RingConnection<'_> {}
+
+impl<'a> RingConnection<'a> {
+    /// Initialize a new ring in the provided memory region, returning the
+    /// stub's end of the connection.
+    ///
+    /// The ring's capacity is the largest power of two which fits within the
+    /// region (see [`ring_region_len`]).
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must be valid for reads and writes of `len` bytes for the
+    ///   lifetime `'a`.
+    /// - Aside from the (single) host-side end of the ring, the memory region
+    ///   must not be accessed by anything else for the lifetime `'a`.
+    pub unsafe fn init(ptr: *mut u8, len: usize) -> Result<RingConnection<'a>, RingError> {
+        let base = check_alignment(ptr)?;
+
+        let max = len
+            .checked_sub(RING_HEADER_LEN)
+            .ok_or(RingError::TooSmall)?
+            / 2;
+        let max = max.min(1 << 31) as u32;
+        if max == 0 {
+            return Err(RingError::TooSmall);
+        }
+        let capacity = 1 << (31 - max.leading_zeros());
+
+        let conn = RingConnection::new(base, capacity, false);
+        conn.word(OFFSET_MAGIC).store(0, Ordering::Relaxed);
+        conn.word(OFFSET_VERSION)
+            .store(RING_VERSION, Ordering::Relaxed);
+        conn.word(OFFSET_CAPACITY)
+            .store(capacity, Ordering::Relaxed);
+        conn.word(OFFSET_RESERVED).store(0, Ordering::Relaxed);
+        for offset in (OFFSET_STUB_TO_HOST..RING_HEADER_LEN).step_by(4) {
+            conn.word(offset).store(0, Ordering::Relaxed);
+        }
+        // publish the header last, so that the host never observes a partially
+        // initialized ring
+        conn.word(OFFSET_MAGIC).store(RING_MAGIC, Ordering::Release);
+
+        Ok(conn)
+    }
+
+    /// Attach to a ring previously initialized via [`RingConnection::init`],
+    /// returning the host's end of the connection.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must be valid for reads and writes of `len` bytes for the
+    ///   lifetime `'a`.
+    /// - Aside from the (single) stub-side end of the ring, the memory region
+    ///   must not be accessed by anything else for the lifetime `'a`.
+    pub unsafe fn attach(ptr: *mut u8, len: usize) -> Result<RingConnection<'a>, RingError> {
+        let base = check_alignment(ptr)?;
+        if len < RING_HEADER_LEN {
+            return Err(RingError::TooSmall);
+        }
+
+        // validate the header using a temporary connection, as `capacity` is
+        // not yet known
+        let conn = RingConnection::new(base, 0, true);
+        if conn.word(OFFSET_MAGIC).load(Ordering::Acquire) != RING_MAGIC {
+            return Err(RingError::BadMagic);
+        }
+        match conn.word(OFFSET_VERSION).load(Ordering::Relaxed) {
+            RING_VERSION => {}
+            version => return Err(RingError::UnsupportedVersion(version)),
+        }
+        let capacity = conn.word(OFFSET_CAPACITY).load(Ordering::Relaxed);
+        if !capacity.is_power_of_two() || ring_region_len(capacity) > len {
+            return Err(RingError::BadCapacity(capacity));
+        }
+
+        Ok(RingConnection::new(base, capacity, true))
+    }
+
+    fn new(base: NonNull<u8>, capacity: u32, host: bool) -> RingConnection<'a> {
+        let stub_to_host = Ring {
+            indices: OFFSET_STUB_TO_HOST,
+            data: RING_HEADER_LEN,
+        };
+        let host_to_stub = Ring {
+            indices: OFFSET_HOST_TO_STUB,
+            data: RING_HEADER_LEN + capacity as usize,
+        };
+
+        let (tx, rx) = if host {
+            (host_to_stub, stub_to_host)
+        } else {
+            (stub_to_host, host_to_stub)
+        };
+
+        RingConnection {
+            base,
+            capacity,
+            tx,
+            rx,
+            _region: PhantomData,
+        }
+    }
+
+    /// Return the capacity of each direction of the ring, in bytes.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Write as much of `buf` into the ring as will fit, without blocking,
+    /// returning the number of bytes written.
+    pub fn try_write(&mut self, buf: &[u8]) -> usize {
+        let head = self.head(self.tx).load(Ordering::Relaxed);
+        let tail = self.tail(self.tx).load(Ordering::Acquire);
+        let free = self.capacity - self.fill_level(head, tail);
+
+        let n = buf.len().min(free as usize);
+        for (i, &b) in buf[..n].iter().enumerate() {
+            let offset = self.data_offset(self.tx, head.wrapping_add(i as u32));
+            // SAFETY: `data_offset` is always within the ring's data region,
+            // which the safety contract of `init` / `attach` guarantees is
+            // valid for writes. The consumer doesn't read this slot until it
+            // is published via the subsequent store to `head`.
+            unsafe { self.base.as_ptr().add(offset).write_volatile(b) };
+        }
+
+        self.head(self.tx)
+            .store(head.wrapping_add(n as u32), Ordering::Release);
+        n
+    }
+
+    /// Read as much data out of the ring as will fit in `buf`, without
+    /// blocking, returning the number of bytes read.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> usize {
+        let tail = self.tail(self.rx).load(Ordering::Relaxed);
+        let head = self.head(self.rx).load(Ordering::Acquire);
+        let available = self.fill_level(head, tail);
+
+        let n = buf.len().min(available as usize);
+        for (i, b) in buf[..n].iter_mut().enumerate() {
+            *b = self.read_data(tail.wrapping_add(i as u32));
+        }
+
+        self.tail(self.rx)
+            .store(tail.wrapping_add(n as u32), Ordering::Release);
+        n
+    }
+
+    fn try_peek(&self) -> Option<u8> {
+        let tail = self.tail(self.rx).load(Ordering::Relaxed);
+        let head = self.head(self.rx).load(Ordering::Acquire);
+        if head == tail {
+            None
+        } else {
+            Some(self.read_data(tail))
+        }
+    }
+
+    /// Number of bytes held in a ring with the given indices.
+    ///
+    /// One of the indices is written by the other end of the ring, which may
+    /// not be trustworthy (e.g: a buggy / hostile host). Inconsistent indices
+    /// are treated as a full ring, so that neither end ever overwrites unread
+    /// data or reads past the end of the data region.
+    fn fill_level(&self, head: u32, tail: u32) -> u32 {
+        head.wrapping_sub(tail).min(self.capacity)
+    }
+
+    fn read_data(&self, idx: u32) -> u8 {
+        let offset = self.data_offset(self.rx, idx);
+        // SAFETY: `data_offset` is always within the ring's data region, which
+        // the safety contract of `init` / `attach` guarantees is valid for
+        // reads. The producer doesn't overwrite this slot until it is freed via
+        // a store to `tail`.
+        unsafe { self.base.as_ptr().add(offset).read_volatile() }
+    }
+
+    fn data_offset(&self, ring: Ring, idx: u32) -> usize {
+        ring.data + (idx & (self.capacity - 1)) as usize
+    }
+
+    fn head(&self, ring: Ring) -> &AtomicU32 {
+        self.word(ring.indices)
+    }
+
+    fn tail(&self, ring: Ring) -> &AtomicU32 {
+        self.word(ring.indices + 4)
+    }
+
+    fn word(&self, offset: usize) -> &AtomicU32 {
+        // SAFETY: all header offsets are 4-byte aligned, and lie within the
+        // header (which `init` / `attach` have checked fits within the
+        // region). The region is shared with the other end of the ring, which
+        // only ever accesses the header via atomic operations.
+        unsafe { &*(self.base.as_ptr().add(offset) as *const AtomicU32) }
+    }
+}
+
+impl Connection for RingConnection<'_> {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.write_all(&[byte])
+    }
+
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            let n = self.try_write(buf);
+            if n == 0 {
+                core::hint::spin_loop();
+            }
+            buf = &buf[n..];
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ConnectionExt for RingConnection<'_> {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        let mut buf = [0];
+        while self.try_read(&mut buf) == 0 {
+            core::hint::spin_loop();
+        }
+        Ok(buf[0])
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(self.try_peek())
+    }
+}
+
+/// Relay traffic between the host's end of a ring and a single TCP client,
+/// returning once the client disconnects.
+///
+/// The relay polls the ring, sleeping for a short period whenever neither
+/// side has any data to transfer.
+#[cfg(feature = "std")]
+pub fn relay_stream(
+    ring: &mut RingConnection<'_>,
+    stream: &mut std::net::TcpStream,
+) -> std::io::Result<()> {
+    use std::io::{ErrorKind, Read, Write};
+    use std::time::Duration;
+
+    const MAX_IDLE_SLEEP: Duration = Duration::from_millis(10);
+
+    stream.set_nodelay(true)?;
+    let mut buf = [0; 4096];
+    let mut pending: &[u8] = &[];
+    let mut pending_buf = [0; 4096];
+    let mut idle_sleep = Duration::from_micros(50);
+
+    loop {
+        let mut progress = false;
+
+        // host-to-stub (stream.read is non-blocking)
+        stream.set_nonblocking(true)?;
+        let free = ring.capacity() as usize - ring_len(ring);
+        if free != 0 {
+            let len = free.min(buf.len());
+            match Read::read(stream, &mut buf[..len]) {
+                Ok(0) => return Ok(()),
+                Ok(n) => {
+                    let written = ring.try_write(&buf[..n]);
+                    debug_assert_eq!(written, n);
+                    progress = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        stream.set_nonblocking(false)?;
+
+        // stub-to-host
+        if pending.is_empty() {
+            let n = ring.try_read(&mut pending_buf);
+            pending = &pending_buf[..n];
+        }
+        if !pending.is_empty() {
+            Write::write_all(stream, pending)?;
+            pending = &[];
+            progress = true;
+        }
+
+        if progress {
+            idle_sleep = Duration::from_micros(50);
+        } else {
+            std::thread::sleep(idle_sleep);
+            idle_sleep = (idle_sleep * 2).min(MAX_IDLE_SLEEP);
+        }
+    }
+}
+
+/// Relay traffic between the host's end of a ring and GDB clients connecting
+/// to the provided listener, serving one client at a time.
+///
+/// This method only returns on error.
+#[cfg(feature = "std")]
+pub fn relay_tcp(
+    ring: &mut RingConnection<'_>,
+    listener: &std::net::TcpListener,
+) -> std::io::Result<()> {
+    loop {
+        let (mut stream, _addr) = listener.accept()?;
+        relay_stream(ring, &mut stream)?;
+    }
+}
+
+/// Number of bytes in the ring's outgoing direction.
+#[cfg(feature = "std")]
+fn ring_len(ring: &RingConnection<'_>) -> usize {
+    let head = ring.head(ring.tx).load(Ordering::Relaxed);
+    let tail = ring.tail(ring.tx).load(Ordering::Acquire);
+    ring.fill_level(head, tail) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stub_host_roundtrip() {
+        // u32s, to ensure the region is 4-byte aligned
+        let mut region = [0u32; (RING_HEADER_LEN + 2 * 16 + 12) / 4];
+        let ptr = region.as_mut_ptr() as *mut u8;
+        let len = core::mem::size_of_val(&region);
+
+        // SAFETY: `region` outlives both ends of the ring, and is not accessed
+        // by anything else.
+        let (mut stub, mut host) = unsafe {
+            assert_eq!(
+                RingConnection::attach(ptr, len).unwrap_err(),
+                RingError::BadMagic
+            );
+            let stub = RingConnection::init(ptr, len).unwrap();
+            let host = RingConnection::attach(ptr, len).unwrap();
+            (stub, host)
+        };
+        assert_eq!(stub.capacity(), 16);
+        assert_eq!(host.capacity(), 16);
+
+        assert_eq!(stub.peek(), Ok(None));
+        for round in 0..4u8 {
+            // wrap around the ring a few times
+            let data: [u8; 10] = core::array::from_fn(|i| round * 10 + i as u8);
+            assert_eq!(host.try_write(&data), 10);
+            assert_eq!(stub.peek(), Ok(Some(data[0])));
+            for &b in &data {
+                assert_eq!(stub.read(), Ok(b));
+            }
+            assert_eq!(stub.peek(), Ok(None));
+
+            stub.write_all(&data).unwrap();
+            let mut buf = [0; 16];
+            assert_eq!(host.try_read(&mut buf), 10);
+            assert_eq!(&buf[..10], &data);
+        }
+
+        // the ring is full after writing `capacity` bytes
+        assert_eq!(stub.try_write(&[0xaa; 20]), 16);
+        assert_eq!(stub.try_write(&[0xaa]), 0);
+    }
+
+    #[test]
+    fn inconsistent_indices() {
+        let mut region = [0u32; (RING_HEADER_LEN + 2 * 16) / 4];
+        let ptr = region.as_mut_ptr() as *mut u8;
+        let len = core::mem::size_of_val(&region);
+
+        // SAFETY: `region` outlives the ring, and is not accessed by anything
+        // else.
+        let mut stub = unsafe { RingConnection::init(ptr, len).unwrap() };
+
+        // a `tail` past `head` must not be mistaken for free space
+        stub.tail(stub.tx).store(100, Ordering::Relaxed);
+        assert_eq!(stub.try_write(&[0xaa; 4]), 0);
+
+        // a `head` more than `capacity` bytes ahead only yields `capacity`
+        // bytes
+        stub.head(stub.rx).store(1000, Ordering::Relaxed);
+        let mut buf = [0; 64];
+        assert_eq!(stub.try_read(&mut buf), 16);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn relay_clients() {
+        use std::io::{Read, Write};
+        use std::net::{TcpListener, TcpStream};
+
+        let mut region = [0u32; (RING_HEADER_LEN + 2 * 64) / 4];
+        let ptr = region.as_mut_ptr() as *mut u8;
+        let len = core::mem::size_of_val(&region);
+
+        // SAFETY: `region` outlives both ends of the ring, and is not accessed
+        // by anything else.
+        let (mut stub, mut host) = unsafe {
+            let stub = RingConnection::init(ptr, len).unwrap();
+            let host = RingConnection::attach(ptr, len).unwrap();
+            (stub, host)
+        };
+
+        // both clients are queued up front, and the listener is non-blocking,
+        // so `relay_tcp` bails out once there are no more clients to serve
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let clients = vec![
+            TcpStream::connect(addr).unwrap(),
+            TcpStream::connect(addr).unwrap(),
+        ];
+        listener.set_nonblocking(true).unwrap();
+
+        std::thread::scope(|s| {
+            let relay = s.spawn(|| relay_tcp(&mut host, &listener));
+
+            // clients are served one at a time, in order (each client hangs up
+            // at the end of its iteration)
+            for (i, mut client) in clients.into_iter().enumerate() {
+                let i = i as u8;
+                Write::write_all(&mut client, &[b'a' + i, b'b' + i]).unwrap();
+                assert_eq!(stub.read(), Ok(b'a' + i));
+                assert_eq!(stub.read(), Ok(b'b' + i));
+
+                stub.write_all(&[b'x' + i; 3]).unwrap();
+                let mut buf = [0; 3];
+                client.read_exact(&mut buf).unwrap();
+                assert_eq!(buf, [b'x' + i; 3]);
+            }
+
+            let err = relay.join().unwrap().unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        });
+    }
+}
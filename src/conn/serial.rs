@@ -0,0 +1,396 @@
+//! A [`Connection`] over a serial port / TTY device.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+
+use crate::conn::Connection;
+use crate::conn::ConnectionExt;
+
+/// Serial port parity checking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    /// No parity bit.
+    None,
+    /// Odd parity.
+    Odd,
+    /// Even parity.
+    Even,
+}
+
+/// Serial port flow control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowControl {
+    /// No flow control.
+    None,
+    /// Hardware (RTS/CTS) flow control.
+    Hardware,
+    /// Software (XON/XOFF) flow control.
+    Software,
+}
+
+/// Line settings used when configuring a [`SerialConnection`].
+///
+/// The port is always configured in "raw" mode, with 8 data bits and 1 stop
+/// bit. Defaults to 115200 baud, no parity, and no flow control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialConfig {
+    baud_rate: u32,
+    parity: Parity,
+    flow_control: FlowControl,
+}
+
+impl Default for SerialConfig {
+    fn default() -> SerialConfig {
+        SerialConfig::new(115200)
+    }
+}
+
+impl SerialConfig {
+    /// Create a new `SerialConfig` with the given baud rate, no parity, and no
+    /// flow control.
+    pub fn new(baud_rate: u32) -> SerialConfig {
+        SerialConfig {
+            baud_rate,
+            parity: Parity::None,
+            flow_control: FlowControl::None,
+        }
+    }
+
+    /// Set the baud rate.
+    pub fn baud_rate(mut self, baud_rate: u32) -> SerialConfig {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// Set the parity mode.
+    pub fn parity(mut self, parity: Parity) -> SerialConfig {
+        self.parity = parity;
+        self
+    }
+
+    /// Set the flow control mode.
+    pub fn flow_control(mut self, flow_control: FlowControl) -> SerialConfig {
+        self.flow_control = flow_control;
+        self
+    }
+}
+
+/// A [`Connection`] over a serial port / TTY device (e.g: `/dev/ttyUSB0`).
+///
+/// The device is configured in raw mode via `termios(3)`, using the line
+/// settings specified in the provided [`SerialConfig`].
+///
+/// [`ConnectionExt::peek`] never blocks: if data is available, a single byte
+/// is read from the device and buffered until the next call to
+/// [`ConnectionExt::read`]. When driving the connection via `poll(2)` (e.g:
+/// using [`GdbStub::run_poll`](crate::stub::GdbStub::run_poll)), avoid calling
+/// `peek`, as a buffered byte will not be reported as readable by the fd.
+#[derive(Debug)]
+pub struct SerialConnection {
+    file: File,
+    peeked: Option<u8>,
+}
+
+impl SerialConnection {
+    /// Open and configure the TTY device at the given path.
+    pub fn open(path: impl AsRef<Path>, config: &SerialConfig) -> io::Result<SerialConnection> {
+        // open in non-blocking mode, as otherwise `open` may block until the
+        // device asserts DCD (i.e: before `CLOCAL` has been set)
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+            .open(path)?;
+
+        // SAFETY: `fcntl` is called with a valid fd, and F_GETFL / F_SETFL
+        // take no pointer arguments.
+        let ret = unsafe {
+            let flags = libc::fcntl(file.as_raw_fd(), libc::F_GETFL);
+            if flags == -1 {
+                -1
+            } else {
+                libc::fcntl(file.as_raw_fd(), libc::F_SETFL, flags & !libc::O_NONBLOCK)
+            }
+        };
+        cvt(ret)?;
+
+        SerialConnection::from_file(file, config)
+    }
+
+    /// Configure an already-open TTY device.
+    pub fn from_file(file: File, config: &SerialConfig) -> io::Result<SerialConnection> {
+        let mut conn = SerialConnection { file, peeked: None };
+        conn.configure(config)?;
+        Ok(conn)
+    }
+
+    /// Re-configure the device's line settings.
+    ///
+    /// Any pending input / output is discarded.
+    pub fn configure(&mut self, config: &SerialConfig) -> io::Result<()> {
+        let fd = self.file.as_raw_fd();
+        let speed = baud_to_speed(config.baud_rate)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "unsupported baud rate"))?;
+
+        // SAFETY: `termios` is a plain-old-data struct, for which all-zeroes is
+        // a valid bit pattern. It is fully initialized by `tcgetattr` before
+        // use.
+        let mut termios: libc::termios = unsafe { core::mem::zeroed() };
+        // SAFETY: `termios` is a valid, exclusively borrowed `termios` struct.
+        cvt(unsafe { libc::tcgetattr(fd, &mut termios) })?;
+
+        // SAFETY: `termios` is a valid, exclusively borrowed `termios` struct.
+        unsafe { libc::cfmakeraw(&mut termios) };
+
+        termios.c_cflag &=
+            !(libc::CSIZE | libc::CSTOPB | libc::PARENB | libc::PARODD | libc::CRTSCTS);
+        termios.c_cflag |= libc::CS8 | libc::CLOCAL | libc::CREAD;
+        termios.c_iflag &= !(libc::INPCK | libc::IXON | libc::IXOFF | libc::IXANY);
+
+        match config.parity {
+            Parity::None => {}
+            Parity::Odd => {
+                termios.c_cflag |= libc::PARENB | libc::PARODD;
+                termios.c_iflag |= libc::INPCK;
+            }
+            Parity::Even => {
+                termios.c_cflag |= libc::PARENB;
+                termios.c_iflag |= libc::INPCK;
+            }
+        }
+
+        match config.flow_control {
+            FlowControl::None => {}
+            FlowControl::Hardware => termios.c_cflag |= libc::CRTSCTS,
+            FlowControl::Software => termios.c_iflag |= libc::IXON | libc::IXOFF,
+        }
+
+        // block until at least 1 byte is available, without an inter-byte timeout
+        termios.c_cc[libc::VMIN] = 1;
+        termios.c_cc[libc::VTIME] = 0;
+
+        // SAFETY: `termios` is a valid, exclusively borrowed `termios` struct.
+        unsafe {
+            cvt(libc::cfsetispeed(&mut termios, speed))?;
+            cvt(libc::cfsetospeed(&mut termios, speed))?;
+        }
+
+        // SAFETY: `termios` is a valid `termios` struct, and `tcflush` takes no
+        // pointer arguments.
+        unsafe {
+            cvt(libc::tcsetattr(fd, libc::TCSANOW, &termios))?;
+            cvt(libc::tcflush(fd, libc::TCIOFLUSH))?;
+        }
+
+        self.peeked = None;
+        Ok(())
+    }
+
+    /// Return a reference to the underlying TTY device.
+    pub fn get_ref(&self) -> &File {
+        &self.file
+    }
+
+    /// Consume the connection, returning the underlying TTY device.
+    ///
+    /// _Note:_ any byte buffered by [`ConnectionExt::peek`] is lost.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+
+    /// Check if the device has data available to read, without blocking.
+    fn poll_readable(&self) -> io::Result<bool> {
+        let mut fds = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        loop {
+            // SAFETY: `fds` is a single valid `pollfd`, and `nfds` is 1.
+            let ret = unsafe { libc::poll(&mut fds, 1, 0) };
+            if ret >= 0 {
+                break;
+            }
+
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+
+        // report hangups / errors as "readable", so that the subsequent read
+        // surfaces the underlying error
+        Ok(fds.revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0)
+    }
+
+    fn read_byte(&mut self) -> io::Result<u8> {
+        let mut buf = [0u8];
+        self.file.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+}
+
+impl AsRawFd for SerialConnection {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl Connection for SerialConnection {
+    type Error = io::Error;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        Write::write_all(&mut self.file, &[byte])
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        Write::write_all(&mut self.file, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Write::flush(&mut self.file)
+    }
+}
+
+impl ConnectionExt for SerialConnection {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        match self.peeked.take() {
+            Some(byte) => Ok(byte),
+            None => self.read_byte(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.peeked.is_none() && self.poll_readable()? {
+            self.peeked = Some(self.read_byte()?);
+        }
+        Ok(self.peeked)
+    }
+}
+
+fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn baud_to_speed(baud_rate: u32) -> Option<libc::speed_t> {
+    let speed = match baud_rate {
+        50 => libc::B50,
+        75 => libc::B75,
+        110 => libc::B110,
+        134 => libc::B134,
+        150 => libc::B150,
+        200 => libc::B200,
+        300 => libc::B300,
+        600 => libc::B600,
+        1200 => libc::B1200,
+        1800 => libc::B1800,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        460800 => libc::B460800,
+        500000 => libc::B500000,
+        576000 => libc::B576000,
+        921600 => libc::B921600,
+        1000000 => libc::B1000000,
+        1152000 => libc::B1152000,
+        1500000 => libc::B1500000,
+        2000000 => libc::B2000000,
+        2500000 => libc::B2500000,
+        3000000 => libc::B3000000,
+        3500000 => libc::B3500000,
+        4000000 => libc::B4000000,
+        _ => return None,
+    };
+    Some(speed)
+}
+
+// BSD-derived platforms (including macOS) encode `speed_t` as the literal baud
+// rate, leaving it up to the driver to reject unsupported rates.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn baud_to_speed(baud_rate: u32) -> Option<libc::speed_t> {
+    use std::convert::TryInto;
+
+    baud_rate.try_into().ok()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    use std::ffi::CStr;
+    use std::os::unix::io::FromRawFd;
+    use std::time::{Duration, Instant};
+
+    /// Open a pseudo-terminal pair, returning the master end, and the path to
+    /// the slave end.
+    fn openpty() -> (File, String) {
+        // SAFETY: the returned fd is checked for validity before use, and
+        // `ptsname_r` is passed a valid buffer alongside its length.
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(fd >= 0);
+            let master = File::from_raw_fd(fd);
+            assert_eq!(libc::grantpt(fd), 0);
+            assert_eq!(libc::unlockpt(fd), 0);
+
+            let mut buf = [0 as libc::c_char; 64];
+            assert_eq!(libc::ptsname_r(fd, buf.as_mut_ptr(), buf.len()), 0);
+            let path = CStr::from_ptr(buf.as_ptr()).to_str().unwrap().to_owned();
+            (master, path)
+        }
+    }
+
+    fn peek_timeout(conn: &mut SerialConnection) -> Option<u8> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if let Some(byte) = conn.peek().unwrap() {
+                return Some(byte);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        None
+    }
+
+    #[test]
+    fn pty_roundtrip() {
+        let (mut master, path) = openpty();
+        let config = SerialConfig::new(9600)
+            .parity(Parity::Even)
+            .flow_control(FlowControl::Software);
+        let mut conn = SerialConnection::open(&path, &config).unwrap();
+
+        // nothing has been sent yet
+        assert_eq!(conn.peek().unwrap(), None);
+
+        master.write_all(b"$?#3f").unwrap();
+        assert_eq!(peek_timeout(&mut conn), Some(b'$'));
+        // peeking doesn't consume the byte
+        assert_eq!(conn.peek().unwrap(), Some(b'$'));
+        for &expected in b"$?#3f" {
+            assert_eq!(conn.read().unwrap(), expected);
+        }
+
+        // raw mode: no echo, no CR/LF translation
+        Connection::write_all(&mut conn, b"+\n").unwrap();
+        Connection::flush(&mut conn).unwrap();
+        let mut buf = [0u8; 2];
+        master.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"+\n");
+
+        assert!(SerialConnection::open(&path, &SerialConfig::new(12345)).is_err());
+    }
+}
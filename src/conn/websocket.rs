@@ -0,0 +1,605 @@
+//! A [`Connection`] over a WebSocket ([RFC 6455](https://www.rfc-editor.org/rfc/rfc6455)).
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+
+use crate::conn::Connection;
+use crate::conn::ConnectionExt;
+
+/// GUID used to compute the `Sec-WebSocket-Accept` header.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Maximum size of the client's opening handshake.
+const MAX_HANDSHAKE_LEN: usize = 8 * 1024;
+/// Maximum payload size of a single incoming frame.
+const MAX_FRAME_PAYLOAD_LEN: u64 = 1024 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xa;
+
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+const CLOSE_TOO_BIG: u16 = 1009;
+
+/// A WebSocket server [`Connection`], carrying GDB RSP bytes in binary
+/// messages over a [`TcpStream`].
+///
+/// Useful for browser-hosted debugger front-ends, which cannot open raw TCP
+/// connections.
+///
+/// - Incoming data is accepted in both binary and text messages, which may be
+///   fragmented. The stub treats the concatenated payloads as a single byte
+///   stream.
+/// - Outgoing data is buffered until [`Connection::flush`] is called, and is
+///   then sent as a single binary message (fragmented into frames of at most
+///   [`set_max_frame_len`](Self::set_max_frame_len) bytes).
+/// - Pings are answered automatically. Once the client sends a close frame,
+///   the close is echoed, and subsequent reads return an
+///   [`io::ErrorKind::UnexpectedEof`] error.
+///
+/// [`ConnectionExt::peek`] never blocks: it only parses frames which have been
+/// fully received.
+#[derive(Debug)]
+pub struct WebSocketConnection {
+    stream: TcpStream,
+    /// raw, yet-to-be-parsed incoming data
+    raw: Vec<u8>,
+    /// payload data from parsed frames
+    payload: VecDeque<u8>,
+    /// outgoing data, buffered until the next flush
+    tx: Vec<u8>,
+    /// whether a fragmented message is currently being received
+    fragmented: bool,
+    closed: bool,
+    max_frame_len: usize,
+}
+
+impl WebSocketConnection {
+    /// Perform the server side of the WebSocket opening handshake over an
+    /// accepted `TcpStream`.
+    ///
+    /// Any path in the request line is accepted. If the request is not a
+    /// valid WebSocket upgrade request, a `400 Bad Request` response is sent,
+    /// and an [`io::ErrorKind::InvalidData`] error is returned.
+    pub fn accept(mut stream: TcpStream) -> io::Result<WebSocketConnection> {
+        // read byte-by-byte, so as not to consume any data after the handshake
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            if request.len() == MAX_HANDSHAKE_LEN {
+                return Err(bad_request(&mut stream, "handshake too large"));
+            }
+            let mut buf = [0];
+            stream.read_exact(&mut buf)?;
+            request.push(buf[0]);
+        }
+
+        let key = match parse_handshake(&request) {
+            Ok(key) => key,
+            Err(msg) => return Err(bad_request(&mut stream, msg)),
+        };
+
+        let mut digest_input = Vec::from(key.as_bytes());
+        digest_input.extend_from_slice(WEBSOCKET_GUID.as_bytes());
+        let accept = base64_encode(&sha1(&digest_input));
+
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\n\
+             Upgrade: websocket\r\n\
+             Connection: Upgrade\r\n\
+             Sec-WebSocket-Accept: {}\r\n\r\n",
+            accept
+        )?;
+        Write::flush(&mut stream)?;
+
+        Ok(WebSocketConnection {
+            stream,
+            raw: Vec::new(),
+            payload: VecDeque::new(),
+            tx: Vec::new(),
+            fragmented: false,
+            closed: false,
+            max_frame_len: 16 * 1024,
+        })
+    }
+
+    /// Set the maximum payload size of outgoing frames. Messages larger than
+    /// this are fragmented across multiple frames.
+    ///
+    /// Defaults to 16 KiB.
+    pub fn set_max_frame_len(&mut self, len: usize) {
+        self.max_frame_len = len.max(1);
+    }
+
+    /// Return a reference to the underlying `TcpStream`.
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Parse all fully-received frames, returning once payload data is
+    /// available, or once no more complete frames are buffered.
+    fn parse_frames(&mut self) -> io::Result<()> {
+        while self.payload.is_empty() && !self.closed {
+            let frame = match parse_frame_header(&self.raw)? {
+                Some(frame) => frame,
+                None => return Ok(()),
+            };
+
+            if frame.len > MAX_FRAME_PAYLOAD_LEN {
+                return Err(self.fail(CLOSE_TOO_BIG, "frame too large"));
+            }
+            // checked above, so `frame.len` fits in a `usize`
+            let end = frame.header_len + frame.len as usize;
+            if self.raw.len() < end {
+                return Ok(());
+            }
+
+            // clients must mask all frames sent to the server
+            let mask = match frame.mask {
+                Some(mask) => mask,
+                None => return Err(self.fail(CLOSE_PROTOCOL_ERROR, "unmasked client frame")),
+            };
+            let mut data: Vec<u8> = self.raw.drain(..end).skip(frame.header_len).collect();
+            for (i, b) in data.iter_mut().enumerate() {
+                *b ^= mask[i % 4];
+            }
+
+            let is_control = frame.opcode & 0x8 != 0;
+            if is_control && (!frame.fin || data.len() > 125) {
+                return Err(self.fail(CLOSE_PROTOCOL_ERROR, "invalid control frame"));
+            }
+
+            match frame.opcode {
+                OPCODE_TEXT | OPCODE_BINARY if !self.fragmented => {
+                    self.fragmented = !frame.fin;
+                    self.payload.extend(data);
+                }
+                OPCODE_CONTINUATION if self.fragmented => {
+                    self.fragmented = !frame.fin;
+                    self.payload.extend(data);
+                }
+                OPCODE_PING => self.send_frame(true, OPCODE_PONG, &data)?,
+                OPCODE_PONG => {}
+                OPCODE_CLOSE => {
+                    // echo the status code (if any)
+                    let status = data.get(..2).unwrap_or(&[]);
+                    self.send_frame(true, OPCODE_CLOSE, status)?;
+                    self.closed = true;
+                }
+                _ => return Err(self.fail(CLOSE_PROTOCOL_ERROR, "unexpected opcode")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a close frame with the given status code, returning an error to
+    /// report to the caller.
+    fn fail(&mut self, status: u16, msg: &'static str) -> io::Error {
+        // best-effort, as the connection is being torn down regardless
+        let _ = self.send_frame(true, OPCODE_CLOSE, &status.to_be_bytes());
+        self.closed = true;
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    /// Read any available data from the stream into `raw`, returning `false`
+    /// if no data was available (when non-blocking).
+    fn fill_raw(&mut self, block: bool) -> io::Result<bool> {
+        self.stream.set_nonblocking(!block)?;
+        let mut buf = [0; 4096];
+        let res = loop {
+            match Read::read(&mut self.stream, &mut buf) {
+                Ok(0) => break Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    self.raw.extend_from_slice(&buf[..n]);
+                    break Ok(true);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break Ok(false),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => break Err(e),
+            }
+        };
+        if !block {
+            self.stream.set_nonblocking(false)?;
+        }
+        res
+    }
+
+    fn send_frame(&mut self, fin: bool, opcode: u8, data: &[u8]) -> io::Result<()> {
+        let mut header = [0u8; 10];
+        header[0] = (fin as u8) << 7 | opcode;
+        // server frames are never masked
+        let header_len = match data.len() {
+            len @ 0..=125 => {
+                header[1] = len as u8;
+                2
+            }
+            len @ 126..=0xffff => {
+                header[1] = 126;
+                header[2..4].copy_from_slice(&(len as u16).to_be_bytes());
+                4
+            }
+            len => {
+                header[1] = 127;
+                header[2..10].copy_from_slice(&(len as u64).to_be_bytes());
+                10
+            }
+        };
+
+        Write::write_all(&mut self.stream, &header[..header_len])?;
+        Write::write_all(&mut self.stream, data)
+    }
+
+    fn pending_tx(&mut self) -> io::Result<()> {
+        if self.tx.is_empty() {
+            return Ok(());
+        }
+
+        let tx = core::mem::take(&mut self.tx);
+        let mut chunks = tx.chunks(self.max_frame_len).peekable();
+        let mut opcode = OPCODE_BINARY;
+        while let Some(chunk) = chunks.next() {
+            self.send_frame(chunks.peek().is_none(), opcode, chunk)?;
+            opcode = OPCODE_CONTINUATION;
+        }
+
+        // re-use the allocation
+        self.tx = tx;
+        self.tx.clear();
+        Write::flush(&mut self.stream)
+    }
+}
+
+impl Connection for WebSocketConnection {
+    type Error = io::Error;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.tx.push(byte);
+        Ok(())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.tx.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.pending_tx()
+    }
+
+    fn on_session_start(&mut self) -> Result<(), Self::Error> {
+        // see issue #28
+        self.stream.set_nodelay(true)
+    }
+}
+
+impl ConnectionExt for WebSocketConnection {
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.pending_tx()?;
+        loop {
+            self.parse_frames()?;
+            if let Some(byte) = self.payload.pop_front() {
+                return Ok(byte);
+            }
+            if self.closed {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            self.fill_raw(true)?;
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        self.pending_tx()?;
+        loop {
+            self.parse_frames()?;
+            if let Some(&byte) = self.payload.front() {
+                return Ok(Some(byte));
+            }
+            if self.closed {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if !self.fill_raw(false)? {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Respond to an invalid handshake with `400 Bad Request`.
+fn bad_request(stream: &mut TcpStream, msg: &'static str) -> io::Error {
+    // best-effort, as the connection is being rejected regardless
+    let _ = Write::write_all(
+        stream,
+        b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n",
+    );
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Validate the client's opening handshake, returning the `Sec-WebSocket-Key`.
+fn parse_handshake(request: &[u8]) -> Result<&str, &'static str> {
+    let request = core::str::from_utf8(request).map_err(|_| "handshake is not valid UTF-8")?;
+    let mut lines = request.split("\r\n");
+
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    if parts.next() != Some("GET") || parts.nth(1) != Some("HTTP/1.1") {
+        return Err("expected a `GET ... HTTP/1.1` request");
+    }
+
+    let mut key = None;
+    let mut upgrade = false;
+    let mut connection_upgrade = false;
+    let mut version_13 = false;
+    for line in lines {
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => continue,
+        };
+
+        let has_token = |token: &str| {
+            value
+                .split(',')
+                .any(|v| v.trim().eq_ignore_ascii_case(token))
+        };
+        if name.eq_ignore_ascii_case("Upgrade") {
+            upgrade = has_token("websocket");
+        } else if name.eq_ignore_ascii_case("Connection") {
+            connection_upgrade = has_token("upgrade");
+        } else if name.eq_ignore_ascii_case("Sec-WebSocket-Version") {
+            version_13 = value == "13";
+        } else if name.eq_ignore_ascii_case("Sec-WebSocket-Key") {
+            key = Some(value);
+        }
+    }
+
+    if !upgrade || !connection_upgrade {
+        return Err("not a WebSocket upgrade request");
+    }
+    if !version_13 {
+        return Err("unsupported WebSocket version");
+    }
+    key.ok_or("missing Sec-WebSocket-Key")
+}
+
+struct FrameHeader {
+    fin: bool,
+    opcode: u8,
+    mask: Option<[u8; 4]>,
+    len: u64,
+    header_len: usize,
+}
+
+/// Parse a frame header, returning `None` if more data is required.
+fn parse_frame_header(raw: &[u8]) -> io::Result<Option<FrameHeader>> {
+    let (b0, b1) = match raw {
+        [b0, b1, ..] => (*b0, *b1),
+        _ => return Ok(None),
+    };
+
+    if b0 & 0x70 != 0 {
+        // no extensions are negotiated, so the RSV bits must be clear
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected RSV bits",
+        ));
+    }
+
+    let mut header_len = 2;
+    let len = match b1 & 0x7f {
+        126 => {
+            header_len += 2;
+            match raw.get(2..4) {
+                Some(len) => u16::from_be_bytes([len[0], len[1]]) as u64,
+                None => return Ok(None),
+            }
+        }
+        127 => {
+            header_len += 8;
+            match raw.get(2..10) {
+                Some(len) => {
+                    let mut buf = [0; 8];
+                    buf.copy_from_slice(len);
+                    u64::from_be_bytes(buf)
+                }
+                None => return Ok(None),
+            }
+        }
+        len => len as u64,
+    };
+
+    let mask = if b1 & 0x80 != 0 {
+        let mask = match raw.get(header_len..header_len + 4) {
+            Some(mask) => [mask[0], mask[1], mask[2], mask[3]],
+            None => return Ok(None),
+        };
+        header_len += 4;
+        Some(mask)
+    } else {
+        None
+    };
+
+    Ok(Some(FrameHeader {
+        fin: b0 & 0x80 != 0,
+        opcode: b0 & 0x0f,
+        mask,
+        len,
+        header_len,
+    }))
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A minimal SHA-1 implementation, as required by the WebSocket handshake.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in msg.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &w) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(w);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut out = [0; 20];
+    for (out, h) in out.chunks_mut(4).zip(h) {
+        out.copy_from_slice(&h.to_be_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::TcpListener;
+
+    /// Write a masked client frame.
+    fn client_frame(fin: bool, opcode: u8, data: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![(fin as u8) << 7 | opcode, 0x80 | data.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(data.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    /// Read an (unmasked) server frame, returning its first byte and payload.
+    fn server_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut header = [0; 2];
+        stream.read_exact(&mut header).unwrap();
+        assert_eq!(header[1] & 0x80, 0);
+        let mut data = vec![0; header[1] as usize];
+        stream.read_exact(&mut data).unwrap();
+        (header[0], data)
+    }
+
+    #[test]
+    fn loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        // example handshake from RFC 6455, section 1.3
+        Write::write_all(
+            &mut client,
+            b"GET /gdb HTTP/1.1\r\n\
+                  Host: localhost\r\n\
+                  Upgrade: websocket\r\n\
+                  Connection: keep-alive, Upgrade\r\n\
+                  Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .unwrap();
+        let mut conn = WebSocketConnection::accept(listener.accept().unwrap().0).unwrap();
+        conn.set_max_frame_len(4);
+
+        let mut response = vec![0; 129];
+        client.read_exact(&mut response).unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+
+        assert_eq!(conn.peek().unwrap(), None);
+
+        // a fragmented message, with an interleaved ping
+        let mut data = client_frame(false, OPCODE_BINARY, b"$?");
+        data.extend(client_frame(true, OPCODE_PING, b"hi"));
+        data.extend(client_frame(true, OPCODE_CONTINUATION, b"#3f"));
+        // a partial frame, which `peek` shouldn't block on
+        let next = client_frame(true, OPCODE_TEXT, b"+");
+        data.extend_from_slice(&next[..3]);
+        Write::write_all(&mut client, &data).unwrap();
+
+        let mut received = Vec::new();
+        while received.len() < 5 {
+            if conn.peek().unwrap().is_some() {
+                received.push(conn.read().unwrap());
+            }
+        }
+        assert_eq!(received, b"$?#3f");
+        assert_eq!(conn.peek().unwrap(), None);
+        assert_eq!(
+            server_frame(&mut client),
+            (0x80 | OPCODE_PONG, b"hi".to_vec())
+        );
+
+        Write::write_all(&mut client, &next[3..]).unwrap();
+        assert_eq!(conn.read().unwrap(), b'+');
+
+        // outgoing data is fragmented according to `max_frame_len`
+        Connection::write_all(&mut conn, b"$S05#b8").unwrap();
+        Connection::flush(&mut conn).unwrap();
+        assert_eq!(server_frame(&mut client), (OPCODE_BINARY, b"$S05".to_vec()));
+        assert_eq!(server_frame(&mut client), (0x80, b"#b8".to_vec()));
+
+        // closing handshake
+        Write::write_all(
+            &mut client,
+            &client_frame(true, OPCODE_CLOSE, &1000u16.to_be_bytes()),
+        )
+        .unwrap();
+        assert_eq!(
+            conn.read().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(
+            server_frame(&mut client),
+            (0x80 | OPCODE_CLOSE, 1000u16.to_be_bytes().to_vec())
+        );
+    }
+}
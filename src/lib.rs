@@ -18,12 +18,37 @@
 //!           provided via `GdbStubBuilder::with_packet_buffer`).
 //!         - (Monitor Command) Use a heap-allocated output buffer in
 //!           `ConsoleOutput`.
+//!     - Track breakpoints set by the GDB client across reconnects (via
+//!       `GdbStubBuilder::track_breakpoints`).
 //! - `std` (implies `alloc`)
 //!     - Implement `Connection` for [`TcpStream`](std::net::TcpStream) and
 //!       [`UnixStream`](std::os::unix::net::UnixStream).
+//!     - Provide [`DuplexConnection`](conn::DuplexConnection), a thread-safe
+//!       in-memory `Connection` pair.
+//!     - (unix) Provide [`PipeConnection`](conn::PipeConnection), a
+//!       `Connection` over a pair of pipes (e.g: stdin / stdout).
+//!     - Provide [`WebSocketConnection`](conn::WebSocketConnection), a
+//!       WebSocket server `Connection` (e.g: for browser-based clients).
 //!     - Implement [`std::error::Error`] for `gdbstub::Error`.
 //!     - Add a `TargetError::Io` variant to simplify `std::io::Error` handling
 //!       from Target methods.
+//!     - (unix) Provide the `GdbStub::run_poll` event loop, and the
+//!       [`threaded`] module.
+//! - `serial` (implies `std`)
+//!     - (unix) Provide `conn::SerialConnection`, a `Connection` over a
+//!       serial port / TTY device. Pulls in the `libc` crate.
+//! - `embedded-io`
+//!     - Provide `conn::EmbeddedIoConnection`, adapting any
+//!       [`embedded-io`](https://docs.rs/embedded-io) `Read + ReadReady + Write`
+//!       stream into a `Connection`.
+//! - `embedded-hal-nb`
+//!     - Provide `conn::NbSerialConnection`, adapting any
+//!       [`embedded-hal-nb`](https://docs.rs/embedded-hal-nb) serial
+//!       peripheral into a `Connection`.
+//! - `serde`
+//!     - Implement `Serialize` / `Deserialize` for
+//!       [`GdbStubSnapshot`](stub::GdbStubSnapshot) (and the types it
+//!       contains).
 //! - `paranoid_unsafe`
 //!     - Please refer to the [`unsafe` in `gdbstub`](https://github.com/daniel5151/gdbstub#unsafe-in-gdbstub)
 //!       section of the README.md for more details.
@@ -250,6 +275,7 @@
 //!                 println!("Target terminated with signal {}", sig)
 //!             }
 //!             DisconnectReason::Kill => println!("GDB sent a kill command"),
+//!             DisconnectReason::StubShutdown => println!("The stub shut down"),
 //!         },
 //!         Err(GdbStubError::TargetError(e)) => {
 //!             println!("target encountered a fatal error: {}", e)
@@ -339,6 +365,9 @@ pub mod conn;
 pub mod stub;
 pub mod target;
 
+#[cfg(all(feature = "std", unix))]
+pub mod threaded;
+
 /// (Internal) The fake Tid that's used when running in single-threaded mode.
 // SAFETY: 1 is clearly non-zero.
 const SINGLE_THREAD_TID: common::Tid = unsafe { common::Tid::new_unchecked(1) };
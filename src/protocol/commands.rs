@@ -220,7 +220,9 @@ commands! {
 
     resume use 'a {
         "c" => _c::c<'a>,
+        "C" => _c_upcase::C<'a>,
         "s" => _s::s<'a>,
+        "S" => _s_upcase::S<'a>,
         "vCont" => _vCont::vCont<'a>,
     }
 
@@ -18,3 +18,37 @@ impl<'a> ParseCommand<'a> for c<'a> {
         Some(c { addr })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test_buf {
+        ($bufname:ident, $body:literal) => {
+            let mut test = $body.to_vec();
+            let mut buf = PacketBuf::new_with_raw_body(&mut test).unwrap();
+            if !buf.strip_prefix(b"c") {
+                panic!("invalid test");
+            }
+            let $bufname = buf;
+        };
+    }
+
+    #[test]
+    fn valid_c() {
+        test_buf!(buf, b"c");
+
+        let pkt = c::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.addr, None);
+    }
+
+    #[test]
+    fn valid_c_addr() {
+        test_buf!(buf, b"c1234");
+
+        let pkt = c::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.addr, Some(&[0x12, 0x34][..]));
+    }
+}
@@ -0,0 +1,72 @@
+use super::prelude::*;
+
+use crate::common::Signal;
+
+#[derive(Debug)]
+pub struct C<'a> {
+    pub sig: Signal,
+    pub addr: Option<&'a [u8]>,
+}
+
+impl<'a> ParseCommand<'a> for C<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let (sig, addr) = parse_sig_addr(buf.into_body())?;
+        Some(C { sig, addr })
+    }
+}
+
+/// Parse the `sig[;addr]` body shared by the `C` and `S` packets.
+pub(super) fn parse_sig_addr(body: &mut [u8]) -> Option<(Signal, Option<&[u8]>)> {
+    let mut body = body.splitn_mut_no_panic(2, |b| *b == b';');
+    let sig = Signal::from_protocol_u8(decode_hex(body.next()?).ok()?);
+    let addr = match body.next() {
+        Some(addr) => Some(decode_hex_buf(addr).ok()? as &[u8]),
+        None => None,
+    };
+    Some((sig, addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test_buf {
+        ($bufname:ident, $body:literal) => {
+            let mut test = $body.to_vec();
+            let mut buf = PacketBuf::new_with_raw_body(&mut test).unwrap();
+            if !buf.strip_prefix(b"C") {
+                panic!("invalid test");
+            }
+            let $bufname = buf;
+        };
+    }
+
+    #[test]
+    fn valid_C_sig() {
+        test_buf!(buf, b"C05");
+
+        let pkt = C::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.sig, Signal::SIGTRAP);
+        assert_eq!(pkt.addr, None);
+    }
+
+    #[test]
+    fn valid_C_sig_addr() {
+        test_buf!(buf, b"C0b;1234");
+
+        let pkt = C::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.sig, Signal::SIGSEGV);
+        assert_eq!(pkt.addr, Some(&[0x12, 0x34][..]));
+    }
+
+    #[test]
+    fn invalid_C() {
+        test_buf!(buf, b"C");
+        assert!(C::from_packet(buf).is_none());
+
+        test_buf!(buf, b"C05;xyz");
+        assert!(C::from_packet(buf).is_none());
+    }
+}
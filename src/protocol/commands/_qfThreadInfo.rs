@@ -1,13 +1,16 @@
 use super::prelude::*;
 
 #[derive(Debug)]
-pub struct qfThreadInfo;
+pub struct qfThreadInfo {
+    pub packet_buffer_len: usize,
+}
 
 impl<'a> ParseCommand<'a> for qfThreadInfo {
     fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let packet_buffer_len = buf.full_len();
         if !buf.into_body().is_empty() {
             return None;
         }
-        Some(qfThreadInfo)
+        Some(qfThreadInfo { packet_buffer_len })
     }
 }
@@ -0,0 +1,52 @@
+use super::prelude::*;
+
+use crate::common::Signal;
+
+#[derive(Debug)]
+pub struct S<'a> {
+    pub sig: Signal,
+    pub addr: Option<&'a [u8]>,
+}
+
+impl<'a> ParseCommand<'a> for S<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let (sig, addr) = super::_c_upcase::parse_sig_addr(buf.into_body())?;
+        Some(S { sig, addr })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test_buf {
+        ($bufname:ident, $body:literal) => {
+            let mut test = $body.to_vec();
+            let mut buf = PacketBuf::new_with_raw_body(&mut test).unwrap();
+            if !buf.strip_prefix(b"S") {
+                panic!("invalid test");
+            }
+            let $bufname = buf;
+        };
+    }
+
+    #[test]
+    fn valid_S_sig() {
+        test_buf!(buf, b"S05");
+
+        let pkt = S::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.sig, Signal::SIGTRAP);
+        assert_eq!(pkt.addr, None);
+    }
+
+    #[test]
+    fn valid_S_sig_addr() {
+        test_buf!(buf, b"S0b;1234");
+
+        let pkt = S::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.sig, Signal::SIGSEGV);
+        assert_eq!(pkt.addr, Some(&[0x12, 0x34][..]));
+    }
+}
@@ -31,8 +31,7 @@ impl<'a> ParseCommand<'a> for vCont<'a> {
 #[derive(Debug)]
 pub enum Actions<'a> {
     Buf(ActionsBuf<'a>),
-    FixedStep(SpecificThreadId),
-    FixedCont(SpecificThreadId),
+    Fixed(VContAction<'a>),
 }
 
 impl<'a> Actions<'a> {
@@ -40,25 +39,30 @@ impl<'a> Actions<'a> {
         Actions::Buf(ActionsBuf(buf))
     }
 
-    pub fn new_step(tid: SpecificThreadId) -> Actions<'a> {
-        Actions::FixedStep(tid)
+    pub fn new_step(tid: SpecificThreadId, signal: Option<Signal>) -> Actions<'a> {
+        Actions::Fixed(VContAction {
+            kind: match signal {
+                Some(sig) => VContKind::StepWithSig(sig),
+                None => VContKind::Step,
+            },
+            thread: Some(tid),
+        })
     }
 
-    pub fn new_continue(tid: SpecificThreadId) -> Actions<'a> {
-        Actions::FixedCont(tid)
+    pub fn new_continue(tid: SpecificThreadId, signal: Option<Signal>) -> Actions<'a> {
+        Actions::Fixed(VContAction {
+            kind: match signal {
+                Some(sig) => VContKind::ContinueWithSig(sig),
+                None => VContKind::Continue,
+            },
+            thread: Some(tid),
+        })
     }
 
     pub fn iter(&self) -> impl Iterator<Item = Option<VContAction<'a>>> + '_ {
         match self {
             Actions::Buf(x) => EitherIter::A(x.iter()),
-            Actions::FixedStep(x) => EitherIter::B(core::iter::once(Some(VContAction {
-                kind: VContKind::Step,
-                thread: Some(*x),
-            }))),
-            Actions::FixedCont(x) => EitherIter::B(core::iter::once(Some(VContAction {
-                kind: VContKind::Continue,
-                thread: Some(*x),
-            }))),
+            Actions::Fixed(x) => EitherIter::B(core::iter::once(Some(*x))),
         }
     }
 }
@@ -42,6 +42,8 @@ pub struct GdbStubBuilder<'a, T: Target, C: Connection> {
     conn: C,
     packet_buffer: Option<&'a mut [u8]>,
     packet_buffer_size: Option<usize>,
+    #[cfg(feature = "alloc")]
+    track_breakpoints: bool,
 
     _target: PhantomData<T>,
 }
@@ -53,6 +55,8 @@ impl<'a, T: Target, C: Connection> GdbStubBuilder<'a, T, C> {
             conn,
             packet_buffer: None,
             packet_buffer_size: None,
+            #[cfg(feature = "alloc")]
+            track_breakpoints: false,
 
             _target: PhantomData,
         }
@@ -75,6 +79,31 @@ impl<'a, T: Target, C: Connection> GdbStubBuilder<'a, T, C> {
         self
     }
 
+    /// Keep a stub-side record of all breakpoints / watchpoints set by the GDB
+    /// client. Defaults to `false`.
+    ///
+    /// This is useful when re-using a single `GdbStub` across multiple GDB
+    /// client connections (e.g: via [`GdbStub::run_blocking_server`]), as it
+    /// allows any breakpoints left behind by a previous client (e.g: one which
+    /// disconnected abruptly while the target was running) to be removed
+    /// before a new client connects.
+    ///
+    /// See [`GdbStubStateMachineInner::tracked_breakpoints`] and
+    /// [`GdbStubStateMachineInner::remove_tracked_breakpoints`].
+    ///
+    /// _Note:_ `track_breakpoints` is only available when the `alloc` feature
+    /// is enabled.
+    ///
+    /// [`GdbStubStateMachineInner::tracked_breakpoints`]:
+    /// super::state_machine::GdbStubStateMachineInner::tracked_breakpoints
+    /// [`GdbStubStateMachineInner::remove_tracked_breakpoints`]:
+    /// super::state_machine::GdbStubStateMachineInner::remove_tracked_breakpoints
+    #[cfg(feature = "alloc")]
+    pub fn track_breakpoints(mut self, enabled: bool) -> Self {
+        self.track_breakpoints = enabled;
+        self
+    }
+
     /// Build the GdbStub, returning an error if something went wrong.
     pub fn build(self) -> Result<GdbStub<'a, T, C>, GdbStubBuilderError> {
         let packet_buffer = match self.packet_buffer {
@@ -106,10 +135,17 @@ impl<'a, T: Target, C: Connection> GdbStubBuilder<'a, T, C> {
             }
         };
 
+        #[allow(unused_mut)]
+        let mut inner = GdbStubImpl::new();
+        #[cfg(feature = "alloc")]
+        if self.track_breakpoints {
+            inner.enable_breakpoint_tracking();
+        }
+
         Ok(GdbStub {
             conn: self.conn,
             packet_buffer,
-            inner: GdbStubImpl::new(),
+            inner,
         })
     }
 }
@@ -5,6 +5,7 @@ use crate::conn::Connection;
 use crate::protocol::commands::Command;
 use crate::protocol::{Packet, ResponseWriter, SpecificIdKind};
 use crate::stub::GdbStubError as Error;
+use crate::stub::{GdbStubSnapshot, SnapshotState};
 use crate::target::Target;
 use crate::SINGLE_THREAD_TID;
 
@@ -21,6 +22,7 @@ mod prelude {
     pub(super) use crate::target::Target;
 }
 
+mod async_target;
 mod auxv;
 mod base;
 mod breakpoints;
@@ -37,6 +39,8 @@ mod single_register_access;
 mod target_xml;
 mod x_upcase_packet;
 
+pub(crate) use async_target::AsyncRequest;
+pub use breakpoints::TrackedBreakpoint;
 pub(crate) use resume::FinishExecStatus;
 
 pub(crate) mod target_result_ext {
@@ -81,6 +85,9 @@ pub enum DisconnectReason {
     Disconnect,
     /// GDB issued a kill command
     Kill,
+    /// The stub ended the session (see
+    /// [`ShutdownReason::StubShutdown`](super::state_machine::ShutdownReason::StubShutdown))
+    StubShutdown,
 }
 
 pub enum State {
@@ -97,6 +104,24 @@ pub struct GdbStubImpl<T: Target, C: Connection> {
     current_mem_tid: Tid,
     current_resume_tid: SpecificIdKind,
     features: ProtocolFeatures,
+    /// Position within an in-progress `qfThreadInfo` / `qsThreadInfo`
+    /// enumeration (`None` once all threads have been reported).
+    thread_list_cursor: Option<usize>,
+    /// Record of breakpoints / watchpoints set by the GDB client (`None` if
+    /// breakpoint tracking is disabled).
+    #[cfg(feature = "alloc")]
+    tracked_breakpoints:
+        Option<alloc::vec::Vec<TrackedBreakpoint<<T::Arch as crate::arch::Arch>::Usize>>>,
+    /// Defer base target operations (register / memory access, resuming) to
+    /// an async driver, instead of invoking the target's sync methods.
+    ///
+    /// Only ever set by `run_async_target`.
+    defer_to_async: bool,
+    /// Address range of the packet buffer the current packet was parsed from
+    /// (only tracked while deferring to an async driver).
+    packet_buffer_addrs: core::ops::Range<usize>,
+    /// Operation awaiting completion by the async driver.
+    async_request: Option<AsyncRequest<<T::Arch as crate::arch::Arch>::Usize>>,
 }
 
 pub enum HandlerStatus {
@@ -104,6 +129,9 @@ pub enum HandlerStatus {
     NeedsOk,
     DeferredStopReason,
     Disconnect(DisconnectReason),
+    /// The response will be written once the async driver has completed the
+    /// pending `AsyncRequest`.
+    AsyncPending,
 }
 
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
@@ -123,9 +151,83 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             current_mem_tid: SINGLE_THREAD_TID,
             current_resume_tid: SpecificIdKind::WithId(SINGLE_THREAD_TID),
             features: ProtocolFeatures::empty(),
+            thread_list_cursor: None,
+            #[cfg(feature = "alloc")]
+            tracked_breakpoints: None,
+            defer_to_async: false,
+            packet_buffer_addrs: 0..0,
+            async_request: None,
         }
     }
 
+    /// Start recording breakpoints / watchpoints set by the GDB client.
+    #[cfg(feature = "alloc")]
+    pub fn enable_breakpoint_tracking(&mut self) {
+        self.tracked_breakpoints = Some(alloc::vec::Vec::new());
+    }
+
+    /// Return the breakpoints / watchpoints currently set by the GDB client.
+    #[cfg(feature = "alloc")]
+    pub fn tracked_breakpoints(
+        &self,
+    ) -> &[TrackedBreakpoint<<T::Arch as crate::arch::Arch>::Usize>] {
+        self.tracked_breakpoints.as_deref().unwrap_or(&[])
+    }
+
+    /// Capture the state negotiated with the GDB client, alongside the
+    /// provided state machine state.
+    pub fn snapshot(
+        &self,
+        state: SnapshotState<<T::Arch as crate::arch::Arch>::Usize>,
+    ) -> GdbStubSnapshot<<T::Arch as crate::arch::Arch>::Usize> {
+        GdbStubSnapshot {
+            no_ack_mode: self.features.no_ack_mode(),
+            multiprocess: self.features.multiprocess(),
+            current_mem_tid: self.current_mem_tid,
+            current_resume_tid: match self.current_resume_tid {
+                SpecificIdKind::WithId(tid) => Some(tid),
+                SpecificIdKind::All => None,
+            },
+            thread_list_cursor: self.thread_list_cursor,
+            #[cfg(feature = "alloc")]
+            tracked_breakpoints: self.tracked_breakpoints.clone(),
+            state,
+        }
+    }
+
+    /// Restore the state negotiated with a GDB client from a snapshot,
+    /// returning the snapshot's state machine state.
+    pub fn restore(
+        &mut self,
+        snapshot: GdbStubSnapshot<<T::Arch as crate::arch::Arch>::Usize>,
+    ) -> SnapshotState<<T::Arch as crate::arch::Arch>::Usize> {
+        self.features.set_no_ack_mode(snapshot.no_ack_mode);
+        self.features.set_multiprocess(snapshot.multiprocess);
+        self.current_mem_tid = snapshot.current_mem_tid;
+        self.current_resume_tid = match snapshot.current_resume_tid {
+            Some(tid) => SpecificIdKind::WithId(tid),
+            None => SpecificIdKind::All,
+        };
+        self.thread_list_cursor = snapshot.thread_list_cursor;
+        #[cfg(feature = "alloc")]
+        {
+            self.tracked_breakpoints = snapshot.tracked_breakpoints;
+        }
+        snapshot.state
+    }
+
+    /// Reset any state negotiated with the GDB client, in preparation for a
+    /// new client connecting.
+    ///
+    /// Any tracked breakpoints are retained.
+    pub fn reset_session(&mut self) {
+        self.current_mem_tid = SINGLE_THREAD_TID;
+        self.current_resume_tid = SpecificIdKind::WithId(SINGLE_THREAD_TID);
+        self.features = ProtocolFeatures::empty();
+        self.thread_list_cursor = None;
+        self.async_request = None;
+    }
+
     pub fn handle_packet(
         &mut self,
         target: &mut T,
@@ -143,6 +245,10 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 // Acknowledge the command
                 if !self.features.no_ack_mode() {
                     conn.write(b'+').map_err(Error::ConnectionWrite)?;
+                    // commands which resume the target don't send a response
+                    // until the target stops, so make sure buffering
+                    // connections don't hold onto the ack until then
+                    conn.flush().map_err(Error::ConnectionWrite)?;
                 }
 
                 let mut res = ResponseWriter::new(conn, target.use_rle());
@@ -153,6 +259,7 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                         None
                     }
                     Ok(HandlerStatus::DeferredStopReason) => return Ok(State::DeferredStopReason),
+                    Ok(HandlerStatus::AsyncPending) => return Ok(State::Pump),
                     Ok(HandlerStatus::Disconnect(reason)) => Some(reason),
                     // HACK: handling this "dummy" error is required as part of the
                     // `TargetResultExt::handle_error()` machinery.
@@ -0,0 +1,312 @@
+use super::prelude::*;
+
+use crate::arch::{Arch, Registers};
+use crate::common::Signal;
+use crate::target::ext::base::AsyncBase;
+
+/// A base target operation which has been deferred to the async driver (see
+/// [`GdbStub::run_async_target`](crate::stub::GdbStub::run_async_target)).
+///
+/// Any packet data required to complete the request is left in-place in the
+/// packet buffer, and is referenced by offset to avoid holding a borrow.
+pub(crate) enum AsyncRequest<U> {
+    /// `g`
+    ReadRegisters,
+    /// `G`
+    WriteRegisters(PacketData),
+    /// `m`
+    ReadAddrs { addr: U, len: usize },
+    /// `M` / `X`
+    WriteAddrs { addr: U, data: PacketData },
+    /// `c` / `C` / `vCont;c`
+    Resume(Option<Signal>),
+    /// `s` / `S` / `vCont;s`
+    Step(Option<Signal>),
+}
+
+/// Location of previously-parsed packet data within the packet buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PacketData {
+    offset: usize,
+    len: usize,
+}
+
+impl PacketData {
+    /// Locate `data` within the packet buffer spanning `buf_addrs`, returning
+    /// `None` if `data` doesn't lie within the buffer.
+    fn new(buf_addrs: &core::ops::Range<usize>, data: &[u8]) -> Option<PacketData> {
+        let start = data.as_ptr() as usize;
+        let end = start.checked_add(data.len())?;
+        if start < buf_addrs.start || end > buf_addrs.end {
+            return None;
+        }
+
+        Some(PacketData {
+            offset: start - buf_addrs.start,
+            len: data.len(),
+        })
+    }
+
+    /// Retrieve the data from the packet buffer, returning `None` if it lies
+    /// out of bounds.
+    fn get(self, buf: &[u8]) -> Option<&[u8]> {
+        buf.get(self.offset..self.offset.checked_add(self.len)?)
+    }
+}
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    /// Defer base target operations to an async driver, which must call
+    /// `complete_async_request` after each packet is handled.
+    pub fn set_defer_to_async(&mut self, enabled: bool) {
+        self.defer_to_async = enabled;
+    }
+
+    /// Whether base target operations should be deferred to the async driver.
+    #[inline(always)]
+    pub(crate) fn defer_to_async(&self) -> bool {
+        self.defer_to_async
+    }
+
+    /// Record the packet buffer the next packet will be parsed from, so that
+    /// deferred requests can refer to data within it.
+    #[inline(always)]
+    pub fn set_packet_buffer(&mut self, buf: &[u8]) {
+        if self.defer_to_async() {
+            let start = buf.as_ptr() as usize;
+            self.packet_buffer_addrs = start..start + buf.len();
+        }
+    }
+
+    /// Defer writing `data` (a slice of the current packet) to `addr`.
+    pub(crate) fn defer_write_addrs(
+        &mut self,
+        addr: <T::Arch as Arch>::Usize,
+        data: &[u8],
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let data =
+            PacketData::new(&self.packet_buffer_addrs, data).ok_or(Error::PacketUnexpected)?;
+        self.async_request = Some(AsyncRequest::WriteAddrs { addr, data });
+        Ok(HandlerStatus::AsyncPending)
+    }
+
+    /// Defer writing the registers serialized in `data` (a slice of the
+    /// current packet).
+    pub(crate) fn defer_write_registers(
+        &mut self,
+        data: &[u8],
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let data =
+            PacketData::new(&self.packet_buffer_addrs, data).ok_or(Error::PacketUnexpected)?;
+        self.async_request = Some(AsyncRequest::WriteRegisters(data));
+        Ok(HandlerStatus::AsyncPending)
+    }
+
+    /// Complete any pending `AsyncRequest`, writing the deferred response to
+    /// the connection.
+    ///
+    /// `packet_buffer` must be the buffer the most recent packet was parsed
+    /// from. Once the request has been completed, it is also re-used as
+    /// scratch space.
+    pub async fn complete_async_request<K>(
+        &mut self,
+        target: &mut T,
+        conn: &mut C,
+        packet_buffer: &mut [u8],
+    ) -> Result<(), Error<T::Error, C::Error>>
+    where
+        T: AsyncBase<K>,
+    {
+        let request = match self.async_request.take() {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+
+        let mut res = ResponseWriter::new(conn, target.use_rle());
+        let tid = self.current_mem_tid;
+
+        let result = match request {
+            // resuming the target doesn't send a response
+            AsyncRequest::Resume(signal) => {
+                return target
+                    .async_resume(signal)
+                    .await
+                    .map_err(Error::TargetError)
+            }
+            AsyncRequest::Step(signal) => {
+                return target.async_step(signal).await.map_err(Error::TargetError)
+            }
+            AsyncRequest::ReadRegisters => Self::async_read_registers(&mut res, target, tid).await,
+            AsyncRequest::WriteRegisters(data) => {
+                let mut regs: <T::Arch as Arch>::Registers = Default::default();
+                let data = data.get(packet_buffer).ok_or(Error::PacketUnexpected)?;
+                regs.gdb_deserialize(data)
+                    .map_err(|_| Error::TargetMismatch)?;
+
+                target
+                    .async_write_registers(&regs, tid)
+                    .await
+                    .handle_error()
+                    .map(|_| true)
+            }
+            AsyncRequest::ReadAddrs { addr, len } => {
+                Self::async_read_addrs(&mut res, target, tid, addr, len, packet_buffer).await
+            }
+            AsyncRequest::WriteAddrs { addr, data } => {
+                let data = data.get(packet_buffer).ok_or(Error::PacketUnexpected)?;
+                target
+                    .async_write_addrs(addr, data, tid)
+                    .await
+                    .handle_error()
+                    .map(|_| true)
+            }
+        };
+
+        // mirrors the response handling in `handle_packet`
+        match result {
+            Ok(true) => res.write_str("OK")?,
+            Ok(false) => {}
+            Err(Error::NonFatalError(code)) => {
+                res.write_str("E")?;
+                res.write_num(code)?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        res.flush()?;
+        Ok(())
+    }
+
+    async fn async_read_registers<K>(
+        res: &mut ResponseWriter<'_, C>,
+        target: &mut T,
+        tid: crate::common::Tid,
+    ) -> Result<bool, Error<T::Error, C::Error>>
+    where
+        T: AsyncBase<K>,
+    {
+        let mut regs: <T::Arch as Arch>::Registers = Default::default();
+        target
+            .async_read_registers(&mut regs, tid)
+            .await
+            .handle_error()?;
+
+        let mut err = Ok(());
+        regs.gdb_serialize(|val| {
+            let res = match val {
+                Some(b) => res.write_hex_buf(&[b]),
+                None => res.write_str("xx"),
+            };
+            if let Err(e) = res {
+                err = Err(e);
+            }
+        });
+        err?;
+        Ok(false)
+    }
+
+    async fn async_read_addrs<K>(
+        res: &mut ResponseWriter<'_, C>,
+        target: &mut T,
+        tid: crate::common::Tid,
+        addr: <T::Arch as Arch>::Usize,
+        len: usize,
+        buf: &mut [u8],
+    ) -> Result<bool, Error<T::Error, C::Error>>
+    where
+        T: AsyncBase<K>,
+    {
+        use num_traits::NumCast;
+
+        let mut i = 0;
+        let mut n = len;
+        while n != 0 {
+            let chunk_size = n.min(buf.len());
+
+            let addr = addr + NumCast::from(i).ok_or(Error::TargetMismatch)?;
+            let data = &mut buf[..chunk_size];
+            target
+                .async_read_addrs(addr, data, tid)
+                .await
+                .handle_error()?;
+
+            n -= chunk_size;
+            i += chunk_size;
+
+            res.write_hex_buf(data)?;
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::PacketData;
+    use crate::conn::{AsyncConnectionAdapter, ConnectionExt, MemConnection};
+    use crate::stub::state_machine::GdbStubStateMachine;
+    use crate::stub::test_util::{block_on, packets, AsyncMemConnection, TestTarget};
+    use crate::stub::GdbStub;
+    use crate::target::ext::base::async_kind::SingleThread;
+
+    #[test]
+    fn packet_data_bounds() {
+        let buf = [0u8; 8];
+        let addrs = {
+            let start = buf.as_ptr() as usize;
+            start..start + buf.len()
+        };
+
+        let data = PacketData::new(&addrs, &buf[2..5]).unwrap();
+        assert_eq!(data, PacketData { offset: 2, len: 3 });
+        assert_eq!(data.get(&buf), Some(&buf[2..5]));
+        // the data no longer fits within a (smaller) buffer
+        assert_eq!(data.get(&buf[..4]), None);
+
+        let other = [0u8; 4];
+        assert_eq!(PacketData::new(&addrs, &other), None);
+    }
+
+    #[test]
+    fn deferred_requests_complete() {
+        let mut target = TestTarget::new();
+        let mut conn = MemConnection::new();
+        conn.inject_packet(b"M10,2:abcd").unwrap();
+        conn.inject_packet(b"X20,2:\x12\xef").unwrap();
+        conn.inject_packet(b"G0100000002000000efbeadde").unwrap();
+        conn.inject_packet(b"g").unwrap();
+        conn.inject_packet(b"m10,4").unwrap();
+
+        let mut stub = GdbStub::new(AsyncConnectionAdapter::new(AsyncMemConnection(&mut conn)));
+        stub.inner.set_defer_to_async(true);
+        let mut gdb = stub.run_state_machine(&mut target).unwrap();
+
+        let mut responses = Vec::new();
+        loop {
+            let mut idle = match gdb {
+                GdbStubStateMachine::Idle(gdb) => gdb,
+                _ => panic!("unexpected state"),
+            };
+
+            let conn = idle.borrow_conn();
+            block_on(conn.drain()).unwrap();
+            let conn = &mut *conn.get_mut().0;
+            responses.extend(packets(conn.output()));
+            conn.clear_output();
+            let byte = match ConnectionExt::read(conn) {
+                Ok(byte) => byte,
+                Err(_) => break,
+            };
+
+            gdb = idle.incoming_data(&mut target, byte).unwrap();
+            block_on(gdb.complete_async_request::<SingleThread>(&mut target)).unwrap();
+        }
+
+        assert_eq!(
+            responses,
+            ["OK", "OK", "OK", "0100000002000000efbeadde", "abcd1213"]
+        );
+        assert_eq!(target.mem[0x20..0x22], [0x12, 0xef]);
+        assert_eq!(target.regs.pc, 0xdeadbeef);
+        assert_eq!(target.sync_calls, 0);
+        assert_eq!(target.async_calls, 5);
+    }
+}
@@ -3,11 +3,11 @@ use crate::protocol::commands::ext::Base;
 
 use crate::arch::{Arch, Registers};
 use crate::common::Tid;
-use crate::protocol::{IdKind, SpecificIdKind, SpecificThreadId};
+use crate::protocol::{IdKind, ResponseWriterError, SpecificIdKind, SpecificThreadId};
 use crate::target::ext::base::{BaseOps, ResumeOps};
 use crate::{FAKE_PID, SINGLE_THREAD_TID};
 
-use super::DisconnectReason;
+use super::{AsyncRequest, DisconnectReason};
 
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     #[inline(always)]
@@ -34,6 +34,71 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         Ok(tid)
     }
 
+    /// Write the next page of the `qfThreadInfo` / `qsThreadInfo` thread list,
+    /// resuming from wherever the previous page left off.
+    fn write_thread_list_page(
+        &mut self,
+        res: &mut ResponseWriter<'_, C>,
+        target: &mut T,
+        packet_buffer_len: usize,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let cursor = match self.thread_list_cursor.take() {
+            Some(cursor) => cursor,
+            None => {
+                res.write_str("l")?;
+                return Ok(());
+            }
+        };
+
+        let mut page = ThreadListPage {
+            res,
+            multiprocess: self.features.multiprocess(),
+            remaining: packet_buffer_len.saturating_sub("$m#XX".len()),
+            empty: true,
+            err: Ok(()),
+        };
+
+        let next_cursor = match target.base_ops() {
+            BaseOps::SingleThread(_) => {
+                page.push(SINGLE_THREAD_TID);
+                None
+            }
+            BaseOps::MultiThread(ops) => match ops.support_list_cursor() {
+                Some(ops) => ops
+                    .list_active_threads_from(cursor, &mut |tid| page.push(tid))
+                    .map_err(Error::TargetError)?,
+                None => {
+                    // no cursor support, so re-walk the thread list, skipping over any
+                    // threads which were already reported.
+                    let mut idx = 0;
+                    let mut next_cursor = None;
+                    ops.list_active_threads(&mut |tid| {
+                        if idx >= cursor && next_cursor.is_none() && !page.push(tid) {
+                            next_cursor = Some(idx);
+                        }
+                        idx += 1;
+                    })
+                    .map_err(Error::TargetError)?;
+                    next_cursor
+                }
+            },
+        };
+
+        let ThreadListPage {
+            res, empty, err, ..
+        } = page;
+        err?;
+
+        if empty {
+            // threads may have exited between pages
+            res.write_str("l")?;
+        } else {
+            self.thread_list_cursor = next_cursor;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn handle_base<'a>(
         &mut self,
         res: &mut ResponseWriter<'_, C>,
@@ -178,6 +243,10 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 res.write_str(if is_attached { "1" } else { "0" })?;
                 HandlerStatus::Handled
             }
+            Base::g(_) if self.defer_to_async() => {
+                self.async_request = Some(AsyncRequest::ReadRegisters);
+                HandlerStatus::AsyncPending
+            }
             Base::g(_) => {
                 let mut regs: <T::Arch as Arch>::Registers = Default::default();
                 match target.base_ops() {
@@ -202,6 +271,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 HandlerStatus::Handled
             }
             Base::G(cmd) => {
+                if self.defer_to_async() {
+                    // the register data is left in-place in the packet buffer
+                    return self.defer_write_registers(cmd.vals);
+                }
+
                 let mut regs: <T::Arch as Arch>::Registers = Default::default();
                 regs.gdb_deserialize(cmd.vals)
                     .map_err(|_| Error::TargetMismatch)?;
@@ -219,6 +293,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
                     .ok_or(Error::TargetMismatch)?;
 
+                if self.defer_to_async() {
+                    self.async_request = Some(AsyncRequest::ReadAddrs { addr, len: cmd.len });
+                    return Ok(HandlerStatus::AsyncPending);
+                }
+
                 let mut i = 0;
                 let mut n = cmd.len;
                 while n != 0 {
@@ -247,6 +326,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
                     .ok_or(Error::TargetMismatch)?;
 
+                if self.defer_to_async() {
+                    // the data to write is left in-place in the packet buffer
+                    return self.defer_write_addrs(addr, cmd.val);
+                }
+
                 match target.base_ops() {
                     BaseOps::SingleThread(ops) => ops.write_addrs(addr, cmd.val),
                     BaseOps::MultiThread(ops) => {
@@ -311,50 +395,13 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 }
                 HandlerStatus::NeedsOk
             }
-            Base::qfThreadInfo(_) => {
-                res.write_str("m")?;
-
-                match target.base_ops() {
-                    BaseOps::SingleThread(_) => res.write_specific_thread_id(SpecificThreadId {
-                        pid: self
-                            .features
-                            .multiprocess()
-                            .then(|| SpecificIdKind::WithId(FAKE_PID)),
-                        tid: SpecificIdKind::WithId(SINGLE_THREAD_TID),
-                    })?,
-                    BaseOps::MultiThread(ops) => {
-                        let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
-                        let mut first = true;
-                        ops.list_active_threads(&mut |tid| {
-                            // TODO: replace this with a try block (once stabilized)
-                            let e = (|| {
-                                if !first {
-                                    res.write_str(",")?
-                                }
-                                first = false;
-                                res.write_specific_thread_id(SpecificThreadId {
-                                    pid: self
-                                        .features
-                                        .multiprocess()
-                                        .then(|| SpecificIdKind::WithId(FAKE_PID)),
-                                    tid: SpecificIdKind::WithId(tid),
-                                })?;
-                                Ok(())
-                            })();
-
-                            if let Err(e) = e {
-                                err = Err(e)
-                            }
-                        })
-                        .map_err(Error::TargetError)?;
-                        err?;
-                    }
-                }
-
+            Base::qfThreadInfo(cmd) => {
+                self.thread_list_cursor = Some(0);
+                self.write_thread_list_page(res, target, cmd.packet_buffer_len)?;
                 HandlerStatus::Handled
             }
-            Base::qsThreadInfo(_) => {
-                res.write_str("l")?;
+            Base::qsThreadInfo(cmd) => {
+                self.write_thread_list_page(res, target, cmd.packet_buffer_len)?;
                 HandlerStatus::Handled
             }
             Base::T(cmd) => {
@@ -378,3 +425,211 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         Ok(handler_status)
     }
 }
+
+/// Accumulates a single `qfThreadInfo` / `qsThreadInfo` response, ensuring that
+/// the response doesn't exceed the negotiated packet size.
+struct ThreadListPage<'a, 'b, C: Connection> {
+    res: &'a mut ResponseWriter<'b, C>,
+    multiprocess: bool,
+    remaining: usize,
+    empty: bool,
+    err: Result<(), ResponseWriterError<C::Error>>,
+}
+
+impl<C: Connection> ThreadListPage<'_, '_, C> {
+    /// Append `tid` to the response, returning `false` if the thread didn't
+    /// fit (or if the connection returned an error).
+    fn push(&mut self, tid: Tid) -> bool {
+        if self.err.is_err() {
+            return false;
+        }
+
+        let pid = if self.multiprocess {
+            Some(SpecificIdKind::WithId(FAKE_PID))
+        } else {
+            None
+        };
+
+        let mut len = hex_num_len(tid.get());
+        if pid.is_some() {
+            len += "p.".len() + hex_num_len(FAKE_PID.get());
+        }
+        if !self.empty {
+            len += ",".len();
+        }
+
+        // always report at least one thread per page, to guarantee forward progress
+        if !self.empty && len > self.remaining {
+            return false;
+        }
+        self.remaining = self.remaining.saturating_sub(len);
+
+        let sep = if self.empty { "m" } else { "," };
+        self.empty = false;
+        self.err = self.res.write_str(sep).and_then(|_| {
+            self.res.write_specific_thread_id(SpecificThreadId {
+                pid,
+                tid: SpecificIdKind::WithId(tid),
+            })
+        });
+
+        self.err.is_ok()
+    }
+}
+
+/// Number of chars `ResponseWriter::write_num` uses to encode `n`.
+fn hex_num_len(n: usize) -> usize {
+    let mut bytes = 1;
+    let mut n = n >> 8;
+    while n != 0 {
+        bytes += 1;
+        n >>= 8;
+    }
+    bytes * 2
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::common::Tid;
+    use crate::conn::MemConnection;
+    use crate::stub::test_util::{feed, take_responses, TestArch, TestRegs};
+    use crate::stub::GdbStub;
+    use crate::target::ext::base::multithread::{
+        MultiThreadBase, MultiThreadListCursor, MultiThreadListCursorOps,
+    };
+    use crate::target::ext::base::BaseOps;
+    use crate::target::{Target, TargetResult};
+
+    /// A target with threads `1..=count`, which only cares about being listed.
+    struct Threads {
+        count: usize,
+        cursor: bool,
+        /// Cursors passed to `list_active_threads_from`.
+        cursors: Vec<usize>,
+    }
+
+    impl Target for Threads {
+        type Arch = TestArch;
+        type Error = ();
+
+        fn base_ops(&mut self) -> BaseOps<'_, TestArch, ()> {
+            BaseOps::MultiThread(self)
+        }
+
+        fn guard_rail_implicit_sw_breakpoints(&self) -> bool {
+            true
+        }
+
+        fn use_rle(&self) -> bool {
+            false
+        }
+    }
+
+    impl MultiThreadBase for Threads {
+        fn read_registers(&mut self, _regs: &mut TestRegs, _tid: Tid) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn write_registers(&mut self, _regs: &TestRegs, _tid: Tid) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn read_addrs(
+            &mut self,
+            _addr: u32,
+            _data: &mut [u8],
+            _tid: Tid,
+        ) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn write_addrs(&mut self, _addr: u32, _data: &[u8], _tid: Tid) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn list_active_threads(
+            &mut self,
+            thread_is_active: &mut dyn FnMut(Tid),
+        ) -> Result<(), Self::Error> {
+            for tid in 1..=self.count {
+                thread_is_active(Tid::new(tid).unwrap());
+            }
+            Ok(())
+        }
+
+        fn support_list_cursor(&mut self) -> Option<MultiThreadListCursorOps<'_, Self>> {
+            if self.cursor {
+                Some(self)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl MultiThreadListCursor for Threads {
+        fn list_active_threads_from(
+            &mut self,
+            cursor: usize,
+            thread_is_active: &mut dyn FnMut(Tid) -> bool,
+        ) -> Result<Option<usize>, Self::Error> {
+            self.cursors.push(cursor);
+            for idx in cursor..self.count {
+                if !thread_is_active(Tid::new(idx + 1).unwrap()) {
+                    return Ok(Some(idx));
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    fn list_threads(target: &mut Threads) -> Vec<alloc::string::String> {
+        let mut conn = MemConnection::new();
+        conn.inject_packet(b"qfThreadInfo").unwrap();
+        conn.inject_packet(b"qsThreadInfo").unwrap();
+        conn.inject_packet(b"qsThreadInfo").unwrap();
+
+        // just large enough to receive `qsThreadInfo`, leaving room for 4
+        // threads per page
+        let stub = GdbStub::builder(conn)
+            .packet_buffer_size(18)
+            .build()
+            .unwrap();
+        let mut gdb = feed(stub.run_state_machine(target).unwrap(), target);
+        take_responses(&mut gdb)
+    }
+
+    #[test]
+    fn thread_list_pages() {
+        let mut target = Threads {
+            count: 5,
+            cursor: false,
+            cursors: Vec::new(),
+        };
+        assert_eq!(list_threads(&mut target), ["m01,02,03,04", "m05", "l"]);
+    }
+
+    #[test]
+    fn thread_list_pages_with_cursor() {
+        let mut target = Threads {
+            count: 5,
+            cursor: true,
+            cursors: Vec::new(),
+        };
+        assert_eq!(list_threads(&mut target), ["m01,02,03,04", "m05", "l"]);
+        // the final `l` is sent without consulting the target
+        assert_eq!(target.cursors, [0, 4]);
+    }
+
+    #[test]
+    fn thread_list_single_page() {
+        let mut target = Threads {
+            count: 4,
+            cursor: true,
+            cursors: Vec::new(),
+        };
+        assert_eq!(list_threads(&mut target), ["m01,02,03,04", "l", "l"]);
+        assert_eq!(target.cursors, [0]);
+    }
+}
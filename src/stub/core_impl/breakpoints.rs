@@ -2,12 +2,53 @@ use super::prelude::*;
 use crate::protocol::commands::ext::Breakpoints;
 
 use crate::arch::{Arch, BreakpointKind};
+use crate::target::ext::breakpoints::WatchKind;
 
 enum CmdKind {
     Add,
     Remove,
 }
 
+/// A breakpoint / watchpoint which was set by the GDB client, as recorded by
+/// the stub.
+///
+/// Breakpoint tracking must be enabled via
+/// [`GdbStubBuilder::track_breakpoints`](crate::stub::GdbStubBuilder::track_breakpoints).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrackedBreakpoint<U> {
+    /// A software breakpoint.
+    Sw {
+        /// Address of the breakpoint
+        addr: U,
+        /// Raw (arch-specific) breakpoint kind, as sent by the GDB client
+        kind: usize,
+    },
+    /// A hardware breakpoint.
+    Hw {
+        /// Address of the breakpoint
+        addr: U,
+        /// Raw (arch-specific) breakpoint kind, as sent by the GDB client
+        kind: usize,
+    },
+    /// A hardware watchpoint.
+    Watch {
+        /// Address of watched memory
+        addr: U,
+        /// Length of watched memory
+        len: U,
+        /// Kind of watchpoint
+        kind: WatchKind,
+    },
+}
+
+#[cfg(feature = "alloc")]
+fn bp_kind_from_raw<T: Target, C>(
+    kind: usize,
+) -> Result<<T::Arch as Arch>::BreakpointKind, Error<T::Error, C>> {
+    <T::Arch as Arch>::BreakpointKind::from_usize(kind).ok_or(Error::TargetMismatch)
+}
+
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     #[inline(always)]
     fn handle_breakpoint_common(
@@ -19,33 +60,41 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         let addr =
             <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr).ok_or(Error::TargetMismatch)?;
 
-        macro_rules! bp_kind {
+        macro_rules! raw_kind {
             () => {
-                BeBytes::from_be_bytes(cmd.kind)
-                    .and_then(<T::Arch as Arch>::BreakpointKind::from_usize)
+                BeBytes::from_be_bytes(cmd.kind).ok_or(Error::TargetMismatch)?
+            };
+        }
+
+        macro_rules! bp_kind {
+            ($raw_kind:expr) => {
+                <T::Arch as Arch>::BreakpointKind::from_usize($raw_kind)
                     .ok_or(Error::TargetMismatch)?
             };
         }
 
-        let supported = match cmd.type_ {
+        let (supported, bp) = match cmd.type_ {
             0 if ops.support_sw_breakpoint().is_some() => {
                 let ops = ops.support_sw_breakpoint().unwrap();
-                let bp_kind = bp_kind!();
-                match cmd_kind {
+                let kind = raw_kind!();
+                let bp_kind = bp_kind!(kind);
+                let supported = match cmd_kind {
                     CmdKind::Add => ops.add_sw_breakpoint(addr, bp_kind),
                     CmdKind::Remove => ops.remove_sw_breakpoint(addr, bp_kind),
-                }
+                };
+                (supported, TrackedBreakpoint::Sw { addr, kind })
             }
             1 if ops.support_hw_breakpoint().is_some() => {
                 let ops = ops.support_hw_breakpoint().unwrap();
-                let bp_kind = bp_kind!();
-                match cmd_kind {
+                let kind = raw_kind!();
+                let bp_kind = bp_kind!(kind);
+                let supported = match cmd_kind {
                     CmdKind::Add => ops.add_hw_breakpoint(addr, bp_kind),
                     CmdKind::Remove => ops.remove_hw_breakpoint(addr, bp_kind),
-                }
+                };
+                (supported, TrackedBreakpoint::Hw { addr, kind })
             }
             2 | 3 | 4 if ops.support_hw_watchpoint().is_some() => {
-                use crate::target::ext::breakpoints::WatchKind;
                 let kind = match cmd.type_ {
                     2 => WatchKind::Write,
                     3 => WatchKind::Read,
@@ -55,10 +104,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 let len = <T::Arch as Arch>::Usize::from_be_bytes(cmd.kind)
                     .ok_or(Error::TargetMismatch)?;
                 let ops = ops.support_hw_watchpoint().unwrap();
-                match cmd_kind {
+                let supported = match cmd_kind {
                     CmdKind::Add => ops.add_hw_watchpoint(addr, len, kind),
                     CmdKind::Remove => ops.remove_hw_watchpoint(addr, len, kind),
-                }
+                };
+                (supported, TrackedBreakpoint::Watch { addr, len, kind })
             }
             // explicitly handle unguarded variants of known breakpoint types
             0 | 1 | 2 | 3 | 4 => return Ok(HandlerStatus::Handled),
@@ -70,11 +120,89 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         };
 
         match supported.handle_error()? {
-            true => Ok(HandlerStatus::NeedsOk),
+            true => {
+                self.track_breakpoint(bp, cmd_kind);
+                Ok(HandlerStatus::NeedsOk)
+            }
             false => Err(Error::NonFatalError(22)),
         }
     }
 
+    fn track_breakpoint(
+        &mut self,
+        bp: TrackedBreakpoint<<T::Arch as Arch>::Usize>,
+        cmd_kind: CmdKind,
+    ) {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "alloc")] {
+                let bps = match &mut self.tracked_breakpoints {
+                    Some(bps) => bps,
+                    None => return,
+                };
+
+                let idx = bps.iter().position(|x| *x == bp);
+                match (cmd_kind, idx) {
+                    (CmdKind::Add, None) => bps.push(bp),
+                    (CmdKind::Remove, Some(idx)) => {
+                        bps.swap_remove(idx);
+                    }
+                    _ => {}
+                }
+            } else {
+                let _ = (bp, cmd_kind);
+            }
+        }
+    }
+
+    /// Remove all tracked breakpoints / watchpoints from the target, clearing
+    /// the stub's record of them.
+    ///
+    /// Non-fatal errors from the target are ignored, as the breakpoint may
+    /// have already been removed by some other means.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn remove_tracked_breakpoints(
+        &mut self,
+        target: &mut T,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let bps = match &mut self.tracked_breakpoints {
+            Some(bps) => core::mem::take(bps),
+            None => return Ok(()),
+        };
+
+        let ops = match target.support_breakpoints() {
+            Some(ops) => ops,
+            None => return Ok(()),
+        };
+
+        for bp in bps {
+            let res = match bp {
+                TrackedBreakpoint::Sw { addr, kind } => match ops.support_sw_breakpoint() {
+                    Some(ops) => {
+                        ops.remove_sw_breakpoint(addr, bp_kind_from_raw::<T, C::Error>(kind)?)
+                    }
+                    None => continue,
+                },
+                TrackedBreakpoint::Hw { addr, kind } => match ops.support_hw_breakpoint() {
+                    Some(ops) => {
+                        ops.remove_hw_breakpoint(addr, bp_kind_from_raw::<T, C::Error>(kind)?)
+                    }
+                    None => continue,
+                },
+                TrackedBreakpoint::Watch { addr, len, kind } => match ops.support_hw_watchpoint() {
+                    Some(ops) => ops.remove_hw_watchpoint(addr, len, kind),
+                    None => continue,
+                },
+            };
+
+            match res.handle_error() {
+                Ok(_) | Err(Error::NonFatalError(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn handle_breakpoints<'a>(
         &mut self,
         _res: &mut ResponseWriter<'_, C>,
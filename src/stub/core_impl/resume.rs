@@ -11,7 +11,7 @@ use crate::target::ext::base::ResumeOps;
 use crate::target::ext::catch_syscalls::CatchSyscallPosition;
 use crate::FAKE_PID;
 
-use super::DisconnectReason;
+use super::{AsyncRequest, DisconnectReason};
 
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     pub(crate) fn handle_stop_resume<'a>(
@@ -55,27 +55,92 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     vCont::Actions(actions) => actions,
                 }
             }
-            // TODO?: support custom resume addr in 'c' and 's'
-            //
-            // vCont doesn't have a notion of "resume addr", and since the implementation of these
-            // packets reuse vCont infrastructure, supporting this obscure feature will be a bit
-            // annoying...
-            //
             // TODO: add `support_legacy_s_c_packets` flag (similar to `use_X_packet`)
-            Resume::c(_) => Actions::new_continue(SpecificThreadId {
-                pid: None,
-                tid: self.current_resume_tid,
-            }),
-            Resume::s(_) => Actions::new_step(SpecificThreadId {
-                pid: None,
-                tid: self.current_resume_tid,
-            }),
+            Resume::c(cmd) => {
+                self.set_resume_addr(&mut ops, cmd.addr)?;
+                Actions::new_continue(
+                    SpecificThreadId {
+                        pid: None,
+                        tid: self.current_resume_tid,
+                    },
+                    None,
+                )
+            }
+            Resume::C(cmd) => {
+                self.set_resume_addr(&mut ops, cmd.addr)?;
+                Actions::new_continue(
+                    SpecificThreadId {
+                        pid: None,
+                        tid: self.current_resume_tid,
+                    },
+                    Some(cmd.sig),
+                )
+            }
+            Resume::s(cmd) => {
+                self.set_resume_addr(&mut ops, cmd.addr)?;
+                Actions::new_step(
+                    SpecificThreadId {
+                        pid: None,
+                        tid: self.current_resume_tid,
+                    },
+                    None,
+                )
+            }
+            Resume::S(cmd) => {
+                self.set_resume_addr(&mut ops, cmd.addr)?;
+                Actions::new_step(
+                    SpecificThreadId {
+                        pid: None,
+                        tid: self.current_resume_tid,
+                    },
+                    Some(cmd.sig),
+                )
+            }
         };
 
         self.do_vcont(ops, actions)
     }
 
+    /// Update the PC prior to resuming, if the legacy `c`/`s`/`C`/`S` packets
+    /// specified a resume address.
+    fn set_resume_addr(
+        &mut self,
+        ops: &mut ResumeOps<'_, T::Arch, T::Error>,
+        addr: Option<&[u8]>,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let addr = match addr {
+            Some(addr) => {
+                <T::Arch as Arch>::Usize::from_be_bytes(addr).ok_or(Error::TargetMismatch)?
+            }
+            None => return Ok(()),
+        };
+
+        crate::__dead_code_marker!("resume_at_addr", "impl");
+
+        match ops {
+            ResumeOps::SingleThread(ops) => match ops.support_resume_at_addr() {
+                Some(ops) => ops.set_resume_addr(addr).handle_error()?,
+                None => return Err(Error::NonFatalError(22)),
+            },
+            ResumeOps::MultiThread(ops) => {
+                let tid = match self.current_resume_tid {
+                    SpecificIdKind::WithId(tid) => tid,
+                    // can't set the PC of every thread to the same address
+                    SpecificIdKind::All => return Err(Error::NonFatalError(22)),
+                };
+
+                match ops.support_resume_at_addr() {
+                    Some(ops) => ops.set_resume_addr(tid, addr).handle_error()?,
+                    None => return Err(Error::NonFatalError(22)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn do_vcont_single_thread(
+        &mut self,
         ops: &mut dyn crate::target::ext::base::singlethread::SingleThreadResume<
             Arch = T::Arch,
             Error = T::Error,
@@ -117,7 +182,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     _ => None,
                 };
 
-                ops.resume(signal).map_err(Error::TargetError)?;
+                if self.defer_to_async() {
+                    self.async_request = Some(AsyncRequest::Resume(signal));
+                } else {
+                    ops.resume(signal).map_err(Error::TargetError)?;
+                }
                 Ok(())
             }
             VContKind::Step | VContKind::StepWithSig(_) if ops.support_single_step().is_some() => {
@@ -128,7 +197,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     _ => None,
                 };
 
-                ops.step(signal).map_err(Error::TargetError)?;
+                if self.defer_to_async() {
+                    self.async_request = Some(AsyncRequest::Step(signal));
+                } else {
+                    ops.step(signal).map_err(Error::TargetError)?;
+                }
                 Ok(())
             }
             VContKind::RangeStep(start, end) if ops.support_range_step().is_some() => {
@@ -153,6 +226,7 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     }
 
     fn do_vcont_multi_thread(
+        &mut self,
         ops: &mut dyn crate::target::ext::base::multithread::MultiThreadResume<
             Arch = T::Arch,
             Error = T::Error,
@@ -238,6 +312,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             }
         }
 
+        if self.defer_to_async() {
+            self.async_request = Some(AsyncRequest::Resume(None));
+            return Ok(());
+        }
+
         ops.resume().map_err(Error::TargetError)
     }
 
@@ -247,8 +326,8 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         actions: Actions<'_>,
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
         match ops {
-            ResumeOps::SingleThread(ops) => Self::do_vcont_single_thread(ops, &actions)?,
-            ResumeOps::MultiThread(ops) => Self::do_vcont_multi_thread(ops, &actions)?,
+            ResumeOps::SingleThread(ops) => self.do_vcont_single_thread(ops, &actions)?,
+            ResumeOps::MultiThread(ops) => self.do_vcont_multi_thread(ops, &actions)?,
         };
 
         Ok(HandlerStatus::DeferredStopReason)
@@ -430,3 +509,27 @@ pub(crate) enum FinishExecStatus {
     Handled,
     Disconnect(DisconnectReason),
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::conn::MemConnection;
+    use crate::stub::state_machine::GdbStubStateMachine;
+    use crate::stub::test_util::{feed, take_responses, TestTarget};
+    use crate::stub::GdbStub;
+
+    #[test]
+    fn resume_addr_unsupported() {
+        let mut target = TestTarget::new();
+        let mut conn = MemConnection::new();
+        conn.inject_packet(b"c10").unwrap();
+        conn.inject_packet(b"S05;10").unwrap();
+
+        let stub = GdbStub::new(conn);
+        let mut gdb = feed(stub.run_state_machine(&mut target).unwrap(), &mut target);
+
+        // EINVAL, without resuming the target
+        assert!(matches!(gdb, GdbStubStateMachine::Idle(_)));
+        assert_eq!(take_responses(&mut gdb), ["E16", "E16"]);
+        assert_eq!(target.resumed, None);
+    }
+}
@@ -22,6 +22,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
                     .ok_or(Error::TargetMismatch)?;
 
+                if self.defer_to_async() {
+                    // the data to write is left in-place in the packet buffer
+                    return self.defer_write_addrs(addr, cmd.val);
+                }
+
                 match target.base_ops() {
                     BaseOps::SingleThread(ops) => ops.write_addrs(addr, cmd.val),
                     BaseOps::MultiThread(ops) => {
@@ -3,19 +3,30 @@
 
 use managed::ManagedSlice;
 
-use crate::conn::{Connection, ConnectionExt};
+use crate::conn::{
+    AsyncConnection, AsyncConnectionAdapter, AsyncConnectionAdapterError, Connection, ConnectionExt,
+};
+use crate::target::ext::base::AsyncBase;
 use crate::target::Target;
 
 mod builder;
 mod core_impl;
 mod error;
+mod snapshot;
 mod stop_reason;
 
 pub mod state_machine;
 
+#[cfg(all(feature = "std", unix))]
+pub mod run_poll;
+
+#[cfg(all(test, feature = "alloc"))]
+pub(crate) mod test_util;
+
 pub use builder::{GdbStubBuilder, GdbStubBuilderError};
-pub use core_impl::DisconnectReason;
+pub use core_impl::{DisconnectReason, TrackedBreakpoint};
 pub use error::GdbStubError;
+pub use snapshot::{GdbStubSnapshot, SnapshotState};
 pub use stop_reason::{
     BaseStopReason, IntoStopReason, MultiThreadStopReason, SingleThreadStopReason,
 };
@@ -106,6 +117,94 @@ pub mod run_blocking {
     }
 }
 
+/// Types and traits related to the [`GdbStub::run_async`] interface.
+pub mod run_async {
+    use super::*;
+
+    use crate::conn::AsyncConnection;
+
+    pub use super::run_blocking::{Event, WaitForStopReasonError};
+
+    /// A set of user-provided methods required to run a GDB debugging session
+    /// using the [`GdbStub::run_async`] method.
+    ///
+    /// This is the async counterpart to
+    /// [`BlockingEventLoop`](super::run_blocking::BlockingEventLoop).
+    #[allow(async_fn_in_trait)]
+    pub trait AsyncEventLoop {
+        /// The Target being driven.
+        type Target: Target;
+        /// Connection being used to drive the target.
+        type Connection: AsyncConnection;
+
+        /// Which variant of the `StopReason` type should be used. Single
+        /// threaded targets should use [`SingleThreadStopReason`], whereas
+        /// multi threaded targets should use [`MultiThreadStopReason`].
+        ///
+        /// [`SingleThreadStopReason`]: crate::stub::SingleThreadStopReason
+        /// [`MultiThreadStopReason`]: crate::stub::MultiThreadStopReason
+        type StopReason: IntoStopReason<Self::Target>;
+
+        /// Invoked immediately after the target's `resume` method has been
+        /// called. The returned future should resolve once either the target
+        /// reports a stop reason, or new data is sent over the connection.
+        ///
+        /// The specific mechanism to "select" between these two events is
+        /// implementation specific (e.g: `tokio::select!`,
+        /// `embassy_futures::select`, etc...).
+        async fn wait_for_stop_reason(
+            target: &mut Self::Target,
+            conn: &mut Self::Connection,
+        ) -> Result<
+            Event<Self::StopReason>,
+            WaitForStopReasonError<
+                <Self::Target as Target>::Error,
+                <Self::Connection as AsyncConnection>::Error,
+            >,
+        >;
+
+        /// Invoked when the GDB client sends a Ctrl-C interrupt.
+        ///
+        /// See
+        /// [`BlockingEventLoop::on_interrupt`](super::run_blocking::BlockingEventLoop::on_interrupt)
+        /// for more details.
+        async fn on_interrupt(
+            target: &mut Self::Target,
+        ) -> Result<Option<Self::StopReason>, <Self::Target as Target>::Error>;
+    }
+
+    /// Completes target operations deferred by the stub. Only the
+    /// [`GdbStub::run_async_target`] runner defers operations.
+    pub(super) trait CompleteDeferred<T: Target> {
+        async fn complete<C: AsyncConnection>(
+            gdb: &mut state_machine::GdbStubStateMachine<'_, T, AsyncConnectionAdapter<'_, C>>,
+            target: &mut T,
+        ) -> Result<(), Error<T::Error, AsyncConnectionAdapterError<C::Error>>>;
+    }
+
+    pub(super) enum NoDeferral {}
+
+    impl<T: Target> CompleteDeferred<T> for NoDeferral {
+        async fn complete<C: AsyncConnection>(
+            _gdb: &mut state_machine::GdbStubStateMachine<'_, T, AsyncConnectionAdapter<'_, C>>,
+            _target: &mut T,
+        ) -> Result<(), Error<T::Error, AsyncConnectionAdapterError<C::Error>>> {
+            Ok(())
+        }
+    }
+
+    pub(super) struct Deferral<K>(core::marker::PhantomData<K>);
+
+    impl<K, T: AsyncBase<K>> CompleteDeferred<T> for Deferral<K> {
+        async fn complete<C: AsyncConnection>(
+            gdb: &mut state_machine::GdbStubStateMachine<'_, T, AsyncConnectionAdapter<'_, C>>,
+            target: &mut T,
+        ) -> Result<(), Error<T::Error, AsyncConnectionAdapterError<C::Error>>> {
+            gdb.complete_async_request::<K>(target).await
+        }
+    }
+}
+
 /// Debug a [`Target`] using the GDB Remote Serial Protocol over a given
 /// [`Connection`].
 pub struct GdbStub<'a, T: Target, C: Connection> {
@@ -133,6 +232,24 @@ impl<'a, T: Target, C: Connection> GdbStub<'a, T, C> {
         GdbStubBuilder::new(conn).build().unwrap()
     }
 
+    /// Start a GDB remote debugging session using a `poll(2)` based event
+    /// loop.
+    ///
+    /// This works just like [`GdbStub::run_blocking`], except that instead of
+    /// implementing `wait_for_stop_reason` by hand, the implementation
+    /// provides a [`run_poll::PollEventLoop`], and `gdbstub` waits on both
+    /// the connection and a target-supplied [`run_poll::WakeEvent`]. The
+    /// target's execution thread signals that the target has stopped via a
+    /// [`run_poll::WakeHandle`], without either side having to busy-poll.
+    #[cfg(all(feature = "std", unix))]
+    pub fn run_poll<E>(self, target: &mut T) -> Result<DisconnectReason, Error<T::Error, C::Error>>
+    where
+        C: ConnectionExt,
+        E: run_poll::PollEventLoop<Target = T, Connection = C>,
+    {
+        self.run_blocking::<run_poll::PollEventLoopAdapter<E>>(target)
+    }
+
     /// (Quickstart) Start a GDB remote debugging session using a blocking event
     /// loop.
     ///
@@ -158,6 +275,56 @@ impl<'a, T: Target, C: Connection> GdbStub<'a, T, C> {
         self,
         target: &mut T,
     ) -> Result<DisconnectReason, Error<T::Error, C::Error>>
+    where
+        C: ConnectionExt,
+        E: run_blocking::BlockingEventLoop<Target = T, Connection = C>,
+    {
+        // run_blocking keeps things simple, and doesn't re-use the state machine
+        self.run_blocking_impl::<E>(target, None)
+    }
+
+    /// (Quickstart) Serve multiple GDB remote debugging sessions using a
+    /// blocking event loop, keeping the target attached across client
+    /// reconnects.
+    ///
+    /// This method behaves identically to [`GdbStub::run_blocking`], except
+    /// that whenever a session ends, `accept` is invoked with the reason the
+    /// session ended. Returning `Some(conn)` will start a new session over
+    /// `conn`, whereas returning `None` will return from
+    /// `run_blocking_server`.
+    ///
+    /// Unlike `run_blocking`, a failure to read from the connection is treated
+    /// as the GDB client disconnecting (i.e: `accept` is invoked with
+    /// [`DisconnectReason::Disconnect`]).
+    ///
+    /// If the connection is lost while the target is running, the target is
+    /// halted via [`BlockingEventLoop::on_interrupt`] (discarding any returned
+    /// stop reason), as new sessions always begin with the target stopped.
+    ///
+    /// If breakpoint tracking was enabled via
+    /// [`GdbStubBuilder::track_breakpoints`], any breakpoints left behind by
+    /// the previous client are removed from the target before the new session
+    /// begins.
+    ///
+    /// [`BlockingEventLoop::on_interrupt`]:
+    /// run_blocking::BlockingEventLoop::on_interrupt
+    pub fn run_blocking_server<E>(
+        self,
+        target: &mut T,
+        mut accept: impl FnMut(DisconnectReason) -> Option<C>,
+    ) -> Result<DisconnectReason, Error<T::Error, C::Error>>
+    where
+        C: ConnectionExt,
+        E: run_blocking::BlockingEventLoop<Target = T, Connection = C>,
+    {
+        self.run_blocking_impl::<E>(target, Some(&mut accept))
+    }
+
+    fn run_blocking_impl<E>(
+        self,
+        target: &mut T,
+        mut accept: Option<&mut dyn FnMut(DisconnectReason) -> Option<C>>,
+    ) -> Result<DisconnectReason, Error<T::Error, C::Error>>
     where
         C: ConnectionExt,
         E: run_blocking::BlockingEventLoop<Target = T, Connection = C>,
@@ -167,14 +334,25 @@ impl<'a, T: Target, C: Connection> GdbStub<'a, T, C> {
             gdb = match gdb {
                 state_machine::GdbStubStateMachine::Idle(mut gdb) => {
                     // needs more data, so perform a blocking read on the connection
-                    let byte = gdb.borrow_conn().read().map_err(Error::ConnectionRead)?;
-                    gdb.incoming_data(target, byte)?
+                    match gdb.borrow_conn().read() {
+                        Ok(byte) => gdb.incoming_data(target, byte)?,
+                        Err(_) if accept.is_some() => gdb.connection_lost(),
+                        Err(e) => break Err(Error::ConnectionRead(e)),
+                    }
                 }
 
                 state_machine::GdbStubStateMachine::Disconnected(gdb) => {
-                    // run_blocking keeps things simple, and doesn't expose a way to re-use the
-                    // state machine
-                    break Ok(gdb.get_reason());
+                    let reason = gdb.get_reason();
+                    let conn = match accept.as_mut().and_then(|accept| accept(reason)) {
+                        Some(conn) => conn,
+                        None => break Ok(reason),
+                    };
+
+                    #[cfg(feature = "alloc")]
+                    let mut gdb = gdb;
+                    #[cfg(feature = "alloc")]
+                    gdb.remove_tracked_breakpoints(target)?;
+                    gdb.reconnect(conn)?
                 }
 
                 state_machine::GdbStubStateMachine::CtrlCInterrupt(gdb) => {
@@ -200,6 +378,11 @@ impl<'a, T: Target, C: Connection> GdbStub<'a, T, C> {
                         Err(WaitForStopReasonError::Target(e)) => {
                             break Err(Error::TargetError(e));
                         }
+                        Err(WaitForStopReasonError::Connection(_)) if accept.is_some() => {
+                            // the next client expects to attach to a stopped target
+                            E::on_interrupt(target).map_err(Error::TargetError)?;
+                            gdb.connection_lost()
+                        }
                         Err(WaitForStopReasonError::Connection(e)) => {
                             break Err(Error::ConnectionRead(e));
                         }
@@ -264,4 +447,450 @@ impl<'a, T: Target, C: Connection> GdbStub<'a, T, C> {
 
         Ok(state_machine::GdbStubStateMachineInner::from_plain_gdbstub(self).into())
     }
+
+    /// Resume a GDB remote debugging session from a
+    /// [snapshot](GdbStubSnapshot) captured via
+    /// [`GdbStubStateMachine::snapshot`](state_machine::GdbStubStateMachine::snapshot),
+    /// converting this instance of `GdbStub` into a
+    /// [`GdbStubStateMachine`](state_machine::GdbStubStateMachine) in the
+    /// snapshot's state.
+    ///
+    /// This allows an active debugging session to be moved to a new `GdbStub`
+    /// (and a new connection) without the GDB client observing a protocol
+    /// reset.
+    ///
+    /// _Note:_ if the `alloc` feature is enabled, the snapshot's tracked
+    /// breakpoints take precedence over
+    /// [`GdbStubBuilder::track_breakpoints`].
+    pub fn restore_state_machine(
+        self,
+        target: &mut T,
+        snapshot: GdbStubSnapshot<<T::Arch as crate::arch::Arch>::Usize>,
+    ) -> Result<state_machine::GdbStubStateMachine<'a, T, C>, Error<T::Error, C::Error>> {
+        match self.run_state_machine(target)? {
+            state_machine::GdbStubStateMachine::Idle(gdb) => Ok(gdb.restore(snapshot)),
+            // `run_state_machine` always returns a state machine in the `Idle` state
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<'a, 'b, T: Target, C: AsyncConnection> GdbStub<'a, T, AsyncConnectionAdapter<'b, C>> {
+    /// (Quickstart) Start a GDB remote debugging session using an async event
+    /// loop.
+    ///
+    /// This is the async counterpart to [`GdbStub::run_blocking`], whereby
+    /// the implementation provides an implementation of
+    /// [`run_async::AsyncEventLoop`] that describes how to drive the target.
+    ///
+    /// `run_async` only relies on `core::future`, and can therefore be used
+    /// with any async executor (including in `#![no_std]` contexts).
+    ///
+    /// The `GdbStub` must be constructed using an [`AsyncConnectionAdapter`],
+    /// which buffers the stub's outgoing data until it can be asynchronously
+    /// written out over the underlying [`AsyncConnection`].
+    pub async fn run_async<E>(
+        self,
+        target: &mut T,
+    ) -> Result<DisconnectReason, Error<T::Error, AsyncConnectionAdapterError<C::Error>>>
+    where
+        E: run_async::AsyncEventLoop<Target = T, Connection = C>,
+    {
+        self.run_async_impl::<E, run_async::NoDeferral>(target)
+            .await
+    }
+
+    /// Start a GDB remote debugging session using an async event loop, while
+    /// also awaiting the target's register / memory accesses and resume
+    /// operations.
+    ///
+    /// This works just like [`GdbStub::run_async`], except that the target's
+    /// [`AsyncSingleThreadBase`] / [`AsyncMultiThreadBase`] methods are
+    /// called in place of their synchronous counterparts. This is useful for
+    /// targets that must await some event before they can be inspected (e.g:
+    /// pausing a vCPU thread), as it avoids blocking the executor.
+    ///
+    /// Operations not covered by the async base traits (e.g: single register
+    /// access, breakpoints, range stepping) continue to use the target's
+    /// synchronous IDETs.
+    ///
+    /// The `K` type parameter is inferred from which of the async base traits
+    /// the target implements.
+    ///
+    /// [`AsyncSingleThreadBase`]: crate::target::ext::base::singlethread::AsyncSingleThreadBase
+    /// [`AsyncMultiThreadBase`]: crate::target::ext::base::multithread::AsyncMultiThreadBase
+    pub async fn run_async_target<E, K>(
+        mut self,
+        target: &mut T,
+    ) -> Result<DisconnectReason, Error<T::Error, AsyncConnectionAdapterError<C::Error>>>
+    where
+        E: run_async::AsyncEventLoop<Target = T, Connection = C>,
+        T: AsyncBase<K>,
+    {
+        self.inner.set_defer_to_async(true);
+        self.run_async_impl::<E, run_async::Deferral<K>>(target)
+            .await
+    }
+
+    async fn run_async_impl<E, D>(
+        mut self,
+        target: &mut T,
+    ) -> Result<DisconnectReason, Error<T::Error, AsyncConnectionAdapterError<C::Error>>>
+    where
+        E: run_async::AsyncEventLoop<Target = T, Connection = C>,
+        D: run_async::CompleteDeferred<T>,
+    {
+        use AsyncConnectionAdapterError::Connection as AdapterConnection;
+
+        self.conn
+            .get_mut()
+            .on_session_start()
+            .await
+            .map_err(|e| Error::ConnectionInit(AdapterConnection(e)))?;
+
+        let mut gdb = self.run_state_machine(target)?;
+        loop {
+            // complete any target operation deferred by the last packet
+            D::complete(&mut gdb, target).await?;
+
+            gdb = match gdb {
+                state_machine::GdbStubStateMachine::Idle(mut gdb) => {
+                    let conn = gdb.borrow_conn();
+                    conn.drain()
+                        .await
+                        .map_err(|e| Error::ConnectionWrite(AdapterConnection(e)))?;
+                    let byte = conn
+                        .get_mut()
+                        .read()
+                        .await
+                        .map_err(|e| Error::ConnectionRead(AdapterConnection(e)))?;
+                    gdb.incoming_data(target, byte)?
+                }
+
+                state_machine::GdbStubStateMachine::Disconnected(mut gdb) => {
+                    // make sure any final responses (e.g: `W` / `X` packets) reach the client
+                    gdb.borrow_conn()
+                        .drain()
+                        .await
+                        .map_err(|e| Error::ConnectionWrite(AdapterConnection(e)))?;
+                    break Ok(gdb.get_reason());
+                }
+
+                state_machine::GdbStubStateMachine::CtrlCInterrupt(gdb) => {
+                    let stop_reason = E::on_interrupt(target).await.map_err(Error::TargetError)?;
+                    gdb.interrupt_handled(target, stop_reason)?
+                }
+
+                state_machine::GdbStubStateMachine::Running(mut gdb) => {
+                    use run_async::{Event as AsyncEventLoopEvent, WaitForStopReasonError};
+
+                    let conn = gdb.borrow_conn();
+                    conn.drain()
+                        .await
+                        .map_err(|e| Error::ConnectionWrite(AdapterConnection(e)))?;
+
+                    let event = E::wait_for_stop_reason(target, conn.get_mut()).await;
+                    match event {
+                        Ok(AsyncEventLoopEvent::TargetStopped(stop_reason)) => {
+                            gdb.report_stop(target, stop_reason)?
+                        }
+
+                        Ok(AsyncEventLoopEvent::IncomingData(byte)) => {
+                            gdb.incoming_data(target, byte)?
+                        }
+
+                        Err(WaitForStopReasonError::Target(e)) => {
+                            break Err(Error::TargetError(e));
+                        }
+                        Err(WaitForStopReasonError::Connection(e)) => {
+                            break Err(Error::ConnectionRead(AdapterConnection(e)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::test_util::{
+        block_on, packets, AsyncMemConnection, TestAsyncEventLoop, TestEventLoop, TestTarget,
+    };
+    use super::*;
+    use crate::conn::MemConnection;
+
+    /// Connection with a complete GDB session queued up.
+    fn session() -> MemConnection<'static> {
+        let mut conn = MemConnection::new();
+        for pkt in [&b"?"[..], b"g", b"M0,1:ff", b"c", b"m0,2", b"D"] {
+            conn.inject_packet(pkt).unwrap();
+        }
+        conn
+    }
+
+    const RESPONSES: [&str; 6] = ["S05", "000000000000000000100000", "OK", "S05", "ff01", "OK"];
+
+    #[test]
+    fn run_async_session() {
+        let mut target = TestTarget::new();
+        target.regs.pc = 0x1000;
+
+        let mut conn = session();
+        let stub = GdbStub::new(AsyncConnectionAdapter::new(AsyncMemConnection(&mut conn)));
+        let reason = block_on(stub.run_async::<TestAsyncEventLoop<'_>>(&mut target)).unwrap();
+
+        assert_eq!(reason, DisconnectReason::Disconnect);
+        assert_eq!(packets(conn.output()), RESPONSES);
+        assert_eq!(target.mem[0], 0xff);
+        assert_eq!(target.resumed, Some(None));
+        assert!(target.sync_calls > 0);
+        assert_eq!(target.async_calls, 0);
+    }
+
+    #[test]
+    fn run_async_target_session() {
+        let mut target = TestTarget::new();
+        target.regs.pc = 0x1000;
+
+        let mut conn = session();
+        let stub = GdbStub::new(AsyncConnectionAdapter::new(AsyncMemConnection(&mut conn)));
+        let reason =
+            block_on(stub.run_async_target::<TestAsyncEventLoop<'_>, _>(&mut target)).unwrap();
+
+        assert_eq!(reason, DisconnectReason::Disconnect);
+        assert_eq!(packets(conn.output()), RESPONSES);
+        assert_eq!(target.mem[0], 0xff);
+        assert_eq!(target.resumed, Some(None));
+        assert_eq!(target.sync_calls, 0);
+        assert_eq!(target.async_calls, 4);
+    }
+
+    #[test]
+    fn run_blocking_server_reconnect() {
+        let mut target = TestTarget::new();
+
+        // the first client leaves a breakpoint behind, and goes away while the
+        // target is running
+        let mut conn = MemConnection::new();
+        for pkt in [&b"Z0,10,4"[..], b"Z0,20,4", b"z0,10,4", b"c"] {
+            conn.inject_packet(pkt).unwrap();
+        }
+
+        let mut next_conn = Some({
+            let mut conn = MemConnection::new();
+            conn.inject_packet(b"Z0,30,4").unwrap();
+            conn.inject_packet(b"D").unwrap();
+            conn
+        });
+
+        let mut reasons = Vec::new();
+        let stub = GdbStub::builder(conn)
+            .track_breakpoints(true)
+            .build()
+            .unwrap();
+        let reason = stub
+            .run_blocking_server::<TestEventLoop>(&mut target, |reason| {
+                reasons.push(reason);
+                next_conn.take()
+            })
+            .unwrap();
+
+        assert_eq!(reason, DisconnectReason::Disconnect);
+        assert_eq!(
+            reasons,
+            [DisconnectReason::Disconnect, DisconnectReason::Disconnect]
+        );
+        // halted via `on_interrupt` once the first client went away
+        assert_eq!(target.resumed, Some(None));
+        assert!(!target.running);
+        // only the second client's breakpoint remains
+        assert_eq!(target.breakpoints, [0x30]);
+    }
+
+    #[test]
+    fn tracked_breakpoints() {
+        let mut target = TestTarget::new();
+        let mut conn = MemConnection::new();
+        for pkt in [&b"Z0,10,4"[..], b"Z0,20,2", b"z0,10,4", b"D"] {
+            conn.inject_packet(pkt).unwrap();
+        }
+
+        let stub = GdbStub::builder(conn)
+            .track_breakpoints(true)
+            .build()
+            .unwrap();
+        let gdb = test_util::feed(stub.run_state_machine(&mut target).unwrap(), &mut target);
+        let mut gdb = match gdb {
+            state_machine::GdbStubStateMachine::Disconnected(gdb) => gdb,
+            _ => panic!("expected the client to detach"),
+        };
+
+        assert_eq!(
+            gdb.tracked_breakpoints(),
+            [TrackedBreakpoint::Sw {
+                addr: 0x20,
+                kind: 2
+            }]
+        );
+        assert_eq!(target.breakpoints, [0x20]);
+
+        gdb.remove_tracked_breakpoints(&mut target).unwrap();
+        assert!(gdb.tracked_breakpoints().is_empty());
+        assert!(target.breakpoints.is_empty());
+
+        // subsequent sessions start with a clean slate
+        let mut conn = MemConnection::new();
+        conn.inject_packet(b"Z0,40,4").unwrap();
+        let gdb = gdb.reconnect(conn).unwrap();
+        let gdb = test_util::feed(gdb, &mut target);
+        match gdb {
+            state_machine::GdbStubStateMachine::Idle(gdb) => assert_eq!(
+                gdb.tracked_breakpoints(),
+                [TrackedBreakpoint::Sw {
+                    addr: 0x40,
+                    kind: 4
+                }]
+            ),
+            _ => panic!("expected the stub to be idle"),
+        }
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    mod run_poll {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixStream;
+        use std::string::String;
+        use std::time::Duration;
+
+        use super::super::test_util::{TestPollEventLoop, TestTarget};
+        use super::super::{DisconnectReason, GdbStub};
+
+        fn send_packet(client: &mut UnixStream, payload: &[u8]) {
+            let checksum = payload.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+            client.write_all(b"$").unwrap();
+            client.write_all(payload).unwrap();
+            write!(client, "#{:02x}", checksum).unwrap();
+        }
+
+        /// Read the payload of the next packet sent by the stub, skipping over
+        /// any acks.
+        fn recv_packet(client: &mut UnixStream) -> String {
+            let mut read_byte = || {
+                let mut b = [0];
+                client.read_exact(&mut b).unwrap();
+                b[0]
+            };
+
+            while read_byte() != b'$' {}
+            let payload = core::iter::from_fn(|| Some(read_byte()))
+                .take_while(|b| *b != b'#')
+                .collect();
+            // checksum
+            read_byte();
+            read_byte();
+            String::from_utf8(payload).unwrap()
+        }
+
+        /// Run a session over a `UnixStream` pair, with the client's side of
+        /// the session driven by `client` on another thread.
+        fn session(
+            target: &mut TestTarget,
+            client: impl FnOnce(&mut UnixStream) + Send + 'static,
+        ) -> DisconnectReason {
+            let (conn, mut client_conn) = UnixStream::pair().unwrap();
+            // fail the test rather than hang it if the stub goes quiet
+            client_conn
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let client = std::thread::spawn(move || client(&mut client_conn));
+
+            let reason = GdbStub::new(conn)
+                .run_poll::<TestPollEventLoop>(target)
+                .unwrap();
+            client.join().unwrap();
+            reason
+        }
+
+        fn detach(client: &mut UnixStream) {
+            send_packet(client, b"D");
+            assert_eq!(recv_packet(client), "OK");
+        }
+
+        #[test]
+        fn run_poll_wake() {
+            let mut target = TestTarget::new();
+            let wake = target.poll.wake.handle();
+
+            let reason = session(&mut target, move |client| {
+                send_packet(client, b"c");
+                std::thread::spawn(move || wake.wake().unwrap())
+                    .join()
+                    .unwrap();
+                assert_eq!(recv_packet(client), "S05");
+                detach(client);
+            });
+
+            assert_eq!(reason, DisconnectReason::Disconnect);
+            assert_eq!(target.poll.stop_checks, 1);
+        }
+
+        #[test]
+        fn run_poll_coalesced_wakes() {
+            let mut target = TestTarget::new();
+            let wake = target.poll.wake.handle();
+            for _ in 0..3 {
+                wake.wake().unwrap();
+            }
+
+            let reason = session(&mut target, |client| {
+                send_packet(client, b"c");
+                assert_eq!(recv_packet(client), "S05");
+                // any leftover wake-ups would report another SIGTRAP as soon
+                // as the target is resumed, rather than waiting for the Ctrl-C
+                send_packet(client, b"c");
+                client.write_all(&[0x03]).unwrap();
+                assert_eq!(recv_packet(client), "S02");
+                detach(client);
+            });
+
+            assert_eq!(reason, DisconnectReason::Disconnect);
+            assert_eq!(target.poll.stop_checks, 1);
+        }
+
+        #[test]
+        fn run_poll_timeout() {
+            let mut target = TestTarget::new();
+            target.poll.timeout = Some(Duration::from_millis(1));
+
+            let reason = session(&mut target, |client| {
+                send_packet(client, b"c");
+                assert_eq!(recv_packet(client), "S05");
+                detach(client);
+            });
+
+            assert_eq!(reason, DisconnectReason::Disconnect);
+            assert_eq!(target.poll.stop_checks, 1);
+        }
+
+        #[test]
+        fn run_poll_incoming_data() {
+            let mut target = TestTarget::new();
+
+            let reason = session(&mut target, |client| {
+                send_packet(client, b"c");
+                client.write_all(&[0x03]).unwrap();
+                assert_eq!(recv_packet(client), "S02");
+                detach(client);
+            });
+
+            assert_eq!(reason, DisconnectReason::Disconnect);
+            // the Ctrl-C was read off the connection, without the target
+            // ever being woken
+            assert_eq!(target.poll.stop_checks, 0);
+            assert!(!target.running);
+        }
+    }
 }
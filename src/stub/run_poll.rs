@@ -0,0 +1,186 @@
+//! Types and traits related to the [`GdbStub::run_poll`] interface.
+//!
+//! [`GdbStub::run_poll`]: super::GdbStub::run_poll
+
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::conn::ConnectionExt;
+use crate::stub::run_blocking::{BlockingEventLoop, Event, WaitForStopReasonError};
+use crate::stub::IntoStopReason;
+use crate::target::Target;
+use crate::util::poll::poll_readable;
+
+/// A pollable event, used by a target's execution thread to wake up the
+/// [`GdbStub::run_poll`](super::GdbStub::run_poll) event loop (e.g: when the
+/// target has stopped).
+///
+/// Wake-ups are coalesced: waking the event multiple times before the event
+/// loop gets around to servicing it results in a single call to
+/// [`PollEventLoop::check_stop_reason`].
+#[derive(Debug)]
+pub struct WakeEvent {
+    rx: UnixStream,
+    tx: Arc<UnixStream>,
+}
+
+impl WakeEvent {
+    /// Create a new `WakeEvent`.
+    pub fn new() -> io::Result<WakeEvent> {
+        let (rx, tx) = UnixStream::pair()?;
+        rx.set_nonblocking(true)?;
+        tx.set_nonblocking(true)?;
+        Ok(WakeEvent {
+            rx,
+            tx: Arc::new(tx),
+        })
+    }
+
+    /// Return a handle which can be used to wake the event (e.g: from the
+    /// target's execution thread).
+    pub fn handle(&self) -> WakeHandle {
+        WakeHandle(self.tx.clone())
+    }
+
+    /// Consume any pending wake-ups.
+    fn clear(&self) -> io::Result<()> {
+        let mut buf = [0; 64];
+        loop {
+            match (&self.rx).read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(_) => continue,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl AsRawFd for WakeEvent {
+    fn as_raw_fd(&self) -> RawFd {
+        self.rx.as_raw_fd()
+    }
+}
+
+/// A cloneable, thread-safe handle used to wake a [`WakeEvent`].
+#[derive(Debug, Clone)]
+pub struct WakeHandle(Arc<UnixStream>);
+
+impl WakeHandle {
+    /// Wake the associated [`WakeEvent`].
+    pub fn wake(&self) -> io::Result<()> {
+        match (&*self.0).write(&[1]) {
+            Ok(_) => Ok(()),
+            // the socket buffer is full, so a wake-up is already pending
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A set of user-provided methods required to run a GDB debugging session
+/// using the [`GdbStub::run_poll`](super::GdbStub::run_poll) method.
+///
+/// While the target is running, the event loop sleeps in `poll(2)` until
+/// either the GDB client sends data over the connection, the target's
+/// [`WakeEvent`] is woken, or the (optional) timeout expires. As such, an idle
+/// debugging session consumes no CPU time.
+pub trait PollEventLoop {
+    /// The Target being driven.
+    type Target: Target;
+    /// Connection being used to drive the target.
+    type Connection: ConnectionExt<Error = io::Error> + AsRawFd;
+
+    /// Which variant of the `StopReason` type should be used. Single
+    /// threaded targets should use [`SingleThreadStopReason`], whereas
+    /// multi threaded targets should use [`MultiThreadStopReason`].
+    ///
+    /// [`SingleThreadStopReason`]: crate::stub::SingleThreadStopReason
+    /// [`MultiThreadStopReason`]: crate::stub::MultiThreadStopReason
+    type StopReason: IntoStopReason<Self::Target>;
+
+    /// Return the target's [`WakeEvent`].
+    fn wake_event(target: &Self::Target) -> &WakeEvent;
+
+    /// Return the maximum amount of time to wait for an event while the
+    /// target is running, after which [`check_stop_reason`] is called
+    /// regardless.
+    ///
+    /// Defaults to `None` (i.e: wait indefinitely).
+    ///
+    /// [`check_stop_reason`]: Self::check_stop_reason
+    fn timeout(target: &mut Self::Target) -> Option<Duration> {
+        let _ = target;
+        None
+    }
+
+    /// Invoked whenever the target's [`WakeEvent`] has been woken (or the
+    /// timeout has expired). Returns the target's stop reason, or `None` if
+    /// the target is still running.
+    fn check_stop_reason(
+        target: &mut Self::Target,
+    ) -> Result<Option<Self::StopReason>, <Self::Target as Target>::Error>;
+
+    /// Invoked when the GDB client sends a Ctrl-C interrupt.
+    ///
+    /// See [`BlockingEventLoop::on_interrupt`] for details.
+    fn on_interrupt(
+        target: &mut Self::Target,
+    ) -> Result<Option<Self::StopReason>, <Self::Target as Target>::Error>;
+}
+
+/// Drives a [`PollEventLoop`] via the `run_blocking` machinery.
+pub(super) struct PollEventLoopAdapter<E>(PhantomData<E>);
+
+impl<E: PollEventLoop> BlockingEventLoop for PollEventLoopAdapter<E> {
+    type Target = E::Target;
+    type Connection = E::Connection;
+    type StopReason = E::StopReason;
+
+    fn wait_for_stop_reason(
+        target: &mut E::Target,
+        conn: &mut E::Connection,
+    ) -> Result<Event<E::StopReason>, WaitForStopReasonError<<E::Target as Target>::Error, io::Error>>
+    {
+        loop {
+            let timeout = E::timeout(target);
+            let [conn_ready, wake_ready] = poll_readable(
+                [conn.as_raw_fd(), E::wake_event(target).as_raw_fd()],
+                timeout,
+            )
+            .map_err(WaitForStopReasonError::Connection)?;
+
+            // prioritize reporting stop reasons over incoming data, as the
+            // incoming data will still be there on the next iteration
+            if wake_ready || (!conn_ready && timeout.is_some()) {
+                if wake_ready {
+                    E::wake_event(target)
+                        .clear()
+                        .map_err(WaitForStopReasonError::Connection)?;
+                }
+
+                if let Some(reason) =
+                    E::check_stop_reason(target).map_err(WaitForStopReasonError::Target)?
+                {
+                    return Ok(Event::TargetStopped(reason));
+                }
+            }
+
+            if conn_ready {
+                let byte = conn.read().map_err(WaitForStopReasonError::Connection)?;
+                return Ok(Event::IncomingData(byte));
+            }
+        }
+    }
+
+    fn on_interrupt(
+        target: &mut E::Target,
+    ) -> Result<Option<E::StopReason>, <E::Target as Target>::Error> {
+        E::on_interrupt(target)
+    }
+}
@@ -0,0 +1,206 @@
+//! Serializable snapshots of a [`GdbStub`](super::GdbStub)'s session state.
+
+use crate::common::Tid;
+use crate::stub::MultiThreadStopReason;
+
+#[cfg(feature = "alloc")]
+use crate::stub::TrackedBreakpoint;
+
+/// A snapshot of the state negotiated between a `GdbStub` and its GDB client.
+///
+/// Snapshots can be used to transparently move an active debugging session
+/// between `GdbStub` instances (e.g: when live-migrating a VM to a different
+/// host), whereby the GDB client will observe a brief pause, rather than a
+/// protocol reset.
+///
+/// Snapshots are obtained via [`GdbStubStateMachine::snapshot`], and are
+/// restored via [`GdbStub::restore_state_machine`].
+///
+/// When the `serde` feature is enabled, snapshots implement `Serialize` and
+/// `Deserialize`.
+///
+/// _Note:_ Snapshots do not include partially received packets, and should
+/// therefore be taken in-between calls to `incoming_data`, after the most
+/// recent packet has been fully processed.
+///
+/// [`GdbStubStateMachine::snapshot`]:
+/// super::state_machine::GdbStubStateMachine::snapshot
+/// [`GdbStub::restore_state_machine`]: super::GdbStub::restore_state_machine
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct GdbStubSnapshot<U> {
+    /// Whether the client enabled no-ack mode (via `QStartNoAckMode`).
+    pub no_ack_mode: bool,
+    /// Whether the client negotiated the `multiprocess` protocol extension.
+    pub multiprocess: bool,
+    /// Thread selected for memory / register access (via `Hg`).
+    pub current_mem_tid: Tid,
+    /// Thread selected for resumption (via `Hc`), where `None` corresponds to
+    /// "all threads".
+    pub current_resume_tid: Option<Tid>,
+    /// Position within an in-progress `qfThreadInfo` / `qsThreadInfo`
+    /// enumeration.
+    pub thread_list_cursor: Option<usize>,
+    /// Breakpoints / watchpoints recorded by the stub (`None` if breakpoint
+    /// tracking is disabled).
+    #[cfg(feature = "alloc")]
+    pub tracked_breakpoints: Option<alloc::vec::Vec<TrackedBreakpoint<U>>>,
+    /// The state of the underlying state machine.
+    pub state: SnapshotState<U>,
+}
+
+/// The [`GdbStubStateMachine`](super::state_machine::GdbStubStateMachine)
+/// state captured by a [`GdbStubSnapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnapshotState<U> {
+    /// The target is stopped, and the stub is waiting for the next packet.
+    Idle {
+        /// Stop reason from a Ctrl-C interrupt which arrived while the target
+        /// was stopped, which will be reported once the target is resumed.
+        deferred_ctrlc_stop_reason: Option<MultiThreadStopReason<U>>,
+    },
+    /// The target is running, and the GDB client is waiting for a stop
+    /// reason.
+    Running,
+    /// The GDB client sent a Ctrl-C interrupt which has not yet been handled.
+    CtrlCInterrupt {
+        /// Whether the interrupt arrived while the target was stopped.
+        from_idle: bool,
+    },
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::common::Signal;
+    use crate::conn::MemConnection;
+    use crate::stub::state_machine::GdbStubStateMachine;
+    use crate::stub::test_util::{feed, take_responses, TestTarget};
+    use crate::stub::{GdbStub, SingleThreadStopReason};
+
+    type Gdb = GdbStubStateMachine<'static, TestTarget, MemConnection<'static>>;
+
+    /// Negotiate no-ack mode and set a breakpoint, before processing `input`.
+    fn session(target: &mut TestTarget, input: &[u8]) -> Gdb {
+        let mut conn = MemConnection::new();
+        conn.inject_packet(b"QStartNoAckMode").unwrap();
+        conn.inject(b"+").unwrap();
+        conn.inject_packet(b"Z0,10,4").unwrap();
+        conn.inject(input).unwrap();
+
+        let stub = GdbStub::builder(conn)
+            .track_breakpoints(true)
+            .build()
+            .unwrap();
+        let mut gdb = feed(stub.run_state_machine(target).unwrap(), target);
+        take_responses(&mut gdb);
+        gdb
+    }
+
+    /// Move the session over to a fresh stub, checking that the restored
+    /// state machine reports an identical snapshot.
+    fn round_trip(target: &mut TestTarget, gdb: &Gdb) -> Gdb {
+        let snapshot = gdb.snapshot().unwrap();
+        assert!(snapshot.no_ack_mode);
+        assert_eq!(
+            snapshot.tracked_breakpoints,
+            Some(Vec::from([TrackedBreakpoint::Sw {
+                addr: 0x10,
+                kind: 4
+            }]))
+        );
+
+        // tracking isn't enabled on the new stub, and is instead restored
+        let stub = GdbStub::new(MemConnection::new());
+        let gdb = stub
+            .restore_state_machine(target, snapshot.clone())
+            .unwrap();
+        assert_eq!(gdb.snapshot(), Some(snapshot));
+        gdb
+    }
+
+    /// Output sent by the restored stub, which must not include any acks.
+    fn output(gdb: &mut Gdb) -> Vec<alloc::string::String> {
+        let conn = match gdb {
+            GdbStubStateMachine::Idle(gdb) => gdb.borrow_conn(),
+            GdbStubStateMachine::Running(gdb) => gdb.borrow_conn(),
+            _ => panic!("unexpected state"),
+        };
+        assert!(!conn.output().contains(&b'+'));
+        take_responses(gdb)
+    }
+
+    #[test]
+    fn restore_idle() {
+        let mut target = TestTarget::new();
+        let gdb = session(&mut target, b"");
+        let mut gdb = round_trip(&mut target, &gdb);
+
+        match &mut gdb {
+            GdbStubStateMachine::Idle(gdb) => gdb.borrow_conn().inject_packet(b"m10,2").unwrap(),
+            _ => panic!("expected the stub to be idle"),
+        }
+        let mut gdb = feed(gdb, &mut target);
+        assert_eq!(output(&mut gdb), ["1011"]);
+    }
+
+    #[test]
+    fn restore_idle_with_deferred_interrupt() {
+        let mut target = TestTarget::new();
+        let gdb = match session(&mut target, &[0x03]) {
+            GdbStubStateMachine::CtrlCInterrupt(gdb) => gdb
+                .interrupt_handled(
+                    &mut target,
+                    Some(SingleThreadStopReason::Signal(Signal::SIGINT)),
+                )
+                .unwrap(),
+            _ => panic!("expected a Ctrl-C interrupt"),
+        };
+        let mut gdb = round_trip(&mut target, &gdb);
+
+        // the deferred stop reason is reported as soon as the target resumes
+        match &mut gdb {
+            GdbStubStateMachine::Idle(gdb) => gdb.borrow_conn().inject_packet(b"c").unwrap(),
+            _ => panic!("expected the stub to be idle"),
+        }
+        let mut gdb = feed(gdb, &mut target);
+        assert_eq!(output(&mut gdb), ["S02"]);
+    }
+
+    #[test]
+    fn restore_running() {
+        let mut target = TestTarget::new();
+        let gdb = session(&mut target, b"$c#63");
+        let gdb = round_trip(&mut target, &gdb);
+
+        let mut gdb = match gdb {
+            GdbStubStateMachine::Running(gdb) => gdb
+                .report_stop(&mut target, SingleThreadStopReason::DoneStep)
+                .unwrap(),
+            _ => panic!("expected the target to be running"),
+        };
+        assert_eq!(output(&mut gdb), ["S05"]);
+    }
+
+    #[test]
+    fn restore_ctrlc_interrupt() {
+        let mut target = TestTarget::new();
+        let gdb = session(&mut target, b"$c#63\x03");
+        let gdb = round_trip(&mut target, &gdb);
+
+        let mut gdb = match gdb {
+            GdbStubStateMachine::CtrlCInterrupt(gdb) => gdb
+                .interrupt_handled(
+                    &mut target,
+                    Some(SingleThreadStopReason::Signal(Signal::SIGINT)),
+                )
+                .unwrap(),
+            _ => panic!("expected a Ctrl-C interrupt"),
+        };
+        assert_eq!(output(&mut gdb), ["S02"]);
+    }
+}
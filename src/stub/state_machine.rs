@@ -36,15 +36,19 @@
 use managed::ManagedSlice;
 
 use crate::arch::Arch;
+use crate::common::Signal;
 use crate::conn::Connection;
 use crate::protocol::recv_packet::RecvPacketStateMachine;
 use crate::protocol::{Packet, ResponseWriter};
 use crate::stub::error::GdbStubError as Error;
 use crate::stub::stop_reason::IntoStopReason;
+use crate::target::ext::base::AsyncBase;
 use crate::target::Target;
 
 use super::core_impl::{FinishExecStatus, GdbStubImpl, State};
-use super::{DisconnectReason, GdbStub};
+#[cfg(feature = "alloc")]
+use super::TrackedBreakpoint;
+use super::{DisconnectReason, GdbStub, GdbStubSnapshot, SnapshotState};
 
 /// State-machine interface to `GdbStub`.
 ///
@@ -69,6 +73,33 @@ where
     Disconnected(GdbStubStateMachineInner<'a, state::Disconnected, T, C>),
 }
 
+impl<'a, T: Target, C: Connection> GdbStubStateMachine<'a, T, C> {
+    /// Capture a snapshot of the current session state, which can later be
+    /// restored via [`GdbStub::restore_state_machine`].
+    ///
+    /// Returns `None` if the GDB client has disconnected.
+    pub fn snapshot(&self) -> Option<GdbStubSnapshot<<T::Arch as Arch>::Usize>> {
+        let (inner, state) = match self {
+            GdbStubStateMachine::Idle(gdb) => (
+                &gdb.i.inner,
+                SnapshotState::Idle {
+                    deferred_ctrlc_stop_reason: gdb.state.deferred_ctrlc_stop_reason,
+                },
+            ),
+            GdbStubStateMachine::Running(gdb) => (&gdb.i.inner, SnapshotState::Running),
+            GdbStubStateMachine::CtrlCInterrupt(gdb) => (
+                &gdb.i.inner,
+                SnapshotState::CtrlCInterrupt {
+                    from_idle: gdb.state.from_idle,
+                },
+            ),
+            GdbStubStateMachine::Disconnected(_) => return None,
+        };
+
+        Some(inner.snapshot(state))
+    }
+}
+
 /// State machine typestates.
 ///
 /// The types in this module are used to parameterize instances of
@@ -106,6 +137,56 @@ pub mod state {
     pub struct Disconnected {
         pub(crate) reason: DisconnectReason,
     }
+
+    /// Marker trait implemented by all states in which the GDB client is
+    /// still connected (i.e: every state other than [`Disconnected`]).
+    pub trait Connected: sealed::Sealed {}
+
+    impl<T: Target> Connected for Idle<T> {}
+    impl Connected for Running {}
+    impl Connected for CtrlCInterrupt {}
+
+    mod sealed {
+        pub trait Sealed {
+            /// Whether the GDB client is waiting on a stop reply.
+            fn awaiting_stop_reply(&self) -> bool;
+        }
+
+        impl<T: crate::target::Target> Sealed for super::Idle<T> {
+            fn awaiting_stop_reply(&self) -> bool {
+                false
+            }
+        }
+
+        impl Sealed for super::Running {
+            fn awaiting_stop_reply(&self) -> bool {
+                true
+            }
+        }
+
+        impl Sealed for super::CtrlCInterrupt {
+            fn awaiting_stop_reply(&self) -> bool {
+                // interrupts which arrive while idle are reported on the next resume
+                !self.from_idle
+            }
+        }
+    }
+}
+
+/// Describes why the stub is ending the GDB session.
+///
+/// See [`GdbStubStateMachineInner::shutdown`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// Target exited with given status code (reported to GDB via `W`)
+    TargetExited(u8),
+    /// Target terminated with given signal (reported to GDB via `X`)
+    TargetTerminated(Signal),
+    /// The stub itself is shutting down.
+    ///
+    /// The GDB RSP has no dedicated packet for this, so no notification is
+    /// sent, and the GDB client will observe the connection being closed.
+    StubShutdown,
 }
 
 /// Internal helper macro to convert between a particular inner state into
@@ -185,6 +266,95 @@ impl<'a, S, T: Target, C: Connection> GdbStubStateMachineInner<'a, S, T, C> {
     pub fn borrow_conn(&mut self) -> &mut C {
         &mut self.i.conn
     }
+
+    /// Return the breakpoints / watchpoints currently set by the GDB client.
+    ///
+    /// Always returns an empty slice unless breakpoint tracking was enabled
+    /// via [`GdbStubBuilder::track_breakpoints`](super::GdbStubBuilder::track_breakpoints).
+    #[cfg(feature = "alloc")]
+    pub fn tracked_breakpoints(&self) -> &[TrackedBreakpoint<<T::Arch as Arch>::Usize>] {
+        self.i.inner.tracked_breakpoints()
+    }
+}
+
+impl<'a, T: Target, C: Connection> GdbStubStateMachine<'a, T, C> {
+    /// Complete any base target operation deferred by the most recently
+    /// handled packet (see [`GdbStub::run_async_target`]).
+    pub(crate) async fn complete_async_request<K>(
+        &mut self,
+        target: &mut T,
+    ) -> Result<(), Error<T::Error, C::Error>>
+    where
+        T: AsyncBase<K>,
+    {
+        let i = match self {
+            GdbStubStateMachine::Idle(gdb) => &mut gdb.i,
+            GdbStubStateMachine::Running(gdb) => &mut gdb.i,
+            GdbStubStateMachine::CtrlCInterrupt(gdb) => &mut gdb.i,
+            GdbStubStateMachine::Disconnected(gdb) => &mut gdb.i,
+        };
+        i.inner
+            .complete_async_request(target, &mut i.conn, &mut i.packet_buffer)
+            .await
+    }
+}
+
+/// Methods which can be called from any state in which the GDB client is still
+/// connected (i.e: `Idle`, `Running`, and `CtrlCInterrupt`).
+impl<'a, S: state::Connected, T: Target, C: Connection> GdbStubStateMachineInner<'a, S, T, C> {
+    /// End the debugging session from the stub side, notifying the GDB client
+    /// as appropriate, and transition into the `Disconnected` state.
+    ///
+    /// This is useful when the target goes away independently of the GDB
+    /// client (e.g: the guest powered off, or the emulator is being shut down
+    /// by its UI), regardless of whether or not the target was running.
+    ///
+    /// The GDB RSP only permits the `W` / `X` exit notifications as replies to
+    /// a resume request, so they are only sent while the client is waiting on
+    /// the target (i.e: from the `Running` state, or after a Ctrl-C interrupt
+    /// that arrived while the target was running). Otherwise, the client will
+    /// simply observe the connection being closed.
+    ///
+    /// Any deferred Ctrl-C stop reason is discarded.
+    pub fn shutdown(
+        mut self,
+        target: &mut T,
+        reason: ShutdownReason,
+    ) -> Result<GdbStubStateMachine<'a, T, C>, Error<T::Error, C::Error>> {
+        let reason = match reason {
+            ShutdownReason::TargetExited(code) => DisconnectReason::TargetExited(code),
+            ShutdownReason::TargetTerminated(sig) => DisconnectReason::TargetTerminated(sig),
+            ShutdownReason::StubShutdown => DisconnectReason::StubShutdown,
+        };
+
+        let awaiting_stop_reply = self.state.awaiting_stop_reply();
+        match reason {
+            DisconnectReason::TargetExited(code) if awaiting_stop_reply => {
+                let mut res = ResponseWriter::new(&mut self.i.conn, target.use_rle());
+                res.write_str("W")?;
+                res.write_num(code)?;
+                res.flush()?;
+            }
+            DisconnectReason::TargetTerminated(sig) if awaiting_stop_reply => {
+                let mut res = ResponseWriter::new(&mut self.i.conn, target.use_rle());
+                res.write_str("X")?;
+                res.write_num(sig as u8)?;
+                res.flush()?;
+            }
+            _ => self.i.conn.flush().map_err(Error::ConnectionWrite)?,
+        }
+
+        Ok(self.transition(state::Disconnected { reason }).into())
+    }
+
+    /// Transition into the `Disconnected` state without notifying the GDB
+    /// client (e.g: because the underlying connection has gone away).
+    pub(crate) fn connection_lost(self) -> GdbStubStateMachine<'a, T, C> {
+        self.transition(state::Disconnected {
+            reason: DisconnectReason::Disconnect,
+        })
+        .into()
+    }
 }
 
 /// Methods which can only be called from the [`GdbStubStateMachine::Idle`]
@@ -207,6 +377,27 @@ impl<'a, T: Target, C: Connection> GdbStubStateMachineInner<'a, state::Idle<T>,
         }
     }
 
+    /// Restore a previously captured session snapshot, transitioning into the
+    /// snapshot's state.
+    pub(crate) fn restore(
+        mut self,
+        snapshot: GdbStubSnapshot<<T::Arch as Arch>::Usize>,
+    ) -> GdbStubStateMachine<'a, T, C> {
+        match self.i.inner.restore(snapshot) {
+            SnapshotState::Idle {
+                deferred_ctrlc_stop_reason,
+            } => self
+                .transition(state::Idle {
+                    deferred_ctrlc_stop_reason,
+                })
+                .into(),
+            SnapshotState::Running => self.transition(state::Running {}).into(),
+            SnapshotState::CtrlCInterrupt { from_idle } => {
+                self.transition(state::CtrlCInterrupt { from_idle }).into()
+            }
+        }
+    }
+
     /// Pass a byte to the GDB stub.
     pub fn incoming_data(
         mut self,
@@ -218,6 +409,7 @@ impl<'a, T: Target, C: Connection> GdbStubStateMachineInner<'a, state::Idle<T>,
             None => return Ok(self.into()),
         };
 
+        self.i.inner.set_packet_buffer(packet_buffer);
         let packet = Packet::from_buf(target, packet_buffer).map_err(Error::PacketParse)?;
         let state = self
             .i
@@ -286,6 +478,7 @@ impl<'a, T: Target, C: Connection> GdbStubStateMachineInner<'a, state::Running,
             None => return Ok(self.into()),
         };
 
+        self.i.inner.set_packet_buffer(packet_buffer);
         let packet = Packet::from_buf(target, packet_buffer).map_err(Error::PacketParse)?;
         let state = self
             .i
@@ -367,4 +560,111 @@ impl<'a, T: Target, C: Connection> GdbStubStateMachineInner<'a, state::Disconnec
         })
         .into()
     }
+
+    /// Reuse the existing state machine instance with a new connection (e.g:
+    /// after a new GDB client connects), reentering the idle loop.
+    ///
+    /// Any state negotiated with the previous GDB client (e.g: no-ack mode,
+    /// selected threads, etc...) is reset, while any tracked breakpoints are
+    /// retained. The previous connection is dropped.
+    pub fn reconnect(
+        mut self,
+        mut conn: C,
+    ) -> Result<GdbStubStateMachine<'a, T, C>, Error<T::Error, C::Error>> {
+        conn.on_session_start().map_err(Error::ConnectionInit)?;
+
+        self.i.conn = conn;
+        self.i.recv_packet = RecvPacketStateMachine::new();
+        self.i.inner.reset_session();
+
+        Ok(self.return_to_idle())
+    }
+
+    /// Remove any breakpoints / watchpoints left behind by the GDB client from
+    /// the target.
+    ///
+    /// Does nothing unless breakpoint tracking was enabled via
+    /// [`GdbStubBuilder::track_breakpoints`](super::GdbStubBuilder::track_breakpoints).
+    #[cfg(feature = "alloc")]
+    pub fn remove_tracked_breakpoints(
+        &mut self,
+        target: &mut T,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        self.i.inner.remove_tracked_breakpoints(target)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::conn::MemConnection;
+    use crate::stub::test_util::{feed, take_responses, TestTarget};
+
+    /// Shut down a session once `input` has been processed, returning the
+    /// stub's final responses and the resulting disconnect reason.
+    fn shutdown_after(input: &[u8], reason: ShutdownReason) -> (Vec<String>, DisconnectReason) {
+        let mut target = TestTarget::new();
+        let mut conn = MemConnection::new();
+        conn.inject(input).unwrap();
+
+        let stub = GdbStub::new(conn);
+        let mut gdb = feed(stub.run_state_machine(&mut target).unwrap(), &mut target);
+        take_responses(&mut gdb);
+
+        let mut gdb = match gdb {
+            GdbStubStateMachine::Idle(gdb) => gdb.shutdown(&mut target, reason),
+            GdbStubStateMachine::Running(gdb) => gdb.shutdown(&mut target, reason),
+            GdbStubStateMachine::CtrlCInterrupt(gdb) => gdb.shutdown(&mut target, reason),
+            GdbStubStateMachine::Disconnected(_) => panic!("already disconnected"),
+        }
+        .unwrap();
+
+        let responses = take_responses(&mut gdb);
+        match gdb {
+            GdbStubStateMachine::Disconnected(gdb) => (responses, gdb.get_reason()),
+            _ => panic!("expected the stub to disconnect"),
+        }
+    }
+
+    const RESUME: &[u8] = b"$c#63";
+
+    #[test]
+    fn shutdown_from_idle() {
+        let (responses, reason) = shutdown_after(b"", ShutdownReason::TargetExited(3));
+        assert!(responses.is_empty());
+        assert_eq!(reason, DisconnectReason::TargetExited(3));
+    }
+
+    #[test]
+    fn shutdown_from_running() {
+        let (responses, reason) = shutdown_after(RESUME, ShutdownReason::TargetExited(3));
+        assert_eq!(responses, ["W03"]);
+        assert_eq!(reason, DisconnectReason::TargetExited(3));
+
+        let (responses, reason) =
+            shutdown_after(RESUME, ShutdownReason::TargetTerminated(Signal::SIGKILL));
+        assert_eq!(responses, ["X09"]);
+        assert_eq!(reason, DisconnectReason::TargetTerminated(Signal::SIGKILL));
+
+        let (responses, reason) = shutdown_after(RESUME, ShutdownReason::StubShutdown);
+        assert!(responses.is_empty());
+        assert_eq!(reason, DisconnectReason::StubShutdown);
+    }
+
+    #[test]
+    fn shutdown_from_ctrlc_interrupt() {
+        let mut input = RESUME.to_vec();
+        input.push(0x03);
+        let (responses, reason) = shutdown_after(&input, ShutdownReason::TargetExited(3));
+        assert_eq!(responses, ["W03"]);
+        assert_eq!(reason, DisconnectReason::TargetExited(3));
+
+        // the client isn't waiting on the target
+        let (responses, reason) = shutdown_after(&[0x03], ShutdownReason::TargetExited(3));
+        assert!(responses.is_empty());
+        assert_eq!(reason, DisconnectReason::TargetExited(3));
+    }
 }
@@ -26,6 +26,7 @@ use crate::target::Target;
 ///
 /// [`HwBreakpoint`]: crate::target::ext::breakpoints::HwBreakpoint
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum BaseStopReason<Tid, U> {
     /// Completed the single-step request.
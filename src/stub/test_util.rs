@@ -0,0 +1,497 @@
+//! Shared fixtures for driving a [`GdbStub`](super::GdbStub) in unit tests.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::task::{Context, Poll, Waker};
+
+use crate::arch::{Arch, Registers, SingleStepGdbBehavior};
+use crate::common::Signal;
+use crate::conn::{AsyncConnection, ConnectionExt, MemConnection, MemConnectionError};
+use crate::stub::state_machine::GdbStubStateMachine;
+use crate::stub::SingleThreadStopReason;
+use crate::target::ext::base::singlethread::{
+    AsyncSingleThreadBase, SingleThreadBase, SingleThreadResume, SingleThreadResumeOps,
+    SingleThreadSingleStep, SingleThreadSingleStepOps,
+};
+use crate::target::ext::base::BaseOps;
+use crate::target::ext::breakpoints::{Breakpoints, BreakpointsOps, SwBreakpoint, SwBreakpointOps};
+use crate::target::{Target, TargetError, TargetResult};
+
+/// A minimal 32-bit architecture.
+pub(crate) enum TestArch {}
+
+impl Arch for TestArch {
+    type Usize = u32;
+    type Registers = TestRegs;
+    type BreakpointKind = usize;
+    type RegId = ();
+
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Optional
+    }
+}
+
+/// Two general purpose registers and a PC, serialized as little-endian words.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct TestRegs {
+    pub r: [u32; 2],
+    pub pc: u32,
+}
+
+impl Registers for TestRegs {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> u32 {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        for reg in self.r.iter().chain(core::iter::once(&self.pc)) {
+            for b in &reg.to_le_bytes() {
+                write_byte(Some(*b))
+            }
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        if bytes.len() != 12 {
+            return Err(());
+        }
+
+        let mut regs = bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+        self.r = [regs.next().unwrap(), regs.next().unwrap()];
+        self.pc = regs.next().unwrap();
+        Ok(())
+    }
+}
+
+/// Size of [`TestTarget`]'s memory, which is mapped at address 0.
+pub(crate) const TEST_MEM_LEN: usize = 0x100;
+
+/// A single threaded target which records how it was accessed.
+pub(crate) struct TestTarget {
+    pub regs: TestRegs,
+    pub mem: Vec<u8>,
+    pub breakpoints: Vec<u32>,
+    /// Number of calls to the synchronous base methods.
+    pub sync_calls: usize,
+    /// Number of calls to the `AsyncSingleThreadBase` methods.
+    pub async_calls: usize,
+    /// Signal passed to the most recent resume / step.
+    pub resumed: Option<Option<Signal>>,
+    /// Whether the target is currently executing.
+    pub running: bool,
+    /// State used by [`TestPollEventLoop`].
+    #[cfg(all(feature = "std", unix))]
+    pub poll: TestPollState,
+}
+
+/// [`TestTarget`] state used by [`TestPollEventLoop`].
+#[cfg(all(feature = "std", unix))]
+pub(crate) struct TestPollState {
+    pub wake: crate::stub::run_poll::WakeEvent,
+    pub timeout: Option<std::time::Duration>,
+    /// Number of calls to `check_stop_reason`.
+    pub stop_checks: usize,
+}
+
+impl TestTarget {
+    pub fn new() -> TestTarget {
+        TestTarget {
+            regs: TestRegs::default(),
+            mem: (0..TEST_MEM_LEN).map(|i| i as u8).collect(),
+            breakpoints: Vec::new(),
+            sync_calls: 0,
+            async_calls: 0,
+            resumed: None,
+            running: false,
+            #[cfg(all(feature = "std", unix))]
+            poll: TestPollState {
+                wake: crate::stub::run_poll::WakeEvent::new().unwrap(),
+                timeout: None,
+                stop_checks: 0,
+            },
+        }
+    }
+
+    fn mem_range(
+        &self,
+        start_addr: u32,
+        len: usize,
+    ) -> TargetResult<core::ops::Range<usize>, Self> {
+        let start = start_addr as usize;
+        match start.checked_add(len) {
+            Some(end) if end <= self.mem.len() => Ok(start..end),
+            // EFAULT
+            _ => Err(TargetError::Errno(14)),
+        }
+    }
+}
+
+impl Target for TestTarget {
+    type Arch = TestArch;
+    type Error = &'static str;
+
+    fn base_ops(&mut self) -> BaseOps<'_, TestArch, &'static str> {
+        BaseOps::SingleThread(self)
+    }
+
+    fn support_breakpoints(&mut self) -> Option<BreakpointsOps<'_, Self>> {
+        Some(self)
+    }
+
+    // keep responses easy to match against
+    fn use_rle(&self) -> bool {
+        false
+    }
+}
+
+impl SingleThreadBase for TestTarget {
+    fn read_registers(&mut self, regs: &mut TestRegs) -> TargetResult<(), Self> {
+        self.sync_calls += 1;
+        *regs = self.regs.clone();
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &TestRegs) -> TargetResult<(), Self> {
+        self.sync_calls += 1;
+        self.regs = regs.clone();
+        Ok(())
+    }
+
+    fn read_addrs(&mut self, start_addr: u32, data: &mut [u8]) -> TargetResult<(), Self> {
+        self.sync_calls += 1;
+        let range = self.mem_range(start_addr, data.len())?;
+        data.copy_from_slice(&self.mem[range]);
+        Ok(())
+    }
+
+    fn write_addrs(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
+        self.sync_calls += 1;
+        let range = self.mem_range(start_addr, data.len())?;
+        self.mem[range].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn support_resume(&mut self) -> Option<SingleThreadResumeOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SingleThreadResume for TestTarget {
+    fn resume(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.sync_calls += 1;
+        self.resumed = Some(signal);
+        self.running = true;
+        Ok(())
+    }
+
+    fn support_single_step(&mut self) -> Option<SingleThreadSingleStepOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SingleThreadSingleStep for TestTarget {
+    fn step(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.sync_calls += 1;
+        self.resumed = Some(signal);
+        self.running = true;
+        Ok(())
+    }
+}
+
+impl Breakpoints for TestTarget {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SwBreakpoint for TestTarget {
+    fn add_sw_breakpoint(&mut self, addr: u32, _kind: usize) -> TargetResult<bool, Self> {
+        self.breakpoints.push(addr);
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(&mut self, addr: u32, _kind: usize) -> TargetResult<bool, Self> {
+        match self.breakpoints.iter().position(|a| *a == addr) {
+            Some(i) => {
+                self.breakpoints.remove(i);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl AsyncSingleThreadBase for TestTarget {
+    async fn read_registers(&mut self, regs: &mut TestRegs) -> TargetResult<(), Self> {
+        self.async_calls += 1;
+        *regs = self.regs.clone();
+        Ok(())
+    }
+
+    async fn write_registers(&mut self, regs: &TestRegs) -> TargetResult<(), Self> {
+        self.async_calls += 1;
+        self.regs = regs.clone();
+        Ok(())
+    }
+
+    async fn read_addrs(&mut self, start_addr: u32, data: &mut [u8]) -> TargetResult<(), Self> {
+        self.async_calls += 1;
+        let range = self.mem_range(start_addr, data.len())?;
+        data.copy_from_slice(&self.mem[range]);
+        Ok(())
+    }
+
+    async fn write_addrs(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
+        self.async_calls += 1;
+        let range = self.mem_range(start_addr, data.len())?;
+        self.mem[range].copy_from_slice(data);
+        Ok(())
+    }
+
+    async fn resume(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.async_calls += 1;
+        self.resumed = Some(signal);
+        self.running = true;
+        Ok(())
+    }
+
+    async fn step(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.async_calls += 1;
+        self.resumed = Some(signal);
+        self.running = true;
+        Ok(())
+    }
+}
+
+/// [`AsyncConnection`] over an in-memory [`MemConnection`], which never
+/// actually needs to wait.
+pub(crate) struct AsyncMemConnection<'a>(pub &'a mut MemConnection<'static>);
+
+impl AsyncConnection for AsyncMemConnection<'_> {
+    type Error = MemConnectionError;
+
+    async fn read(&mut self) -> Result<u8, Self::Error> {
+        self.0.read()
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        crate::conn::Connection::write_all(self.0, buf)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        crate::conn::Connection::flush(self.0)
+    }
+}
+
+/// Event loop which reports a `SIGTRAP` whenever the target is waited on while
+/// (non Ctrl-C) input is still pending, and otherwise reads the next byte from
+/// the connection.
+///
+/// Ctrl-C interrupts are passed through, and once the connection has run dry
+/// the resulting read error simulates the client going away while the target
+/// is running.
+pub(crate) enum TestEventLoop {}
+
+impl crate::stub::run_blocking::BlockingEventLoop for TestEventLoop {
+    type Target = TestTarget;
+    type Connection = MemConnection<'static>;
+    type StopReason = SingleThreadStopReason<u32>;
+
+    fn wait_for_stop_reason(
+        target: &mut TestTarget,
+        conn: &mut MemConnection<'static>,
+    ) -> Result<
+        crate::stub::run_blocking::Event<SingleThreadStopReason<u32>>,
+        crate::stub::run_blocking::WaitForStopReasonError<&'static str, MemConnectionError>,
+    > {
+        use crate::stub::run_blocking::{Event, WaitForStopReasonError};
+
+        // the client only sends further packets once the target has stopped,
+        // so only report a stop while there's still something left to send
+        match conn.peek().map_err(WaitForStopReasonError::Connection)? {
+            Some(0x03) | None => {
+                let byte = ConnectionExt::read(conn).map_err(WaitForStopReasonError::Connection)?;
+                Ok(Event::IncomingData(byte))
+            }
+            Some(_) => {
+                target.running = false;
+                Ok(Event::TargetStopped(SingleThreadStopReason::Signal(
+                    Signal::SIGTRAP,
+                )))
+            }
+        }
+    }
+
+    fn on_interrupt(
+        target: &mut TestTarget,
+    ) -> Result<Option<SingleThreadStopReason<u32>>, &'static str> {
+        target.running = false;
+        Ok(Some(SingleThreadStopReason::Signal(Signal::SIGINT)))
+    }
+}
+
+/// Async counterpart to [`TestEventLoop`].
+pub(crate) struct TestAsyncEventLoop<'a>(core::marker::PhantomData<&'a ()>);
+
+impl<'a> crate::stub::run_async::AsyncEventLoop for TestAsyncEventLoop<'a> {
+    type Target = TestTarget;
+    type Connection = AsyncMemConnection<'a>;
+    type StopReason = SingleThreadStopReason<u32>;
+
+    async fn wait_for_stop_reason(
+        target: &mut TestTarget,
+        conn: &mut AsyncMemConnection<'a>,
+    ) -> Result<
+        crate::stub::run_async::Event<SingleThreadStopReason<u32>>,
+        crate::stub::run_async::WaitForStopReasonError<&'static str, MemConnectionError>,
+    > {
+        use crate::stub::run_async::{Event, WaitForStopReasonError};
+
+        // the client only sends further packets once the target has stopped,
+        // so only report a stop while there's still something left to send
+        match conn.0.peek().map_err(WaitForStopReasonError::Connection)? {
+            Some(0x03) | None => {
+                let byte = conn.0.read().map_err(WaitForStopReasonError::Connection)?;
+                Ok(Event::IncomingData(byte))
+            }
+            Some(_) => {
+                target.running = false;
+                Ok(Event::TargetStopped(SingleThreadStopReason::Signal(
+                    Signal::SIGTRAP,
+                )))
+            }
+        }
+    }
+
+    async fn on_interrupt(
+        target: &mut TestTarget,
+    ) -> Result<Option<SingleThreadStopReason<u32>>, &'static str> {
+        target.running = false;
+        Ok(Some(SingleThreadStopReason::Signal(Signal::SIGINT)))
+    }
+}
+
+/// [`PollEventLoop`](crate::stub::run_poll::PollEventLoop) over a
+/// `UnixStream`, which reports a `SIGTRAP` whenever the stop reason is checked
+/// while the target is running.
+#[cfg(all(feature = "std", unix))]
+pub(crate) enum TestPollEventLoop {}
+
+#[cfg(all(feature = "std", unix))]
+impl crate::stub::run_poll::PollEventLoop for TestPollEventLoop {
+    type Target = TestTarget;
+    type Connection = std::os::unix::net::UnixStream;
+    type StopReason = SingleThreadStopReason<u32>;
+
+    fn wake_event(target: &TestTarget) -> &crate::stub::run_poll::WakeEvent {
+        &target.poll.wake
+    }
+
+    fn timeout(target: &mut TestTarget) -> Option<std::time::Duration> {
+        target.poll.timeout
+    }
+
+    fn check_stop_reason(
+        target: &mut TestTarget,
+    ) -> Result<Option<SingleThreadStopReason<u32>>, &'static str> {
+        target.poll.stop_checks += 1;
+        if !target.running {
+            return Ok(None);
+        }
+        target.running = false;
+        Ok(Some(SingleThreadStopReason::Signal(Signal::SIGTRAP)))
+    }
+
+    fn on_interrupt(
+        target: &mut TestTarget,
+    ) -> Result<Option<SingleThreadStopReason<u32>>, &'static str> {
+        target.running = false;
+        Ok(Some(SingleThreadStopReason::Signal(Signal::SIGINT)))
+    }
+}
+
+/// Feed any pending input on the stub's connection to the state machine,
+/// stopping early if it enters a state which doesn't accept incoming data.
+pub(crate) fn feed<'a, T: Target>(
+    mut gdb: GdbStubStateMachine<'a, T, MemConnection<'static>>,
+    target: &mut T,
+) -> GdbStubStateMachine<'a, T, MemConnection<'static>>
+where
+    T::Error: core::fmt::Debug,
+{
+    loop {
+        gdb = match gdb {
+            GdbStubStateMachine::Idle(mut gdb) => match gdb.borrow_conn().peek().unwrap() {
+                Some(_) => {
+                    let byte = ConnectionExt::read(gdb.borrow_conn()).unwrap();
+                    gdb.incoming_data(target, byte).unwrap()
+                }
+                None => return gdb.into(),
+            },
+            GdbStubStateMachine::Running(mut gdb) => match gdb.borrow_conn().peek().unwrap() {
+                Some(_) => {
+                    let byte = ConnectionExt::read(gdb.borrow_conn()).unwrap();
+                    gdb.incoming_data(target, byte).unwrap()
+                }
+                None => return gdb.into(),
+            },
+            gdb => return gdb,
+        }
+    }
+}
+
+/// Take the payloads of all responses the stub has sent so far.
+pub(crate) fn take_responses<T: Target>(
+    gdb: &mut GdbStubStateMachine<'_, T, MemConnection<'static>>,
+) -> Vec<String> {
+    let conn = match gdb {
+        GdbStubStateMachine::Idle(gdb) => gdb.borrow_conn(),
+        GdbStubStateMachine::Running(gdb) => gdb.borrow_conn(),
+        GdbStubStateMachine::CtrlCInterrupt(gdb) => gdb.borrow_conn(),
+        GdbStubStateMachine::Disconnected(gdb) => gdb.borrow_conn(),
+    };
+    let responses = packets(conn.output());
+    conn.clear_output();
+    responses
+}
+
+/// Minimal executor, sufficient for futures which never return `Pending`
+/// for long (i.e: those driven by in-memory connections).
+pub(crate) fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = core::pin::pin!(fut);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+/// Split the stub's output into packet payloads, skipping over any acks and
+/// checksums.
+pub(crate) fn packets(output: &[u8]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut bytes = output.iter();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'$' => {
+                let payload = bytes
+                    .by_ref()
+                    .take_while(|b| **b != b'#')
+                    .copied()
+                    .collect();
+                packets.push(String::from_utf8(payload).unwrap());
+                // checksum
+                bytes.nth(1);
+            }
+            b'+' => {}
+            b => panic!("unexpected byte in stub output: {:?}", *b as char),
+        }
+    }
+    packets
+}
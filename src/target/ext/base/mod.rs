@@ -0,0 +1,185 @@
+//! Base operations required to debug most targets (e.g: read/write
+//! memory/registers, step/resume, etc...)
+//!
+//! It is **highly recommended** that single threaded targets implement the
+//! simplified `singlethread` API, as `gdbstub` includes optimized
+//! implementations of certain internal routines when operating in single
+//! threaded mode.
+
+use crate::arch::Arch;
+use crate::common::{Signal, Tid};
+use crate::target::{Target, TargetResult};
+
+pub mod multithread;
+pub mod reverse_exec;
+pub mod single_register_access;
+pub mod singlethread;
+
+/// Base required operations for single/multi threaded targets.
+pub enum BaseOps<'a, A, E> {
+    /// Single-threaded target
+    SingleThread(&'a mut dyn singlethread::SingleThreadBase<Arch = A, Error = E>),
+    /// Multi-threaded target
+    MultiThread(&'a mut dyn multithread::MultiThreadBase<Arch = A, Error = E>),
+}
+
+pub(crate) enum ResumeOps<'a, A, E> {
+    /// Single-threaded target
+    SingleThread(&'a mut dyn singlethread::SingleThreadResume<Arch = A, Error = E>),
+    /// Multi-threaded target
+    MultiThread(&'a mut dyn multithread::MultiThreadResume<Arch = A, Error = E>),
+}
+
+impl<'a, A: Arch, E> BaseOps<'a, A, E> {
+    #[inline(always)]
+    pub(crate) fn resume_ops(self) -> Option<ResumeOps<'a, A, E>> {
+        let ret = match self {
+            BaseOps::SingleThread(ops) => ResumeOps::SingleThread(ops.support_resume()?),
+            BaseOps::MultiThread(ops) => ResumeOps::MultiThread(ops.support_resume()?),
+        };
+        Some(ret)
+    }
+}
+
+/// Marker types used to select between the single and multi threaded async
+/// base IDETs. See [`AsyncBase`].
+pub mod async_kind {
+    /// Selects [`AsyncSingleThreadBase`](super::singlethread::AsyncSingleThreadBase).
+    pub enum SingleThread {}
+    /// Selects [`AsyncMultiThreadBase`](super::multithread::AsyncMultiThreadBase).
+    pub enum MultiThread {}
+}
+
+/// Unified interface over [`AsyncSingleThreadBase`] and
+/// [`AsyncMultiThreadBase`], used by
+/// [`GdbStub::run_async_target`](crate::stub::GdbStub::run_async_target).
+///
+/// This trait is automatically implemented for all targets which implement
+/// one of the above traits, and should not be implemented manually. `K` is
+/// one of the marker types in [`async_kind`], and can be inferred.
+///
+/// [`AsyncSingleThreadBase`]: singlethread::AsyncSingleThreadBase
+/// [`AsyncMultiThreadBase`]: multithread::AsyncMultiThreadBase
+#[allow(async_fn_in_trait)]
+pub trait AsyncBase<K>: Target {
+    #[doc(hidden)]
+    async fn async_read_registers(
+        &mut self,
+        regs: &mut <Self::Arch as Arch>::Registers,
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+    #[doc(hidden)]
+    async fn async_write_registers(
+        &mut self,
+        regs: &<Self::Arch as Arch>::Registers,
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+    #[doc(hidden)]
+    async fn async_read_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &mut [u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+    #[doc(hidden)]
+    async fn async_write_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &[u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+    #[doc(hidden)]
+    async fn async_resume(&mut self, signal: Option<Signal>) -> Result<(), Self::Error>;
+    #[doc(hidden)]
+    async fn async_step(&mut self, signal: Option<Signal>) -> Result<(), Self::Error>;
+}
+
+impl<T: singlethread::AsyncSingleThreadBase> AsyncBase<async_kind::SingleThread> for T {
+    async fn async_read_registers(
+        &mut self,
+        regs: &mut <Self::Arch as Arch>::Registers,
+        _tid: Tid,
+    ) -> TargetResult<(), Self> {
+        singlethread::AsyncSingleThreadBase::read_registers(self, regs).await
+    }
+
+    async fn async_write_registers(
+        &mut self,
+        regs: &<Self::Arch as Arch>::Registers,
+        _tid: Tid,
+    ) -> TargetResult<(), Self> {
+        singlethread::AsyncSingleThreadBase::write_registers(self, regs).await
+    }
+
+    async fn async_read_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &mut [u8],
+        _tid: Tid,
+    ) -> TargetResult<(), Self> {
+        singlethread::AsyncSingleThreadBase::read_addrs(self, start_addr, data).await
+    }
+
+    async fn async_write_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &[u8],
+        _tid: Tid,
+    ) -> TargetResult<(), Self> {
+        singlethread::AsyncSingleThreadBase::write_addrs(self, start_addr, data).await
+    }
+
+    async fn async_resume(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        singlethread::AsyncSingleThreadBase::resume(self, signal).await
+    }
+
+    async fn async_step(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        singlethread::AsyncSingleThreadBase::step(self, signal).await
+    }
+}
+
+impl<T: multithread::AsyncMultiThreadBase> AsyncBase<async_kind::MultiThread> for T {
+    async fn async_read_registers(
+        &mut self,
+        regs: &mut <Self::Arch as Arch>::Registers,
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        multithread::AsyncMultiThreadBase::read_registers(self, regs, tid).await
+    }
+
+    async fn async_write_registers(
+        &mut self,
+        regs: &<Self::Arch as Arch>::Registers,
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        multithread::AsyncMultiThreadBase::write_registers(self, regs, tid).await
+    }
+
+    async fn async_read_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &mut [u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        multithread::AsyncMultiThreadBase::read_addrs(self, start_addr, data, tid).await
+    }
+
+    async fn async_write_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &[u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        multithread::AsyncMultiThreadBase::write_addrs(self, start_addr, data, tid).await
+    }
+
+    async fn async_resume(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        // per-thread signals are specified via `set_resume_action_XXX`
+        multithread::AsyncMultiThreadBase::resume(self).await
+    }
+
+    async fn async_step(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        // multi threaded targets are stepped via `set_resume_action_step`
+        multithread::AsyncMultiThreadBase::resume(self).await
+    }
+}
@@ -0,0 +1,655 @@
+//! Run the `GdbStub` on a dedicated thread, communicating with the target
+//! over channels.
+//!
+//! Integrating `gdbstub` into an existing emulator can be tricky, as `GdbStub`
+//! requires exclusive `&mut` access to the target for the entire duration of
+//! the debugging session, which may conflict with the emulator's own main loop
+//! (e.g: a frame-paced render loop).
+//!
+//! This module splits the target in two:
+//!
+//! - A [`TargetProxy`], which lives on the stub's thread, and implements
+//!   [`Target`] (plus all supported IDETs) by forwarding each request to the
+//!   emulator over a channel, and blocking until the emulator responds.
+//! - A [`ProxyServer`], which lives on the emulator's thread. At safe points in
+//!   the emulator's main loop (e.g: between frames), the emulator calls
+//!   [`ServeProxy::serve_proxy_requests`] to service any pending requests
+//!   using its own `Target` implementation.
+//!
+//! When the target stops (e.g: hits a breakpoint), the emulator reports the
+//! stop reason via [`ProxyServer::report_stop`], which wakes up the stub thread
+//! without any polling.
+//!
+//! The stub thread is driven using [`GdbStub::run_poll`] with the
+//! [`ProxyEventLoop`] event loop:
+//!
+//! ```rust,ignore
+//! let (mut proxy, server) = gdbstub::threaded::channel(&mut emu)?;
+//!
+//! std::thread::spawn(move || {
+//!     let gdb = GdbStub::new(conn);
+//!     gdb.run_poll::<ProxyEventLoop<_, TcpStream>>(&mut proxy)
+//! });
+//!
+//! loop {
+//!     emu.run_frame(&server);
+//!     emu.serve_proxy_requests(&server)?;
+//! }
+//! ```
+//!
+//! The set of protocol extensions the proxy reports to the GDB client is
+//! determined by the emulator's `Target` implementation at the time
+//! [`channel`] is called. The following IDETs are forwarded:
+//!
+//! - Base operations (single and multi threaded), including single register
+//!   access and cursor-based thread enumeration
+//! - Resuming, single stepping, range stepping, reverse execution, and resuming
+//!   at a specific address
+//! - Software / hardware breakpoints and hardware watchpoints
+//! - Extended mode (including all of its configuration sub-extensions)
+//! - Host I/O
+//! - Catching syscalls
+//! - Monitor commands
+//! - Section offsets, memory maps, target description XML overrides, exec file
+//!   and auxv queries
+//!
+//! The proxy presents itself to the GDB client as a single threaded target if
+//! (and only if) the emulator's target is single threaded.
+//!
+//! [`GdbStub::run_poll`]: crate::stub::GdbStub::run_poll
+
+use core::convert::TryFrom;
+use std::sync::mpsc;
+
+use crate::arch::{Arch, SingleStepGdbBehavior};
+use crate::common::{Pid, Signal, Tid};
+use crate::stub::run_poll::WakeEvent;
+use crate::stub::MultiThreadStopReason;
+use crate::target::ext::base::BaseOps;
+use crate::target::ext::breakpoints::WatchKind;
+use crate::target::ext::host_io::{
+    FsKind, HostIoErrno, HostIoOpenFlags, HostIoOpenMode, HostIoStat,
+};
+use crate::target::ext::section_offsets::Offsets;
+use crate::target::{Target, TargetError};
+
+mod proxy;
+mod serve;
+
+pub use proxy::{ProxyError, ProxyEventLoop, TargetProxy};
+pub use serve::{ProxyServer, ServeError, ServeProxy};
+
+/// Create a [`TargetProxy`] / [`ProxyServer`] pair for the given target.
+///
+/// The proxy mirrors the target's set of supported protocol extensions (and
+/// guard rail / protocol preferences) at the time of this call.
+pub fn channel<T: Target>(
+    target: &mut T,
+) -> std::io::Result<(TargetProxy<T::Arch>, ProxyServer<T::Arch>)> {
+    let (req_tx, req_rx) = mpsc::channel();
+    let (res_tx, res_rx) = mpsc::channel();
+    let (stop_tx, stop_rx) = mpsc::channel();
+    let wake = WakeEvent::new()?;
+
+    let server = ProxyServer::new(req_rx, res_tx, stop_tx, wake.handle());
+    let proxy = TargetProxy::new(Capabilities::of(target), req_tx, res_rx, stop_rx, wake);
+    Ok((proxy, server))
+}
+
+/// The set of protocol extensions supported by the proxied target.
+#[derive(Debug, Clone, Copy)]
+struct Capabilities {
+    multithread: bool,
+    single_register_access: bool,
+    list_cursor: bool,
+    resume: Option<ResumeCapabilities>,
+    sw_breakpoint: bool,
+    hw_breakpoint: bool,
+    hw_watchpoint: bool,
+    extended_mode: Option<ExtendedModeCapabilities>,
+    host_io: Option<HostIoCapabilities>,
+    catch_syscalls: bool,
+    monitor_cmd: bool,
+    section_offsets: bool,
+    memory_map: bool,
+    target_description_xml_override: bool,
+    exec_file: bool,
+    auxv: bool,
+
+    guard_rail_implicit_sw_breakpoints: bool,
+    guard_rail_single_step_gdb_behavior: SingleStepGdbBehavior,
+    use_x_upcase_packet: bool,
+    use_resume_stub: bool,
+    use_rle: bool,
+    use_target_description_xml: bool,
+}
+
+/// Sub-extensions of the proxied target's resume extension.
+#[derive(Debug, Clone, Copy, Default)]
+struct ResumeCapabilities {
+    single_step: bool,
+    range_step: bool,
+    reverse_step: bool,
+    reverse_cont: bool,
+    resume_at_addr: bool,
+}
+
+/// Sub-extensions of the proxied target's extended mode extension.
+#[derive(Debug, Clone, Copy, Default)]
+struct ExtendedModeCapabilities {
+    configure_aslr: bool,
+    configure_env: bool,
+    configure_startup_shell: bool,
+    configure_working_dir: bool,
+}
+
+/// Sub-extensions of the proxied target's host I/O extension.
+#[derive(Debug, Clone, Copy, Default)]
+struct HostIoCapabilities {
+    open: bool,
+    close: bool,
+    pread: bool,
+    pwrite: bool,
+    fstat: bool,
+    unlink: bool,
+    readlink: bool,
+    setfs: bool,
+}
+
+impl Capabilities {
+    fn of<T: Target>(target: &mut T) -> Capabilities {
+        let (multithread, single_register_access, list_cursor, resume) = match target.base_ops() {
+            BaseOps::SingleThread(ops) => {
+                let single_register_access = ops.support_single_register_access().is_some();
+                let resume = ops.support_resume().map(|ops| ResumeCapabilities {
+                    single_step: ops.support_single_step().is_some(),
+                    range_step: ops.support_range_step().is_some(),
+                    reverse_step: ops.support_reverse_step().is_some(),
+                    reverse_cont: ops.support_reverse_cont().is_some(),
+                    resume_at_addr: ops.support_resume_at_addr().is_some(),
+                });
+                (false, single_register_access, false, resume)
+            }
+            BaseOps::MultiThread(ops) => {
+                let single_register_access = ops.support_single_register_access().is_some();
+                let list_cursor = ops.support_list_cursor().is_some();
+                let resume = ops.support_resume().map(|ops| ResumeCapabilities {
+                    single_step: ops.support_single_step().is_some(),
+                    range_step: ops.support_range_step().is_some(),
+                    reverse_step: ops.support_reverse_step().is_some(),
+                    reverse_cont: ops.support_reverse_cont().is_some(),
+                    resume_at_addr: ops.support_resume_at_addr().is_some(),
+                });
+                (true, single_register_access, list_cursor, resume)
+            }
+        };
+
+        let (sw_breakpoint, hw_breakpoint, hw_watchpoint) = match target.support_breakpoints() {
+            None => (false, false, false),
+            Some(ops) => (
+                ops.support_sw_breakpoint().is_some(),
+                ops.support_hw_breakpoint().is_some(),
+                ops.support_hw_watchpoint().is_some(),
+            ),
+        };
+
+        let extended_mode = target
+            .support_extended_mode()
+            .map(|ops| ExtendedModeCapabilities {
+                configure_aslr: ops.support_configure_aslr().is_some(),
+                configure_env: ops.support_configure_env().is_some(),
+                configure_startup_shell: ops.support_configure_startup_shell().is_some(),
+                configure_working_dir: ops.support_configure_working_dir().is_some(),
+            });
+
+        let host_io = target.support_host_io().map(|ops| HostIoCapabilities {
+            open: ops.support_open().is_some(),
+            close: ops.support_close().is_some(),
+            pread: ops.support_pread().is_some(),
+            pwrite: ops.support_pwrite().is_some(),
+            fstat: ops.support_fstat().is_some(),
+            unlink: ops.support_unlink().is_some(),
+            readlink: ops.support_readlink().is_some(),
+            setfs: ops.support_setfs().is_some(),
+        });
+
+        Capabilities {
+            multithread,
+            single_register_access,
+            list_cursor,
+            resume,
+            sw_breakpoint,
+            hw_breakpoint,
+            hw_watchpoint,
+            extended_mode,
+            host_io,
+            catch_syscalls: target.support_catch_syscalls().is_some(),
+            monitor_cmd: target.support_monitor_cmd().is_some(),
+            section_offsets: target.support_section_offsets().is_some(),
+            memory_map: target.support_memory_map().is_some(),
+            target_description_xml_override: target
+                .support_target_description_xml_override()
+                .is_some(),
+            exec_file: target.support_exec_file().is_some(),
+            auxv: target.support_auxv().is_some(),
+
+            guard_rail_implicit_sw_breakpoints: target.guard_rail_implicit_sw_breakpoints(),
+            guard_rail_single_step_gdb_behavior: target.guard_rail_single_step_gdb_behavior(),
+            use_x_upcase_packet: target.use_x_upcase_packet(),
+            use_resume_stub: target.use_resume_stub(),
+            use_rle: target.use_rle(),
+            use_target_description_xml: target.use_target_description_xml(),
+        }
+    }
+}
+
+/// A resume action set on a particular thread.
+#[derive(Debug)]
+enum ResumeAction<U> {
+    Continue(Option<Signal>),
+    Step(Option<Signal>),
+    RangeStep(U, U),
+}
+
+/// Sources of `qXfer`-style "read a chunk of a document" requests.
+#[derive(Debug)]
+enum Document {
+    MemoryMap,
+    TargetDescriptionXml(Vec<u8>),
+    ExecFile(Option<Pid>),
+    Auxv,
+}
+
+/// A request sent from the [`TargetProxy`] to the [`ProxyServer`].
+enum Request<A: Arch> {
+    ReadRegisters(Tid),
+    WriteRegisters(A::Registers, Tid),
+    ReadRegister(Tid, A::RegId, usize),
+    WriteRegister(Tid, A::RegId, Vec<u8>),
+    ReadAddrs(A::Usize, usize, Tid),
+    WriteAddrs(A::Usize, Vec<u8>, Tid),
+    ListThreads,
+    ListThreadsFrom(usize),
+    Resume(Vec<(Tid, ResumeAction<A::Usize>)>),
+    ReverseCont,
+    ReverseStep(Tid),
+    SetResumeAddr(Tid, A::Usize),
+    Interrupt,
+    SwBreakpoint(bool, A::Usize, A::BreakpointKind),
+    HwBreakpoint(bool, A::Usize, A::BreakpointKind),
+    HwWatchpoint(bool, A::Usize, A::Usize, WatchKind),
+    ExtendedMode(ExtendedModeRequest),
+    HostIo(HostIoRequest<A::Usize>),
+    EnableCatchSyscalls(Option<Vec<A::Usize>>),
+    DisableCatchSyscalls,
+    MonitorCmd(Vec<u8>),
+    SectionOffsets,
+    ReadDocument(Document, u64, usize),
+}
+
+/// Extended mode requests.
+#[derive(Debug)]
+enum ExtendedModeRequest {
+    Run(Option<Vec<u8>>, Vec<Vec<u8>>),
+    Attach(Pid),
+    QueryIfAttached(Pid),
+    Kill(Option<Pid>),
+    Restart,
+    OnStart,
+    ConfigureAslr(bool),
+    SetEnv(Vec<u8>, Option<Vec<u8>>),
+    RemoveEnv(Vec<u8>),
+    ResetEnv,
+    ConfigureStartupShell(bool),
+    ConfigureWorkingDir(Option<Vec<u8>>),
+}
+
+/// Host I/O requests.
+#[derive(Debug)]
+enum HostIoRequest<U> {
+    Open(Vec<u8>, HostIoOpenFlags, HostIoOpenMode),
+    Close(u32),
+    Pread(u32, usize, u64),
+    Pwrite(u32, U, Vec<u8>),
+    Fstat(u32),
+    Unlink(Vec<u8>),
+    Readlink(Vec<u8>, usize),
+    Setfs(FsKind),
+}
+
+/// A successful reply to a [`Request`].
+enum Reply<A: Arch> {
+    Unit,
+    Bool(bool),
+    Registers(A::Registers),
+    Bytes(Vec<u8>),
+    Threads(Vec<Tid>),
+    /// A page of threads, and the cursor to resume enumeration from.
+    ThreadPage(Vec<Tid>, Option<usize>),
+    Pid(Pid),
+    Fd(u32),
+    Usize(A::Usize),
+    Stat(HostIoStat),
+    Offsets(Offsets<A::Usize>),
+}
+
+/// An error returned by the proxied target.
+#[derive(Debug)]
+enum RemoteError {
+    NonFatal,
+    Errno(u8),
+    HostIo(HostIoErrno),
+    Fatal,
+}
+
+/// Errno reported in place of I/O errors which lack an errno, or whose errno
+/// doesn't fit in a `u8` (rather than some unrelated, truncated errno).
+///
+/// `EIO`, which has the same value on every unix.
+const FALLBACK_ERRNO: u8 = 5;
+
+impl RemoteError {
+    fn from_target_error<E>(e: &TargetError<E>) -> RemoteError {
+        match e {
+            TargetError::NonFatal => RemoteError::NonFatal,
+            TargetError::Errno(code) => RemoteError::Errno(*code),
+            TargetError::Fatal(_) => RemoteError::Fatal,
+            TargetError::Io(e) => RemoteError::Errno(
+                e.raw_os_error()
+                    .and_then(|code| u8::try_from(code).ok())
+                    .unwrap_or(FALLBACK_ERRNO),
+            ),
+        }
+    }
+}
+
+type Response<A> = Result<Reply<A>, RemoteError>;
+
+/// Stop reasons reported by the emulator.
+type StopReason<A> = MultiThreadStopReason<<A as Arch>::Usize>;
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::{
+        channel, ProxyServer, RemoteError, ServeError, ServeProxy, TargetProxy, FALLBACK_ERRNO,
+    };
+    use crate::common::{Signal, Tid};
+    use crate::conn::MemConnection;
+    use crate::stub::state_machine::GdbStubStateMachine;
+    use crate::stub::test_util::{feed, take_responses, TestArch, TestRegs, TestTarget};
+    use crate::stub::{GdbStub, SingleThreadStopReason};
+    use crate::target::ext::base::multithread::{
+        MultiThreadBase, MultiThreadListCursor, MultiThreadListCursorOps,
+    };
+    use crate::target::ext::base::BaseOps;
+    use crate::target::{Target, TargetError, TargetResult};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    impl ServeProxy for TestTarget {
+        fn on_interrupt(&mut self) -> Result<(), Self::Error> {
+            self.running = false;
+            Ok(())
+        }
+    }
+
+    /// A target with threads `1..=count`, which records how it was accessed.
+    struct Threads {
+        count: usize,
+        /// Cursors passed to `list_active_threads_from`.
+        cursors: Vec<usize>,
+        /// Threads whose registers were read.
+        regs_read: Vec<Tid>,
+    }
+
+    impl Target for Threads {
+        type Arch = TestArch;
+        type Error = ();
+
+        fn base_ops(&mut self) -> BaseOps<'_, TestArch, ()> {
+            BaseOps::MultiThread(self)
+        }
+
+        fn guard_rail_implicit_sw_breakpoints(&self) -> bool {
+            true
+        }
+
+        fn use_rle(&self) -> bool {
+            false
+        }
+    }
+
+    impl MultiThreadBase for Threads {
+        fn read_registers(&mut self, _regs: &mut TestRegs, tid: Tid) -> TargetResult<(), Self> {
+            self.regs_read.push(tid);
+            Ok(())
+        }
+
+        fn write_registers(&mut self, _regs: &TestRegs, _tid: Tid) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn read_addrs(
+            &mut self,
+            _addr: u32,
+            _data: &mut [u8],
+            _tid: Tid,
+        ) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn write_addrs(&mut self, _addr: u32, _data: &[u8], _tid: Tid) -> TargetResult<(), Self> {
+            Ok(())
+        }
+
+        fn list_active_threads(
+            &mut self,
+            thread_is_active: &mut dyn FnMut(Tid),
+        ) -> Result<(), Self::Error> {
+            for tid in 1..=self.count {
+                thread_is_active(Tid::new(tid).unwrap());
+            }
+            Ok(())
+        }
+
+        fn support_list_cursor(&mut self) -> Option<MultiThreadListCursorOps<'_, Self>> {
+            Some(self)
+        }
+    }
+
+    impl MultiThreadListCursor for Threads {
+        fn list_active_threads_from(
+            &mut self,
+            cursor: usize,
+            thread_is_active: &mut dyn FnMut(Tid) -> bool,
+        ) -> Result<Option<usize>, Self::Error> {
+            self.cursors.push(cursor);
+            for idx in cursor..self.count {
+                if !thread_is_active(Tid::new(idx + 1).unwrap()) {
+                    return Ok(Some(idx));
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    impl ServeProxy for Threads {
+        fn on_interrupt(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    /// Serve proxy requests on an "emulator" thread until the proxy is
+    /// dropped, calling `tick` in between batches of requests.
+    fn spawn_emulator<T>(
+        mut target: T,
+        server: ProxyServer<TestArch>,
+        mut tick: impl FnMut(&mut T, &ProxyServer<TestArch>) + Send + 'static,
+    ) -> JoinHandle<T>
+    where
+        T: ServeProxy<Arch = TestArch> + Send + 'static,
+        T::Error: core::fmt::Debug,
+    {
+        std::thread::spawn(move || loop {
+            match target.serve_proxy_requests_timeout(&server, Duration::from_millis(10)) {
+                Ok(()) => tick(&mut target, &server),
+                Err(ServeError::Disconnected) => return target,
+                Err(e) => panic!("{:?}", e),
+            }
+        })
+    }
+
+    /// Block until the proxied target reports a stop reason.
+    fn wait_for_stop(proxy: &TargetProxy<TestArch>) -> super::StopReason<TestArch> {
+        loop {
+            if let Some(reason) = proxy.try_recv_stop_reason().unwrap() {
+                return reason;
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn proxy_round_trip() {
+        let mut target = TestTarget::new();
+        let (mut proxy, server) = channel(&mut target).unwrap();
+        // single threaded targets aren't presented as multi threaded ones
+        assert!(matches!(proxy.base_ops(), BaseOps::SingleThread(_)));
+
+        let emulator = spawn_emulator(target, server, |target, server| {
+            if target.running {
+                target.running = false;
+                server.report_stop(SingleThreadStopReason::Signal(Signal::SIGTRAP));
+            }
+        });
+
+        let mut conn = MemConnection::new();
+        for pkt in [&b"g"[..], b"M0,2:abcd", b"m0,2", b"m200,1", b"c"] {
+            conn.inject_packet(pkt).unwrap();
+        }
+        let gdb = GdbStub::new(conn).run_state_machine(&mut proxy).unwrap();
+        let mut gdb = match feed(gdb, &mut proxy) {
+            GdbStubStateMachine::Running(gdb) => {
+                let reason = wait_for_stop(&proxy);
+                gdb.report_stop(&mut proxy, reason).unwrap()
+            }
+            _ => panic!("expected the target to be running"),
+        };
+        assert_eq!(
+            take_responses(&mut gdb),
+            ["000000000000000000000000", "OK", "abcd", "E0e", "S05"]
+        );
+
+        match &mut gdb {
+            GdbStubStateMachine::Idle(gdb) => gdb.borrow_conn().inject_packet(b"D").unwrap(),
+            _ => panic!("expected the target to be stopped"),
+        }
+        let gdb = feed(gdb, &mut proxy);
+        assert!(matches!(gdb, GdbStubStateMachine::Disconnected(_)));
+
+        drop(gdb);
+        drop(proxy);
+        let target = emulator.join().unwrap();
+        assert_eq!(target.mem[..2], [0xab, 0xcd]);
+        assert_eq!(target.resumed, Some(None));
+    }
+
+    #[test]
+    fn proxy_multithread() {
+        let mut target = Threads {
+            count: 130,
+            cursors: Vec::new(),
+            regs_read: Vec::new(),
+        };
+        let (mut proxy, server) = channel(&mut target).unwrap();
+        assert!(matches!(proxy.base_ops(), BaseOps::MultiThread(_)));
+        let emulator = spawn_emulator(target, server, |_, _| {});
+
+        // the thread list is re-requested part way through, and then read to
+        // completion (with a few extra requests past the end of the list)
+        let mut conn = MemConnection::new();
+        for pkt in [
+            &b"Hg2"[..],
+            b"g",
+            b"qfThreadInfo",
+            b"qsThreadInfo",
+            b"qfThreadInfo",
+        ] {
+            conn.inject_packet(pkt).unwrap();
+        }
+        for _ in 0..8 {
+            conn.inject_packet(b"qsThreadInfo").unwrap();
+        }
+        conn.inject_packet(b"D").unwrap();
+
+        let stub = GdbStub::builder(conn)
+            .packet_buffer_size(100)
+            .build()
+            .unwrap();
+        let mut gdb = feed(stub.run_state_machine(&mut proxy).unwrap(), &mut proxy);
+        assert!(matches!(gdb, GdbStubStateMachine::Disconnected(_)));
+
+        let responses = take_responses(&mut gdb);
+        assert_eq!(responses[..2], ["OK", "000000000000000000000000"]);
+        // the restarted enumeration begins from the first thread again
+        let pages = &responses[2..responses.len() - 1];
+        assert_eq!(pages[2], pages[0]);
+
+        let tids: Vec<usize> = pages[2..]
+            .iter()
+            .take_while(|page| *page != "l")
+            .flat_map(|page| page.strip_prefix('m').unwrap().split(','))
+            .map(|tid| usize::from_str_radix(tid, 16).unwrap())
+            .collect();
+        assert_eq!(tids, (1..=130).collect::<Vec<_>>());
+        // the list spans several pages, and stays finished once reported
+        assert!(pages.len() > 4);
+        assert_eq!(pages[pages.len() - 2..], ["l", "l"]);
+
+        drop(gdb);
+
+        // resuming from a cursor the proxy didn't just hand out restarts the
+        // enumeration, skipping over the threads before the cursor
+        let mut tids = Vec::new();
+        let cursor = proxy
+            .list_active_threads_from(10, &mut |tid| {
+                if tids.len() == 5 {
+                    return false;
+                }
+                tids.push(tid.get());
+                true
+            })
+            .unwrap();
+        assert_eq!(tids, [11, 12, 13, 14, 15]);
+        assert_eq!(cursor, Some(15));
+
+        drop(proxy);
+        let target = emulator.join().unwrap();
+        assert_eq!(target.regs_read, [Tid::new(2).unwrap()]);
+        // threads are fetched from the emulator 64 at a time, and only
+        // re-fetched from the start when the enumeration was restarted
+        assert_eq!(target.cursors, [0, 64, 0, 64, 128, 0]);
+    }
+
+    #[test]
+    fn out_of_range_errno() {
+        let e = TargetError::<()>::Io(std::io::Error::from_raw_os_error(300));
+        assert!(matches!(
+            RemoteError::from_target_error(&e),
+            RemoteError::Errno(FALLBACK_ERRNO)
+        ));
+
+        // no errno at all
+        let e = TargetError::<()>::Io(std::io::ErrorKind::Other.into());
+        assert!(matches!(
+            RemoteError::from_target_error(&e),
+            RemoteError::Errno(FALLBACK_ERRNO)
+        ));
+
+        let e = TargetError::<()>::Io(std::io::Error::from_raw_os_error(13));
+        assert!(matches!(
+            RemoteError::from_target_error(&e),
+            RemoteError::Errno(13)
+        ));
+    }
+}
@@ -0,0 +1,1204 @@
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::os::unix::io::AsRawFd;
+use std::sync::mpsc;
+
+use crate::arch::{Arch, SingleStepGdbBehavior};
+use crate::common::{Pid, Signal, Tid};
+use crate::conn::ConnectionExt;
+use crate::stub::run_poll::{PollEventLoop, WakeEvent};
+use crate::target::ext::auxv::{Auxv, AuxvOps};
+use crate::target::ext::base::multithread::{
+    MultiThreadBase, MultiThreadListCursor, MultiThreadListCursorOps, MultiThreadRangeStepping,
+    MultiThreadRangeSteppingOps, MultiThreadResume, MultiThreadResumeAtAddr,
+    MultiThreadResumeAtAddrOps, MultiThreadResumeOps, MultiThreadSingleStep,
+    MultiThreadSingleStepOps,
+};
+use crate::target::ext::base::reverse_exec::{
+    ReverseCont, ReverseContOps, ReverseStep, ReverseStepOps,
+};
+use crate::target::ext::base::single_register_access::{
+    SingleRegisterAccess, SingleRegisterAccessOps,
+};
+use crate::target::ext::base::singlethread::{
+    SingleThreadBase, SingleThreadRangeStepping, SingleThreadRangeSteppingOps, SingleThreadResume,
+    SingleThreadResumeAtAddr, SingleThreadResumeAtAddrOps, SingleThreadResumeOps,
+    SingleThreadSingleStep, SingleThreadSingleStepOps,
+};
+use crate::target::ext::base::BaseOps;
+use crate::target::ext::breakpoints::{
+    Breakpoints, BreakpointsOps, HwBreakpoint, HwBreakpointOps, HwWatchpoint, HwWatchpointOps,
+    SwBreakpoint, SwBreakpointOps, WatchKind,
+};
+use crate::target::ext::catch_syscalls::{CatchSyscalls, CatchSyscallsOps, SyscallNumbers};
+use crate::target::ext::exec_file::{ExecFile, ExecFileOps};
+use crate::target::ext::extended_mode::{
+    Args, AttachKind, ConfigureAslr, ConfigureAslrOps, ConfigureEnv, ConfigureEnvOps,
+    ConfigureStartupShell, ConfigureStartupShellOps, ConfigureWorkingDir, ConfigureWorkingDirOps,
+    ExtendedMode, ExtendedModeOps, ShouldTerminate,
+};
+use crate::target::ext::host_io::{
+    FsKind, HostIo, HostIoClose, HostIoCloseOps, HostIoError, HostIoFstat, HostIoFstatOps,
+    HostIoOpen, HostIoOpenFlags, HostIoOpenMode, HostIoOpenOps, HostIoOps, HostIoPread,
+    HostIoPreadOps, HostIoPwrite, HostIoPwriteOps, HostIoReadlink, HostIoReadlinkOps, HostIoResult,
+    HostIoSetfs, HostIoSetfsOps, HostIoStat, HostIoUnlink, HostIoUnlinkOps,
+};
+use crate::target::ext::memory_map::{MemoryMap, MemoryMapOps};
+use crate::target::ext::monitor_cmd::{ConsoleOutput, MonitorCmd, MonitorCmdOps};
+use crate::target::ext::section_offsets::{Offsets, SectionOffsets, SectionOffsetsOps};
+use crate::target::ext::target_description_xml_override::{
+    TargetDescriptionXmlOverride, TargetDescriptionXmlOverrideOps,
+};
+use crate::target::{Target, TargetError, TargetResult};
+use crate::SINGLE_THREAD_TID;
+
+use super::{
+    Capabilities, Document, ExtendedModeRequest, HostIoRequest, RemoteError, Reply, Request,
+    Response, ResumeAction, ResumeCapabilities, StopReason,
+};
+
+/// Errors which may occur when forwarding requests from a [`TargetProxy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyError {
+    /// The [`ProxyServer`](super::ProxyServer) was dropped.
+    Disconnected,
+    /// The proxied target returned a fatal error (which is reported to the
+    /// emulator by [`ServeProxy::serve_proxy_requests`]).
+    ///
+    /// [`ServeProxy::serve_proxy_requests`]: super::ServeProxy::serve_proxy_requests
+    Target,
+    /// The proxied target returned an unexpected reply.
+    UnexpectedReply,
+}
+
+impl core::fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProxyError::Disconnected => write!(f, "the proxied target has been disconnected"),
+            ProxyError::Target => write!(f, "the proxied target returned a fatal error"),
+            ProxyError::UnexpectedReply => {
+                write!(f, "the proxied target returned an unexpected reply")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
+
+/// A [`Target`] which forwards all requests to a target running on another
+/// thread. See the [module level documentation](super) for more details.
+pub struct TargetProxy<A: Arch> {
+    caps: Capabilities,
+    tx: mpsc::Sender<Request<A>>,
+    rx: mpsc::Receiver<Response<A>>,
+    stop_rx: mpsc::Receiver<StopReason<A>>,
+    wake: WakeEvent,
+    resume_actions: Vec<(Tid, ResumeAction<A::Usize>)>,
+    thread_list: ThreadList,
+}
+
+/// State of an in-progress cursor-based thread enumeration.
+///
+/// Threads are fetched from the proxied target a page at a time, whereas the
+/// cursors handed out to the stub simply count the number of threads reported
+/// so far.
+#[derive(Default)]
+struct ThreadList {
+    /// Threads received from the proxied target, but not yet reported.
+    pending: VecDeque<Tid>,
+    /// The proxied target's cursor for the next page (if any).
+    remote_cursor: Option<usize>,
+    reported: usize,
+}
+
+impl<A: Arch> TargetProxy<A> {
+    pub(super) fn new(
+        caps: Capabilities,
+        tx: mpsc::Sender<Request<A>>,
+        rx: mpsc::Receiver<Response<A>>,
+        stop_rx: mpsc::Receiver<StopReason<A>>,
+        wake: WakeEvent,
+    ) -> TargetProxy<A> {
+        TargetProxy {
+            caps,
+            tx,
+            rx,
+            stop_rx,
+            wake,
+            resume_actions: Vec::new(),
+            thread_list: ThreadList::default(),
+        }
+    }
+
+    /// Return the [`WakeEvent`] signalled whenever the proxied target reports
+    /// a stop reason.
+    pub fn wake_event(&self) -> &WakeEvent {
+        &self.wake
+    }
+
+    /// Return the proxied target's most recently reported stop reason (if
+    /// any).
+    pub fn try_recv_stop_reason(&self) -> Result<Option<StopReason<A>>, ProxyError> {
+        match self.stop_rx.try_recv() {
+            Ok(reason) => Ok(Some(reason)),
+            Err(mpsc::TryRecvError::Empty) => Ok(None),
+            Err(mpsc::TryRecvError::Disconnected) => Err(ProxyError::Disconnected),
+        }
+    }
+
+    /// Send a request to the proxied target, and block until it responds.
+    fn send(&self, req: Request<A>) -> Result<Response<A>, ProxyError> {
+        self.tx.send(req).map_err(|_| ProxyError::Disconnected)?;
+        self.rx.recv().map_err(|_| ProxyError::Disconnected)
+    }
+
+    fn call(&self, req: Request<A>) -> TargetResult<Reply<A>, Self> {
+        match self.send(req).map_err(TargetError::Fatal)? {
+            Ok(reply) => Ok(reply),
+            Err(RemoteError::NonFatal) => Err(TargetError::NonFatal),
+            Err(RemoteError::Errno(code)) => Err(TargetError::Errno(code)),
+            // only ever returned in response to host I/O requests
+            Err(RemoteError::HostIo(_)) => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+            Err(RemoteError::Fatal) => Err(TargetError::Fatal(ProxyError::Target)),
+        }
+    }
+
+    /// Like `call`, but for target methods that can only return fatal errors.
+    fn call_fatal(&self, req: Request<A>) -> Result<Reply<A>, ProxyError> {
+        self.call(req).map_err(|e| match e {
+            TargetError::Fatal(e) => e,
+            _ => ProxyError::Target,
+        })
+    }
+
+    /// Like `call`, but for host I/O requests.
+    fn call_host_io(&self, req: HostIoRequest<A::Usize>) -> HostIoResult<Reply<A>, Self> {
+        match self
+            .send(Request::HostIo(req))
+            .map_err(HostIoError::Fatal)?
+        {
+            Ok(reply) => Ok(reply),
+            Err(RemoteError::HostIo(errno)) => Err(HostIoError::Errno(errno)),
+            Err(RemoteError::Fatal) => Err(HostIoError::Fatal(ProxyError::Target)),
+            // the proxy only advertises the host I/O operations the target supports
+            Err(_) => Err(HostIoError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn call_host_io_unit(&self, req: HostIoRequest<A::Usize>) -> HostIoResult<(), Self> {
+        match self.call_host_io(req)? {
+            Reply::Unit => Ok(()),
+            _ => Err(HostIoError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    /// Copy a variable-length reply into `buf`, returning its length.
+    fn copy_bytes(reply: Reply<A>, buf: &mut [u8]) -> Result<usize, ProxyError> {
+        match reply {
+            Reply::Bytes(data) if data.len() <= buf.len() => {
+                buf[..data.len()].copy_from_slice(&data);
+                Ok(data.len())
+            }
+            _ => Err(ProxyError::UnexpectedReply),
+        }
+    }
+
+    fn call_bool(&self, req: Request<A>) -> TargetResult<bool, Self> {
+        match self.call(req)? {
+            Reply::Bool(b) => Ok(b),
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn call_unit(&self, req: Request<A>) -> TargetResult<(), Self> {
+        match self.call(req)? {
+            Reply::Unit => Ok(()),
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn call_fatal_unit(&self, req: Request<A>) -> Result<(), ProxyError> {
+        match self.call_fatal(req)? {
+            Reply::Unit => Ok(()),
+            _ => Err(ProxyError::UnexpectedReply),
+        }
+    }
+
+    fn resume_caps(&self) -> ResumeCapabilities {
+        self.caps.resume.unwrap_or_default()
+    }
+
+    /// Resume the single threaded target with the given action.
+    fn resume_single_thread(&mut self, action: ResumeAction<A::Usize>) -> Result<(), ProxyError> {
+        self.call_fatal_unit(Request::Resume(vec![(SINGLE_THREAD_TID, action)]))
+    }
+
+    fn read_registers_impl(&self, regs: &mut A::Registers, tid: Tid) -> TargetResult<(), Self> {
+        match self.call(Request::ReadRegisters(tid))? {
+            Reply::Registers(r) => {
+                *regs = r;
+                Ok(())
+            }
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn read_addrs_impl(
+        &self,
+        start_addr: A::Usize,
+        data: &mut [u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        match self.call(Request::ReadAddrs(start_addr, data.len(), tid))? {
+            Reply::Bytes(bytes) if bytes.len() == data.len() => {
+                data.copy_from_slice(&bytes);
+                Ok(())
+            }
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn read_register_impl(
+        &self,
+        tid: Tid,
+        reg_id: A::RegId,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        let reply = self.call(Request::ReadRegister(tid, reg_id, buf.len()))?;
+        Self::copy_bytes(reply, buf).map_err(TargetError::Fatal)
+    }
+
+    /// Read (a chunk of) a document into `buf`.
+    fn read_document(
+        &self,
+        doc: Document,
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        let length = length.min(buf.len());
+        let reply = self.call(Request::ReadDocument(doc, offset, length))?;
+        Self::copy_bytes(reply, &mut buf[..length]).map_err(TargetError::Fatal)
+    }
+
+    fn extended_mode(&self, req: ExtendedModeRequest) -> TargetResult<Reply<A>, Self> {
+        self.call(Request::ExtendedMode(req))
+    }
+
+    fn extended_mode_unit(&self, req: ExtendedModeRequest) -> TargetResult<(), Self> {
+        self.call_unit(Request::ExtendedMode(req))
+    }
+}
+
+impl<A: Arch> Target for TargetProxy<A> {
+    type Arch = A;
+    type Error = ProxyError;
+
+    #[inline(always)]
+    fn base_ops(&mut self) -> BaseOps<'_, A, ProxyError> {
+        if self.caps.multithread {
+            BaseOps::MultiThread(self)
+        } else {
+            BaseOps::SingleThread(self)
+        }
+    }
+
+    fn guard_rail_implicit_sw_breakpoints(&self) -> bool {
+        self.caps.guard_rail_implicit_sw_breakpoints
+    }
+
+    fn guard_rail_single_step_gdb_behavior(&self) -> SingleStepGdbBehavior {
+        self.caps.guard_rail_single_step_gdb_behavior
+    }
+
+    fn use_x_upcase_packet(&self) -> bool {
+        self.caps.use_x_upcase_packet
+    }
+
+    fn use_resume_stub(&self) -> bool {
+        self.caps.use_resume_stub
+    }
+
+    fn use_rle(&self) -> bool {
+        self.caps.use_rle
+    }
+
+    fn use_target_description_xml(&self) -> bool {
+        self.caps.use_target_description_xml
+    }
+
+    fn support_breakpoints(&mut self) -> Option<BreakpointsOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_extended_mode(&mut self) -> Option<ExtendedModeOps<'_, Self>> {
+        if self.caps.extended_mode.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_host_io(&mut self) -> Option<HostIoOps<'_, Self>> {
+        if self.caps.host_io.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_catch_syscalls(&mut self) -> Option<CatchSyscallsOps<'_, Self>> {
+        if self.caps.catch_syscalls {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_monitor_cmd(&mut self) -> Option<MonitorCmdOps<'_, Self>> {
+        if self.caps.monitor_cmd {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_section_offsets(&mut self) -> Option<SectionOffsetsOps<'_, Self>> {
+        if self.caps.section_offsets {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_memory_map(&mut self) -> Option<MemoryMapOps<'_, Self>> {
+        if self.caps.memory_map {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_target_description_xml_override(
+        &mut self,
+    ) -> Option<TargetDescriptionXmlOverrideOps<'_, Self>> {
+        if self.caps.target_description_xml_override {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_exec_file(&mut self) -> Option<ExecFileOps<'_, Self>> {
+        if self.caps.exec_file {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_auxv(&mut self) -> Option<AuxvOps<'_, Self>> {
+        if self.caps.auxv {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Arch> SingleThreadBase for TargetProxy<A> {
+    fn read_registers(&mut self, regs: &mut A::Registers) -> TargetResult<(), Self> {
+        self.read_registers_impl(regs, SINGLE_THREAD_TID)
+    }
+
+    fn write_registers(&mut self, regs: &A::Registers) -> TargetResult<(), Self> {
+        self.call_unit(Request::WriteRegisters(regs.clone(), SINGLE_THREAD_TID))
+    }
+
+    fn read_addrs(&mut self, start_addr: A::Usize, data: &mut [u8]) -> TargetResult<(), Self> {
+        self.read_addrs_impl(start_addr, data, SINGLE_THREAD_TID)
+    }
+
+    fn write_addrs(&mut self, start_addr: A::Usize, data: &[u8]) -> TargetResult<(), Self> {
+        self.call_unit(Request::WriteAddrs(
+            start_addr,
+            data.to_vec(),
+            SINGLE_THREAD_TID,
+        ))
+    }
+
+    fn support_single_register_access(&mut self) -> Option<SingleRegisterAccessOps<'_, (), Self>> {
+        if self.caps.single_register_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_resume(&mut self) -> Option<SingleThreadResumeOps<'_, Self>> {
+        if self.caps.resume.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Arch> SingleRegisterAccess<()> for TargetProxy<A> {
+    fn read_register(
+        &mut self,
+        _tid: (),
+        reg_id: A::RegId,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        self.read_register_impl(SINGLE_THREAD_TID, reg_id, buf)
+    }
+
+    fn write_register(&mut self, _tid: (), reg_id: A::RegId, val: &[u8]) -> TargetResult<(), Self> {
+        self.call_unit(Request::WriteRegister(
+            SINGLE_THREAD_TID,
+            reg_id,
+            val.to_vec(),
+        ))
+    }
+}
+
+impl<A: Arch> SingleThreadResume for TargetProxy<A> {
+    fn resume(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.resume_single_thread(ResumeAction::Continue(signal))
+    }
+
+    fn support_single_step(&mut self) -> Option<SingleThreadSingleStepOps<'_, Self>> {
+        if self.resume_caps().single_step {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_range_step(&mut self) -> Option<SingleThreadRangeSteppingOps<'_, Self>> {
+        if self.resume_caps().range_step {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_reverse_step(&mut self) -> Option<ReverseStepOps<'_, (), Self>> {
+        if self.resume_caps().reverse_step {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_reverse_cont(&mut self) -> Option<ReverseContOps<'_, (), Self>> {
+        if self.resume_caps().reverse_cont {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_resume_at_addr(&mut self) -> Option<SingleThreadResumeAtAddrOps<'_, Self>> {
+        if self.resume_caps().resume_at_addr {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Arch> SingleThreadSingleStep for TargetProxy<A> {
+    fn step(&mut self, signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.resume_single_thread(ResumeAction::Step(signal))
+    }
+}
+
+impl<A: Arch> SingleThreadRangeStepping for TargetProxy<A> {
+    fn resume_range_step(&mut self, start: A::Usize, end: A::Usize) -> Result<(), Self::Error> {
+        self.resume_single_thread(ResumeAction::RangeStep(start, end))
+    }
+}
+
+impl<A: Arch> SingleThreadResumeAtAddr for TargetProxy<A> {
+    fn set_resume_addr(&mut self, addr: A::Usize) -> TargetResult<(), Self> {
+        self.call_unit(Request::SetResumeAddr(SINGLE_THREAD_TID, addr))
+    }
+}
+
+impl<A: Arch> ReverseCont<()> for TargetProxy<A> {
+    fn reverse_cont(&mut self) -> Result<(), Self::Error> {
+        self.call_fatal_unit(Request::ReverseCont)
+    }
+}
+
+impl<A: Arch> ReverseStep<()> for TargetProxy<A> {
+    fn reverse_step(&mut self, _tid: ()) -> Result<(), Self::Error> {
+        self.call_fatal_unit(Request::ReverseStep(SINGLE_THREAD_TID))
+    }
+}
+
+impl<A: Arch> MultiThreadBase for TargetProxy<A> {
+    fn read_registers(&mut self, regs: &mut A::Registers, tid: Tid) -> TargetResult<(), Self> {
+        self.read_registers_impl(regs, tid)
+    }
+
+    fn write_registers(&mut self, regs: &A::Registers, tid: Tid) -> TargetResult<(), Self> {
+        self.call_unit(Request::WriteRegisters(regs.clone(), tid))
+    }
+
+    fn read_addrs(
+        &mut self,
+        start_addr: A::Usize,
+        data: &mut [u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        self.read_addrs_impl(start_addr, data, tid)
+    }
+
+    fn write_addrs(
+        &mut self,
+        start_addr: A::Usize,
+        data: &[u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self> {
+        self.call_unit(Request::WriteAddrs(start_addr, data.to_vec(), tid))
+    }
+
+    fn list_active_threads(
+        &mut self,
+        thread_is_active: &mut dyn FnMut(Tid),
+    ) -> Result<(), Self::Error> {
+        match self.call_fatal(Request::ListThreads)? {
+            Reply::Threads(tids) => {
+                tids.into_iter().for_each(thread_is_active);
+                Ok(())
+            }
+            _ => Err(ProxyError::UnexpectedReply),
+        }
+    }
+
+    fn support_single_register_access(&mut self) -> Option<SingleRegisterAccessOps<'_, Tid, Self>> {
+        if self.caps.single_register_access {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_resume(&mut self) -> Option<MultiThreadResumeOps<'_, Self>> {
+        if self.caps.resume.is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_list_cursor(&mut self) -> Option<MultiThreadListCursorOps<'_, Self>> {
+        if self.caps.list_cursor {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Arch> SingleRegisterAccess<Tid> for TargetProxy<A> {
+    fn read_register(
+        &mut self,
+        tid: Tid,
+        reg_id: A::RegId,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        self.read_register_impl(tid, reg_id, buf)
+    }
+
+    fn write_register(&mut self, tid: Tid, reg_id: A::RegId, val: &[u8]) -> TargetResult<(), Self> {
+        self.call_unit(Request::WriteRegister(tid, reg_id, val.to_vec()))
+    }
+}
+
+impl<A: Arch> MultiThreadListCursor for TargetProxy<A> {
+    fn list_active_threads_from(
+        &mut self,
+        cursor: usize,
+        thread_is_active: &mut dyn FnMut(Tid) -> bool,
+    ) -> Result<Option<usize>, Self::Error> {
+        // the stub resumes from the most recently returned cursor, so anything
+        // else means a new enumeration has started (e.g: after a `qfThreadInfo`
+        // was re-sent). Restart from the beginning, skipping over any threads
+        // the stub has already reported.
+        let mut skip = 0;
+        if cursor == 0 || cursor != self.thread_list.reported {
+            self.thread_list = ThreadList {
+                remote_cursor: Some(0),
+                ..ThreadList::default()
+            };
+            skip = cursor;
+        }
+
+        loop {
+            let tid = match self.thread_list.pending.front() {
+                Some(tid) => *tid,
+                None => {
+                    let remote_cursor = match self.thread_list.remote_cursor {
+                        Some(remote_cursor) => remote_cursor,
+                        None => return Ok(None),
+                    };
+                    match self.call_fatal(Request::ListThreadsFrom(remote_cursor))? {
+                        // guard against a target returning empty pages forever
+                        Reply::ThreadPage(tids, _) if tids.is_empty() => {
+                            self.thread_list.remote_cursor = None
+                        }
+                        Reply::ThreadPage(tids, next_cursor) => {
+                            self.thread_list.pending.extend(tids);
+                            self.thread_list.remote_cursor = next_cursor;
+                        }
+                        _ => return Err(ProxyError::UnexpectedReply),
+                    }
+                    continue;
+                }
+            };
+
+            if skip != 0 {
+                skip -= 1;
+            } else if !thread_is_active(tid) {
+                return Ok(Some(self.thread_list.reported));
+            }
+
+            self.thread_list.pending.pop_front();
+            self.thread_list.reported += 1;
+        }
+    }
+}
+
+impl<A: Arch> MultiThreadResume for TargetProxy<A> {
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        let actions = core::mem::take(&mut self.resume_actions);
+        self.call_fatal_unit(Request::Resume(actions))
+    }
+
+    fn clear_resume_actions(&mut self) -> Result<(), Self::Error> {
+        self.resume_actions.clear();
+        Ok(())
+    }
+
+    fn set_resume_action_continue(
+        &mut self,
+        tid: Tid,
+        signal: Option<Signal>,
+    ) -> Result<(), Self::Error> {
+        self.resume_actions
+            .push((tid, ResumeAction::Continue(signal)));
+        Ok(())
+    }
+
+    fn support_single_step(&mut self) -> Option<MultiThreadSingleStepOps<'_, Self>> {
+        if self.resume_caps().single_step {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_range_step(&mut self) -> Option<MultiThreadRangeSteppingOps<'_, Self>> {
+        if self.resume_caps().range_step {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_reverse_step(&mut self) -> Option<ReverseStepOps<'_, Tid, Self>> {
+        if self.resume_caps().reverse_step {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_reverse_cont(&mut self) -> Option<ReverseContOps<'_, Tid, Self>> {
+        if self.resume_caps().reverse_cont {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_resume_at_addr(&mut self) -> Option<MultiThreadResumeAtAddrOps<'_, Self>> {
+        if self.resume_caps().resume_at_addr {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Arch> MultiThreadSingleStep for TargetProxy<A> {
+    fn set_resume_action_step(
+        &mut self,
+        tid: Tid,
+        signal: Option<Signal>,
+    ) -> Result<(), Self::Error> {
+        self.resume_actions.push((tid, ResumeAction::Step(signal)));
+        Ok(())
+    }
+}
+
+impl<A: Arch> MultiThreadRangeStepping for TargetProxy<A> {
+    fn set_resume_action_range_step(
+        &mut self,
+        tid: Tid,
+        start: A::Usize,
+        end: A::Usize,
+    ) -> Result<(), Self::Error> {
+        self.resume_actions
+            .push((tid, ResumeAction::RangeStep(start, end)));
+        Ok(())
+    }
+}
+
+impl<A: Arch> MultiThreadResumeAtAddr for TargetProxy<A> {
+    fn set_resume_addr(&mut self, tid: Tid, addr: A::Usize) -> TargetResult<(), Self> {
+        self.call_unit(Request::SetResumeAddr(tid, addr))
+    }
+}
+
+impl<A: Arch> ReverseCont<Tid> for TargetProxy<A> {
+    fn reverse_cont(&mut self) -> Result<(), Self::Error> {
+        self.call_fatal_unit(Request::ReverseCont)
+    }
+}
+
+impl<A: Arch> ReverseStep<Tid> for TargetProxy<A> {
+    fn reverse_step(&mut self, tid: Tid) -> Result<(), Self::Error> {
+        self.call_fatal_unit(Request::ReverseStep(tid))
+    }
+}
+
+impl<A: Arch> Breakpoints for TargetProxy<A> {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        if self.caps.sw_breakpoint {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_hw_breakpoint(&mut self) -> Option<HwBreakpointOps<'_, Self>> {
+        if self.caps.hw_breakpoint {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn support_hw_watchpoint(&mut self) -> Option<HwWatchpointOps<'_, Self>> {
+        if self.caps.hw_watchpoint {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Arch> SwBreakpoint for TargetProxy<A> {
+    fn add_sw_breakpoint(
+        &mut self,
+        addr: A::Usize,
+        kind: A::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.call_bool(Request::SwBreakpoint(true, addr, kind))
+    }
+
+    fn remove_sw_breakpoint(
+        &mut self,
+        addr: A::Usize,
+        kind: A::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.call_bool(Request::SwBreakpoint(false, addr, kind))
+    }
+}
+
+impl<A: Arch> HwBreakpoint for TargetProxy<A> {
+    fn add_hw_breakpoint(
+        &mut self,
+        addr: A::Usize,
+        kind: A::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.call_bool(Request::HwBreakpoint(true, addr, kind))
+    }
+
+    fn remove_hw_breakpoint(
+        &mut self,
+        addr: A::Usize,
+        kind: A::BreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.call_bool(Request::HwBreakpoint(false, addr, kind))
+    }
+}
+
+impl<A: Arch> HwWatchpoint for TargetProxy<A> {
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: A::Usize,
+        len: A::Usize,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        self.call_bool(Request::HwWatchpoint(true, addr, len, kind))
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: A::Usize,
+        len: A::Usize,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        self.call_bool(Request::HwWatchpoint(false, addr, len, kind))
+    }
+}
+
+impl<A: Arch> MonitorCmd for TargetProxy<A> {
+    fn handle_monitor_cmd(
+        &mut self,
+        cmd: &[u8],
+        mut out: ConsoleOutput<'_>,
+    ) -> Result<(), Self::Error> {
+        match self.call_fatal(Request::MonitorCmd(cmd.to_vec()))? {
+            Reply::Bytes(output) => {
+                out.write_raw(&output);
+                Ok(())
+            }
+            _ => Err(ProxyError::UnexpectedReply),
+        }
+    }
+}
+
+impl<A: Arch> SectionOffsets for TargetProxy<A> {
+    fn get_section_offsets(&mut self) -> Result<Offsets<A::Usize>, Self::Error> {
+        match self.call_fatal(Request::SectionOffsets)? {
+            Reply::Offsets(offsets) => Ok(offsets),
+            _ => Err(ProxyError::UnexpectedReply),
+        }
+    }
+}
+
+impl<A: Arch> MemoryMap for TargetProxy<A> {
+    fn memory_map_xml(
+        &self,
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        self.read_document(Document::MemoryMap, offset, length, buf)
+    }
+}
+
+impl<A: Arch> TargetDescriptionXmlOverride for TargetProxy<A> {
+    fn target_description_xml(
+        &self,
+        annex: &[u8],
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        let doc = Document::TargetDescriptionXml(annex.to_vec());
+        self.read_document(doc, offset, length, buf)
+    }
+}
+
+impl<A: Arch> ExecFile for TargetProxy<A> {
+    fn get_exec_file(
+        &self,
+        pid: Option<Pid>,
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        self.read_document(Document::ExecFile(pid), offset, length, buf)
+    }
+}
+
+impl<A: Arch> Auxv for TargetProxy<A> {
+    fn get_auxv(&self, offset: u64, length: usize, buf: &mut [u8]) -> TargetResult<usize, Self> {
+        self.read_document(Document::Auxv, offset, length, buf)
+    }
+}
+
+impl<A: Arch> ExtendedMode for TargetProxy<A> {
+    fn run(&mut self, filename: Option<&[u8]>, args: Args<'_, '_>) -> TargetResult<Pid, Self> {
+        let filename = filename.map(|filename| filename.to_vec());
+        let args = args.map(|arg| arg.to_vec()).collect();
+        match self.extended_mode(ExtendedModeRequest::Run(filename, args))? {
+            Reply::Pid(pid) => Ok(pid),
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn attach(&mut self, pid: Pid) -> TargetResult<(), Self> {
+        self.extended_mode_unit(ExtendedModeRequest::Attach(pid))
+    }
+
+    fn query_if_attached(&mut self, pid: Pid) -> TargetResult<AttachKind, Self> {
+        match self.extended_mode(ExtendedModeRequest::QueryIfAttached(pid))? {
+            Reply::Bool(true) => Ok(AttachKind::Attach),
+            Reply::Bool(false) => Ok(AttachKind::Run),
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn kill(&mut self, pid: Option<Pid>) -> TargetResult<ShouldTerminate, Self> {
+        match self.extended_mode(ExtendedModeRequest::Kill(pid))? {
+            Reply::Bool(true) => Ok(ShouldTerminate::Yes),
+            Reply::Bool(false) => Ok(ShouldTerminate::No),
+            _ => Err(TargetError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+
+    fn restart(&mut self) -> Result<(), Self::Error> {
+        self.call_fatal_unit(Request::ExtendedMode(ExtendedModeRequest::Restart))
+    }
+
+    fn on_start(&mut self) -> Result<(), Self::Error> {
+        self.call_fatal_unit(Request::ExtendedMode(ExtendedModeRequest::OnStart))
+    }
+
+    fn support_configure_aslr(&mut self) -> Option<ConfigureAslrOps<'_, Self>> {
+        match self.caps.extended_mode {
+            Some(caps) if caps.configure_aslr => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_configure_env(&mut self) -> Option<ConfigureEnvOps<'_, Self>> {
+        match self.caps.extended_mode {
+            Some(caps) if caps.configure_env => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_configure_startup_shell(&mut self) -> Option<ConfigureStartupShellOps<'_, Self>> {
+        match self.caps.extended_mode {
+            Some(caps) if caps.configure_startup_shell => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_configure_working_dir(&mut self) -> Option<ConfigureWorkingDirOps<'_, Self>> {
+        match self.caps.extended_mode {
+            Some(caps) if caps.configure_working_dir => Some(self),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Arch> ConfigureAslr for TargetProxy<A> {
+    fn cfg_aslr(&mut self, enabled: bool) -> TargetResult<(), Self> {
+        self.extended_mode_unit(ExtendedModeRequest::ConfigureAslr(enabled))
+    }
+}
+
+impl<A: Arch> ConfigureEnv for TargetProxy<A> {
+    fn set_env(&mut self, key: &[u8], val: Option<&[u8]>) -> TargetResult<(), Self> {
+        let val = val.map(|val| val.to_vec());
+        self.extended_mode_unit(ExtendedModeRequest::SetEnv(key.to_vec(), val))
+    }
+
+    fn remove_env(&mut self, key: &[u8]) -> TargetResult<(), Self> {
+        self.extended_mode_unit(ExtendedModeRequest::RemoveEnv(key.to_vec()))
+    }
+
+    fn reset_env(&mut self) -> TargetResult<(), Self> {
+        self.extended_mode_unit(ExtendedModeRequest::ResetEnv)
+    }
+}
+
+impl<A: Arch> ConfigureStartupShell for TargetProxy<A> {
+    fn cfg_startup_with_shell(&mut self, enabled: bool) -> TargetResult<(), Self> {
+        self.extended_mode_unit(ExtendedModeRequest::ConfigureStartupShell(enabled))
+    }
+}
+
+impl<A: Arch> ConfigureWorkingDir for TargetProxy<A> {
+    fn cfg_working_dir(&mut self, dir: Option<&[u8]>) -> TargetResult<(), Self> {
+        let dir = dir.map(|dir| dir.to_vec());
+        self.extended_mode_unit(ExtendedModeRequest::ConfigureWorkingDir(dir))
+    }
+}
+
+impl<A: Arch> CatchSyscalls for TargetProxy<A> {
+    fn enable_catch_syscalls(
+        &mut self,
+        filter: Option<SyscallNumbers<'_, A::Usize>>,
+    ) -> TargetResult<(), Self> {
+        let filter = filter.map(|filter| filter.collect());
+        self.call_unit(Request::EnableCatchSyscalls(filter))
+    }
+
+    fn disable_catch_syscalls(&mut self) -> TargetResult<(), Self> {
+        self.call_unit(Request::DisableCatchSyscalls)
+    }
+}
+
+impl<A: Arch> HostIo for TargetProxy<A> {
+    fn support_open(&mut self) -> Option<HostIoOpenOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.open => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_close(&mut self) -> Option<HostIoCloseOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.close => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_pread(&mut self) -> Option<HostIoPreadOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.pread => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_pwrite(&mut self) -> Option<HostIoPwriteOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.pwrite => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_fstat(&mut self) -> Option<HostIoFstatOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.fstat => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_unlink(&mut self) -> Option<HostIoUnlinkOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.unlink => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_readlink(&mut self) -> Option<HostIoReadlinkOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.readlink => Some(self),
+            _ => None,
+        }
+    }
+
+    fn support_setfs(&mut self) -> Option<HostIoSetfsOps<'_, Self>> {
+        match self.caps.host_io {
+            Some(caps) if caps.setfs => Some(self),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Arch> HostIoOpen for TargetProxy<A> {
+    fn open(
+        &mut self,
+        filename: &[u8],
+        flags: HostIoOpenFlags,
+        mode: HostIoOpenMode,
+    ) -> HostIoResult<u32, Self> {
+        match self.call_host_io(HostIoRequest::Open(filename.to_vec(), flags, mode))? {
+            Reply::Fd(fd) => Ok(fd),
+            _ => Err(HostIoError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+}
+
+impl<A: Arch> HostIoClose for TargetProxy<A> {
+    fn close(&mut self, fd: u32) -> HostIoResult<(), Self> {
+        self.call_host_io_unit(HostIoRequest::Close(fd))
+    }
+}
+
+impl<A: Arch> HostIoPread for TargetProxy<A> {
+    fn pread(
+        &mut self,
+        fd: u32,
+        count: usize,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> HostIoResult<usize, Self> {
+        let count = count.min(buf.len());
+        let reply = self.call_host_io(HostIoRequest::Pread(fd, count, offset))?;
+        Self::copy_bytes(reply, &mut buf[..count]).map_err(HostIoError::Fatal)
+    }
+}
+
+impl<A: Arch> HostIoPwrite for TargetProxy<A> {
+    fn pwrite(&mut self, fd: u32, offset: A::Usize, data: &[u8]) -> HostIoResult<A::Usize, Self> {
+        match self.call_host_io(HostIoRequest::Pwrite(fd, offset, data.to_vec()))? {
+            Reply::Usize(n) => Ok(n),
+            _ => Err(HostIoError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+}
+
+impl<A: Arch> HostIoFstat for TargetProxy<A> {
+    fn fstat(&mut self, fd: u32) -> HostIoResult<HostIoStat, Self> {
+        match self.call_host_io(HostIoRequest::Fstat(fd))? {
+            Reply::Stat(stat) => Ok(stat),
+            _ => Err(HostIoError::Fatal(ProxyError::UnexpectedReply)),
+        }
+    }
+}
+
+impl<A: Arch> HostIoUnlink for TargetProxy<A> {
+    fn unlink(&mut self, filename: &[u8]) -> HostIoResult<(), Self> {
+        self.call_host_io_unit(HostIoRequest::Unlink(filename.to_vec()))
+    }
+}
+
+impl<A: Arch> HostIoReadlink for TargetProxy<A> {
+    fn readlink(&mut self, filename: &[u8], buf: &mut [u8]) -> HostIoResult<usize, Self> {
+        let reply = self.call_host_io(HostIoRequest::Readlink(filename.to_vec(), buf.len()))?;
+        Self::copy_bytes(reply, buf).map_err(HostIoError::Fatal)
+    }
+}
+
+impl<A: Arch> HostIoSetfs for TargetProxy<A> {
+    fn setfs(&mut self, fs: FsKind) -> HostIoResult<(), Self> {
+        self.call_host_io_unit(HostIoRequest::Setfs(fs))
+    }
+}
+
+/// A [`PollEventLoop`] which drives a [`TargetProxy`] over the connection `C`,
+/// for use with [`GdbStub::run_poll`](crate::stub::GdbStub::run_poll).
+pub struct ProxyEventLoop<A, C> {
+    _marker: PhantomData<fn() -> (A, C)>,
+}
+
+impl<A, C> PollEventLoop for ProxyEventLoop<A, C>
+where
+    A: Arch,
+    C: ConnectionExt<Error = std::io::Error> + AsRawFd,
+{
+    type Target = TargetProxy<A>;
+    type Connection = C;
+    type StopReason = StopReason<A>;
+
+    fn wake_event(target: &TargetProxy<A>) -> &WakeEvent {
+        target.wake_event()
+    }
+
+    fn check_stop_reason(target: &mut TargetProxy<A>) -> Result<Option<StopReason<A>>, ProxyError> {
+        target.try_recv_stop_reason()
+    }
+
+    fn on_interrupt(target: &mut TargetProxy<A>) -> Result<Option<StopReason<A>>, ProxyError> {
+        // the proxied target reports the resulting stop reason via
+        // `ProxyServer::report_stop`
+        match target.call_fatal(Request::Interrupt)? {
+            Reply::Unit => Ok(None),
+            _ => Err(ProxyError::UnexpectedReply),
+        }
+    }
+}
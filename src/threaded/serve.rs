@@ -0,0 +1,591 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::arch::Arch;
+use crate::stub::run_poll::WakeHandle;
+use crate::stub::MultiThreadStopReason;
+use crate::target::ext::base::{BaseOps, ResumeOps};
+use crate::target::ext::catch_syscalls::SyscallNumbers;
+use crate::target::ext::extended_mode::{Args, ExtendedModeOps};
+use crate::target::ext::host_io::{HostIoError, HostIoOps};
+use crate::target::ext::monitor_cmd::ConsoleOutput;
+use crate::target::{Target, TargetError};
+use crate::SINGLE_THREAD_TID;
+
+use super::{
+    Document, ExtendedModeRequest, HostIoRequest, RemoteError, Reply, Request, Response,
+    ResumeAction, StopReason,
+};
+
+/// Number of threads listed per [`Request::ListThreadsFrom`] round-trip.
+const THREAD_PAGE_LEN: usize = 64;
+
+/// Errors which may occur when servicing requests from a
+/// [`TargetProxy`](super::TargetProxy).
+#[derive(Debug)]
+pub enum ServeError<E> {
+    /// The target returned a fatal error.
+    Target(E),
+    /// The [`TargetProxy`](super::TargetProxy) was dropped (i.e: the debugging
+    /// session has ended).
+    Disconnected,
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for ServeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ServeError::Target(e) => write!(f, "target returned a fatal error: {:?}", e),
+            ServeError::Disconnected => write!(f, "the target proxy has been disconnected"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug> std::error::Error for ServeError<E> {}
+
+/// The emulator-side end of a [`TargetProxy`](super::TargetProxy). See the
+/// [module level documentation](super) for more details.
+pub struct ProxyServer<A: Arch> {
+    rx: mpsc::Receiver<Request<A>>,
+    tx: mpsc::Sender<Response<A>>,
+    stop_tx: mpsc::Sender<StopReason<A>>,
+    wake: WakeHandle,
+}
+
+impl<A: Arch> ProxyServer<A> {
+    pub(super) fn new(
+        rx: mpsc::Receiver<Request<A>>,
+        tx: mpsc::Sender<Response<A>>,
+        stop_tx: mpsc::Sender<StopReason<A>>,
+        wake: WakeHandle,
+    ) -> ProxyServer<A> {
+        ProxyServer {
+            rx,
+            tx,
+            stop_tx,
+            wake,
+        }
+    }
+
+    /// Report a target stop reason back to the stub thread.
+    ///
+    /// Single threaded targets may report a
+    /// [`SingleThreadStopReason`](crate::stub::SingleThreadStopReason).
+    ///
+    /// Returns `false` if the [`TargetProxy`](super::TargetProxy) has been
+    /// dropped.
+    pub fn report_stop(&self, reason: impl Into<MultiThreadStopReason<A::Usize>>) -> bool {
+        if self.stop_tx.send(reason.into()).is_err() {
+            return false;
+        }
+        // the stub thread will notice the disconnect if this fails
+        let _ = self.wake.wake();
+        true
+    }
+}
+
+/// Emulator-side helper trait used to service requests from a
+/// [`TargetProxy`](super::TargetProxy), using the emulator's own [`Target`]
+/// implementation.
+pub trait ServeProxy: Target + Sized {
+    /// Invoked when the GDB client sends a Ctrl-C interrupt.
+    ///
+    /// The target should pause execution at the next opportunity, and then
+    /// report an appropriate stop reason via [`ProxyServer::report_stop`].
+    ///
+    /// _Suggestion_: If you're unsure which stop reason to report,
+    /// [`BaseStopReason::Signal(Signal::SIGINT)`] is a sensible default.
+    ///
+    /// [`BaseStopReason::Signal(Signal::SIGINT)`]:
+    /// crate::stub::BaseStopReason::Signal
+    fn on_interrupt(&mut self) -> Result<(), Self::Error>;
+
+    /// Service all pending requests from the proxy, without blocking.
+    ///
+    /// This should be called at "safe points" in the emulator's main loop,
+    /// where the emulator's state can be inspected and modified (e.g: between
+    /// frames).
+    fn serve_proxy_requests(
+        &mut self,
+        server: &ProxyServer<Self::Arch>,
+    ) -> Result<(), ServeError<Self::Error>> {
+        loop {
+            match server.rx.try_recv() {
+                Ok(req) => serve_one(self, server, req)?,
+                Err(mpsc::TryRecvError::Empty) => return Ok(()),
+                Err(mpsc::TryRecvError::Disconnected) => return Err(ServeError::Disconnected),
+            }
+        }
+    }
+
+    /// Like [`serve_proxy_requests`](Self::serve_proxy_requests), but blocks
+    /// for up to `timeout` waiting for the first request to arrive.
+    ///
+    /// Useful for servicing requests while the target is stopped, without
+    /// busy-looping.
+    fn serve_proxy_requests_timeout(
+        &mut self,
+        server: &ProxyServer<Self::Arch>,
+        timeout: Duration,
+    ) -> Result<(), ServeError<Self::Error>> {
+        match server.rx.recv_timeout(timeout) {
+            Ok(req) => serve_one(self, server, req)?,
+            Err(mpsc::RecvTimeoutError::Timeout) => return Ok(()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Err(ServeError::Disconnected),
+        }
+        self.serve_proxy_requests(server)
+    }
+}
+
+/// A failed request, along with the fatal error to report to the emulator
+/// (if any).
+struct Failure<E> {
+    remote: RemoteError,
+    fatal: Option<E>,
+}
+
+impl<E> From<TargetError<E>> for Failure<E> {
+    fn from(e: TargetError<E>) -> Failure<E> {
+        let remote = RemoteError::from_target_error(&e);
+        let fatal = match e {
+            TargetError::Fatal(e) => Some(e),
+            _ => None,
+        };
+        Failure { remote, fatal }
+    }
+}
+
+impl<E> From<HostIoError<E>> for Failure<E> {
+    fn from(e: HostIoError<E>) -> Failure<E> {
+        match e {
+            HostIoError::Errno(errno) => Failure {
+                remote: RemoteError::HostIo(errno),
+                fatal: None,
+            },
+            HostIoError::Fatal(e) => Failure {
+                remote: RemoteError::Fatal,
+                fatal: Some(e),
+            },
+        }
+    }
+}
+
+fn serve_one<T: ServeProxy>(
+    target: &mut T,
+    server: &ProxyServer<T::Arch>,
+    req: Request<T::Arch>,
+) -> Result<(), ServeError<T::Error>> {
+    let (res, fatal) = match handle_request(target, req) {
+        Ok(reply) => (Ok(reply), None),
+        Err(Failure { remote, fatal }) => (Err(remote), fatal),
+    };
+
+    server.tx.send(res).map_err(|_| ServeError::Disconnected)?;
+
+    match fatal {
+        Some(e) => Err(ServeError::Target(e)),
+        None => Ok(()),
+    }
+}
+
+fn handle_request<T: ServeProxy>(
+    target: &mut T,
+    req: Request<T::Arch>,
+) -> Result<Reply<T::Arch>, Failure<T::Error>> {
+    let reply = match req {
+        Request::ReadRegisters(tid) => {
+            let mut regs: <T::Arch as Arch>::Registers = Default::default();
+            match target.base_ops() {
+                BaseOps::SingleThread(ops) => ops.read_registers(&mut regs),
+                BaseOps::MultiThread(ops) => ops.read_registers(&mut regs, tid),
+            }?;
+            Reply::Registers(regs)
+        }
+        Request::WriteRegisters(regs, tid) => {
+            match target.base_ops() {
+                BaseOps::SingleThread(ops) => ops.write_registers(&regs),
+                BaseOps::MultiThread(ops) => ops.write_registers(&regs, tid),
+            }?;
+            Reply::Unit
+        }
+        Request::ReadRegister(tid, reg_id, len) => {
+            let mut buf = vec![0; len];
+            let n = match target.base_ops() {
+                BaseOps::SingleThread(ops) => match ops.support_single_register_access() {
+                    Some(ops) => ops.read_register((), reg_id, &mut buf),
+                    None => Err(TargetError::NonFatal),
+                },
+                BaseOps::MultiThread(ops) => match ops.support_single_register_access() {
+                    Some(ops) => ops.read_register(tid, reg_id, &mut buf),
+                    None => Err(TargetError::NonFatal),
+                },
+            }?;
+            buf.truncate(n);
+            Reply::Bytes(buf)
+        }
+        Request::WriteRegister(tid, reg_id, val) => {
+            match target.base_ops() {
+                BaseOps::SingleThread(ops) => match ops.support_single_register_access() {
+                    Some(ops) => ops.write_register((), reg_id, &val),
+                    None => Err(TargetError::NonFatal),
+                },
+                BaseOps::MultiThread(ops) => match ops.support_single_register_access() {
+                    Some(ops) => ops.write_register(tid, reg_id, &val),
+                    None => Err(TargetError::NonFatal),
+                },
+            }?;
+            Reply::Unit
+        }
+        Request::ReadAddrs(addr, len, tid) => {
+            let mut buf = vec![0; len];
+            match target.base_ops() {
+                BaseOps::SingleThread(ops) => ops.read_addrs(addr, &mut buf),
+                BaseOps::MultiThread(ops) => ops.read_addrs(addr, &mut buf, tid),
+            }?;
+            Reply::Bytes(buf)
+        }
+        Request::WriteAddrs(addr, data, tid) => {
+            match target.base_ops() {
+                BaseOps::SingleThread(ops) => ops.write_addrs(addr, &data),
+                BaseOps::MultiThread(ops) => ops.write_addrs(addr, &data, tid),
+            }?;
+            Reply::Unit
+        }
+        Request::ListThreads => {
+            let mut tids = Vec::new();
+            match target.base_ops() {
+                BaseOps::SingleThread(_) => tids.push(SINGLE_THREAD_TID),
+                BaseOps::MultiThread(ops) => ops
+                    .list_active_threads(&mut |tid| tids.push(tid))
+                    .map_err(TargetError::Fatal)?,
+            }
+            Reply::Threads(tids)
+        }
+        Request::ListThreadsFrom(cursor) => {
+            let ops = match target.base_ops() {
+                BaseOps::MultiThread(ops) => ops.support_list_cursor(),
+                BaseOps::SingleThread(_) => None,
+            }
+            .ok_or(TargetError::NonFatal)?;
+
+            let mut tids = Vec::new();
+            let next_cursor = ops
+                .list_active_threads_from(cursor, &mut |tid| {
+                    if tids.len() == THREAD_PAGE_LEN {
+                        return false;
+                    }
+                    tids.push(tid);
+                    true
+                })
+                .map_err(TargetError::Fatal)?;
+            Reply::ThreadPage(tids, next_cursor)
+        }
+        Request::Resume(actions) => {
+            match target.base_ops().resume_ops() {
+                None => return Err(TargetError::NonFatal.into()),
+                Some(ResumeOps::SingleThread(ops)) => {
+                    // threads without an explicit action default to continuing
+                    let action = actions
+                        .into_iter()
+                        .find(|(tid, _)| *tid == SINGLE_THREAD_TID)
+                        .map(|(_, action)| action)
+                        .unwrap_or(ResumeAction::Continue(None));
+
+                    match action {
+                        ResumeAction::Continue(signal) => ops.resume(signal),
+                        ResumeAction::Step(signal) => match ops.support_single_step() {
+                            Some(ops) => ops.step(signal),
+                            None => return Err(TargetError::NonFatal.into()),
+                        },
+                        ResumeAction::RangeStep(start, end) => match ops.support_range_step() {
+                            Some(ops) => ops.resume_range_step(start, end),
+                            None => return Err(TargetError::NonFatal.into()),
+                        },
+                    }
+                    .map_err(TargetError::Fatal)?;
+                }
+                Some(ResumeOps::MultiThread(ops)) => {
+                    ops.clear_resume_actions().map_err(TargetError::Fatal)?;
+                    for (tid, action) in actions {
+                        match action {
+                            ResumeAction::Continue(signal) => {
+                                ops.set_resume_action_continue(tid, signal)
+                            }
+                            ResumeAction::Step(signal) => match ops.support_single_step() {
+                                Some(ops) => ops.set_resume_action_step(tid, signal),
+                                None => return Err(TargetError::NonFatal.into()),
+                            },
+                            ResumeAction::RangeStep(start, end) => match ops.support_range_step() {
+                                Some(ops) => ops.set_resume_action_range_step(tid, start, end),
+                                None => return Err(TargetError::NonFatal.into()),
+                            },
+                        }
+                        .map_err(TargetError::Fatal)?;
+                    }
+                    ops.resume().map_err(TargetError::Fatal)?;
+                }
+            }
+            Reply::Unit
+        }
+        Request::ReverseCont => {
+            match target.base_ops().resume_ops() {
+                Some(ResumeOps::SingleThread(ops)) => {
+                    ops.support_reverse_cont().map(|ops| ops.reverse_cont())
+                }
+                Some(ResumeOps::MultiThread(ops)) => {
+                    ops.support_reverse_cont().map(|ops| ops.reverse_cont())
+                }
+                None => None,
+            }
+            .ok_or(TargetError::NonFatal)?
+            .map_err(TargetError::Fatal)?;
+            Reply::Unit
+        }
+        Request::ReverseStep(tid) => {
+            match target.base_ops().resume_ops() {
+                Some(ResumeOps::SingleThread(ops)) => {
+                    ops.support_reverse_step().map(|ops| ops.reverse_step(()))
+                }
+                Some(ResumeOps::MultiThread(ops)) => {
+                    ops.support_reverse_step().map(|ops| ops.reverse_step(tid))
+                }
+                None => None,
+            }
+            .ok_or(TargetError::NonFatal)?
+            .map_err(TargetError::Fatal)?;
+            Reply::Unit
+        }
+        Request::SetResumeAddr(tid, addr) => {
+            match target.base_ops().resume_ops() {
+                Some(ResumeOps::SingleThread(ops)) => ops
+                    .support_resume_at_addr()
+                    .map(|ops| ops.set_resume_addr(addr)),
+                Some(ResumeOps::MultiThread(ops)) => ops
+                    .support_resume_at_addr()
+                    .map(|ops| ops.set_resume_addr(tid, addr)),
+                None => None,
+            }
+            .ok_or(TargetError::NonFatal)??;
+            Reply::Unit
+        }
+        Request::Interrupt => {
+            target.on_interrupt().map_err(TargetError::Fatal)?;
+            Reply::Unit
+        }
+        Request::SwBreakpoint(add, addr, kind) => {
+            let ops = target
+                .support_breakpoints()
+                .and_then(|ops| ops.support_sw_breakpoint())
+                .ok_or(TargetError::NonFatal)?;
+            Reply::Bool(if add {
+                ops.add_sw_breakpoint(addr, kind)?
+            } else {
+                ops.remove_sw_breakpoint(addr, kind)?
+            })
+        }
+        Request::HwBreakpoint(add, addr, kind) => {
+            let ops = target
+                .support_breakpoints()
+                .and_then(|ops| ops.support_hw_breakpoint())
+                .ok_or(TargetError::NonFatal)?;
+            Reply::Bool(if add {
+                ops.add_hw_breakpoint(addr, kind)?
+            } else {
+                ops.remove_hw_breakpoint(addr, kind)?
+            })
+        }
+        Request::HwWatchpoint(add, addr, len, kind) => {
+            let ops = target
+                .support_breakpoints()
+                .and_then(|ops| ops.support_hw_watchpoint())
+                .ok_or(TargetError::NonFatal)?;
+            Reply::Bool(if add {
+                ops.add_hw_watchpoint(addr, len, kind)?
+            } else {
+                ops.remove_hw_watchpoint(addr, len, kind)?
+            })
+        }
+        Request::ExtendedMode(req) => {
+            let ops = target
+                .support_extended_mode()
+                .ok_or(TargetError::NonFatal)?;
+            handle_extended_mode::<T>(ops, req)?
+        }
+        Request::HostIo(req) => {
+            let ops = target.support_host_io().ok_or(TargetError::NonFatal)?;
+            handle_host_io::<T>(ops, req)?
+        }
+        Request::EnableCatchSyscalls(filter) => {
+            let ops = target
+                .support_catch_syscalls()
+                .ok_or(TargetError::NonFatal)?;
+            match filter {
+                Some(filter) => ops.enable_catch_syscalls(Some(SyscallNumbers {
+                    inner: &mut filter.into_iter(),
+                })),
+                None => ops.enable_catch_syscalls(None),
+            }?;
+            Reply::Unit
+        }
+        Request::DisableCatchSyscalls => {
+            let ops = target
+                .support_catch_syscalls()
+                .ok_or(TargetError::NonFatal)?;
+            ops.disable_catch_syscalls()?;
+            Reply::Unit
+        }
+        Request::MonitorCmd(cmd) => {
+            let ops = target.support_monitor_cmd().ok_or(TargetError::NonFatal)?;
+            let mut output = Vec::new();
+            let mut callback = |data: &[u8]| output.extend_from_slice(data);
+            ops.handle_monitor_cmd(&cmd, ConsoleOutput::new(&mut callback))
+                .map_err(TargetError::Fatal)?;
+            Reply::Bytes(output)
+        }
+        Request::SectionOffsets => {
+            let ops = target
+                .support_section_offsets()
+                .ok_or(TargetError::NonFatal)?;
+            Reply::Offsets(ops.get_section_offsets().map_err(TargetError::Fatal)?)
+        }
+        Request::ReadDocument(doc, offset, length) => {
+            let mut buf = vec![0; length];
+            let n = match doc {
+                Document::MemoryMap => target
+                    .support_memory_map()
+                    .ok_or(TargetError::NonFatal)?
+                    .memory_map_xml(offset, length, &mut buf),
+                Document::TargetDescriptionXml(annex) => target
+                    .support_target_description_xml_override()
+                    .ok_or(TargetError::NonFatal)?
+                    .target_description_xml(&annex, offset, length, &mut buf),
+                Document::ExecFile(pid) => target
+                    .support_exec_file()
+                    .ok_or(TargetError::NonFatal)?
+                    .get_exec_file(pid, offset, length, &mut buf),
+                Document::Auxv => target
+                    .support_auxv()
+                    .ok_or(TargetError::NonFatal)?
+                    .get_auxv(offset, length, &mut buf),
+            }?;
+            buf.truncate(n);
+            Reply::Bytes(buf)
+        }
+    };
+
+    Ok(reply)
+}
+
+fn handle_extended_mode<T: ServeProxy>(
+    ops: ExtendedModeOps<'_, T>,
+    req: ExtendedModeRequest,
+) -> Result<Reply<T::Arch>, Failure<T::Error>> {
+    let reply = match req {
+        ExtendedModeRequest::Run(filename, args) => {
+            let mut args = args.iter().map(|arg| arg.as_slice());
+            Reply::Pid(ops.run(filename.as_deref(), Args::new(&mut args))?)
+        }
+        ExtendedModeRequest::Attach(pid) => {
+            ops.attach(pid)?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::QueryIfAttached(pid) => {
+            Reply::Bool(ops.query_if_attached(pid)?.was_attached())
+        }
+        ExtendedModeRequest::Kill(pid) => Reply::Bool(ops.kill(pid)?.into_bool()),
+        ExtendedModeRequest::Restart => {
+            ops.restart().map_err(TargetError::Fatal)?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::OnStart => {
+            ops.on_start().map_err(TargetError::Fatal)?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::ConfigureAslr(enabled) => {
+            let ops = ops.support_configure_aslr().ok_or(TargetError::NonFatal)?;
+            ops.cfg_aslr(enabled)?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::SetEnv(key, val) => {
+            let ops = ops.support_configure_env().ok_or(TargetError::NonFatal)?;
+            ops.set_env(&key, val.as_deref())?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::RemoveEnv(key) => {
+            let ops = ops.support_configure_env().ok_or(TargetError::NonFatal)?;
+            ops.remove_env(&key)?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::ResetEnv => {
+            let ops = ops.support_configure_env().ok_or(TargetError::NonFatal)?;
+            ops.reset_env()?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::ConfigureStartupShell(enabled) => {
+            let ops = ops
+                .support_configure_startup_shell()
+                .ok_or(TargetError::NonFatal)?;
+            ops.cfg_startup_with_shell(enabled)?;
+            Reply::Unit
+        }
+        ExtendedModeRequest::ConfigureWorkingDir(dir) => {
+            let ops = ops
+                .support_configure_working_dir()
+                .ok_or(TargetError::NonFatal)?;
+            ops.cfg_working_dir(dir.as_deref())?;
+            Reply::Unit
+        }
+    };
+
+    Ok(reply)
+}
+
+fn handle_host_io<T: ServeProxy>(
+    ops: HostIoOps<'_, T>,
+    req: HostIoRequest<<T::Arch as Arch>::Usize>,
+) -> Result<Reply<T::Arch>, Failure<T::Error>> {
+    let reply = match req {
+        HostIoRequest::Open(filename, flags, mode) => {
+            let ops = ops.support_open().ok_or(TargetError::NonFatal)?;
+            Reply::Fd(ops.open(&filename, flags, mode)?)
+        }
+        HostIoRequest::Close(fd) => {
+            let ops = ops.support_close().ok_or(TargetError::NonFatal)?;
+            ops.close(fd)?;
+            Reply::Unit
+        }
+        HostIoRequest::Pread(fd, count, offset) => {
+            let ops = ops.support_pread().ok_or(TargetError::NonFatal)?;
+            let mut buf = vec![0; count];
+            let n = ops.pread(fd, count, offset, &mut buf)?;
+            buf.truncate(n);
+            Reply::Bytes(buf)
+        }
+        HostIoRequest::Pwrite(fd, offset, data) => {
+            let ops = ops.support_pwrite().ok_or(TargetError::NonFatal)?;
+            Reply::Usize(ops.pwrite(fd, offset, &data)?)
+        }
+        HostIoRequest::Fstat(fd) => {
+            let ops = ops.support_fstat().ok_or(TargetError::NonFatal)?;
+            Reply::Stat(ops.fstat(fd)?)
+        }
+        HostIoRequest::Unlink(filename) => {
+            let ops = ops.support_unlink().ok_or(TargetError::NonFatal)?;
+            ops.unlink(&filename)?;
+            Reply::Unit
+        }
+        HostIoRequest::Readlink(filename, len) => {
+            let ops = ops.support_readlink().ok_or(TargetError::NonFatal)?;
+            let mut buf = vec![0; len];
+            let n = ops.readlink(&filename, &mut buf)?;
+            buf.truncate(n);
+            Reply::Bytes(buf)
+        }
+        HostIoRequest::Setfs(fs) => {
+            let ops = ops.support_setfs().ok_or(TargetError::NonFatal)?;
+            ops.setfs(fs)?;
+            Reply::Unit
+        }
+    };
+
+    Ok(reply)
+}
@@ -7,3 +7,5 @@ pub mod managed_vec;
 pub mod no_panic_iter;
 
 pub(crate) mod dead_code_marker;
+#[cfg(all(feature = "std", unix))]
+pub(crate) mod poll;
@@ -0,0 +1,80 @@
+//! A minimal wrapper around `poll(2)`, used to wait for fds to become
+//! readable without pulling in the entire `libc` crate.
+
+use std::convert::TryInto;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+// Define some libc types inline (to avoid bringing in entire libc dep)
+
+// every platform supported by the libc crate uses c_int = i32, c_short = i16
+#[allow(non_camel_case_types)]
+type c_int = i32;
+#[allow(non_camel_case_types)]
+type c_short = i16;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(non_camel_case_types)]
+type nfds_t = usize; // c_ulong
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[allow(non_camel_case_types)]
+type nfds_t = u32; // c_uint
+
+const POLLIN: c_short = 0x1;
+const POLLERR: c_short = 0x8;
+const POLLHUP: c_short = 0x10;
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct pollfd {
+    fd: c_int,
+    events: c_short,
+    revents: c_short,
+}
+
+extern "C" {
+    fn poll(fds: *mut pollfd, nfds: nfds_t, timeout: c_int) -> c_int;
+}
+
+/// Wait until any of the provided fds is readable, returning which of them
+/// are ready.
+///
+/// A `timeout` of `None` blocks indefinitely, while `Some(Duration::ZERO)`
+/// returns immediately. Hangups / errors are reported as "readable", so that a
+/// subsequent read surfaces EOF / the underlying error.
+pub(crate) fn poll_readable<const N: usize>(
+    fds: [RawFd; N],
+    timeout: Option<Duration>,
+) -> io::Result<[bool; N]> {
+    let timeout_ms = match timeout {
+        None => -1,
+        // round up, so that sub-millisecond timeouts don't turn into a busy-loop
+        Some(t) => t
+            .as_nanos()
+            .div_ceil(1_000_000)
+            .try_into()
+            .unwrap_or(c_int::MAX),
+    };
+
+    let mut pfds = fds.map(|fd| pollfd {
+        fd,
+        events: POLLIN,
+        revents: 0,
+    });
+
+    loop {
+        // SAFETY: `pfds` is a valid array of `pollfd`s, whose length is passed
+        // alongside it.
+        let ret = unsafe { poll(pfds.as_mut_ptr(), pfds.len() as nfds_t, timeout_ms) };
+        if ret >= 0 {
+            break;
+        }
+
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+
+    Ok(pfds.map(|pfd| pfd.revents & (POLLIN | POLLHUP | POLLERR) != 0))
+}